@@ -1,5 +1,6 @@
 use crate::IndexOrName;
 use log::*;
+use schemars::{schema::RootSchema, JsonSchema};
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use std::collections::HashSet;
@@ -9,15 +10,35 @@ use std::{
     io::{self, Write},
     path::{Path, PathBuf},
 };
-use vimwiki::{HtmlConfig, HtmlWikiConfig, Language, Page};
+use vimwiki::{HtmlConfig, HtmlWikiConfig, Language, Page, PageMetadata};
 use walkdir::WalkDir;
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct Ast {
+    /// Version of the serialized form of the ast, incremented whenever a
+    /// change is made that would break existing consumers
+    pub format_version: u32,
     pub wikis: Vec<Wiki>,
 }
 
+impl Default for Ast {
+    fn default() -> Self {
+        Self {
+            format_version: Self::FORMAT_VERSION,
+            wikis: Vec::new(),
+        }
+    }
+}
+
 impl Ast {
+    /// Current version of the serialized form of the ast
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Returns the json schema describing the serialized form of the ast
+    pub fn json_schema() -> RootSchema {
+        schemars::schema_for!(Ast)
+    }
+
     pub fn load(
         config: &HtmlConfig,
         include: &[IndexOrName],
@@ -84,7 +105,7 @@ impl Ast {
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize, JsonSchema)]
 pub struct Wiki {
     pub index: usize,
     pub name: Option<String>,
@@ -92,10 +113,15 @@ pub struct Wiki {
     pub files: Vec<WikiFile>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct WikiFile {
     pub path: PathBuf,
     pub checksum: String,
+
+    /// Metadata declared by the placeholders of the page
+    #[serde(default)]
+    pub metadata: PageMetadata,
+
     pub data: Page<'static>,
 }
 
@@ -318,6 +344,7 @@ fn load_wiki_file(
     Ok(WikiFile {
         path: path.to_path_buf(),
         checksum,
+        metadata: page.metadata(),
         data: page,
     })
 }
@@ -326,6 +353,54 @@ fn load_wiki_file(
 mod tests {
     use super::*;
 
+    mod ast {
+        use super::*;
+
+        #[test]
+        fn serialize_should_include_format_version() {
+            let value = serde_json::to_value(Ast::default()).unwrap();
+            assert_eq!(value["format_version"], Ast::FORMAT_VERSION);
+        }
+
+        #[test]
+        fn json_schema_should_require_format_version() {
+            let schema = serde_json::to_value(Ast::json_schema()).unwrap();
+            let required = schema["required"].as_array().unwrap();
+            assert!(required.contains(&"format_version".into()));
+            assert_eq!(
+                schema["properties"]["format_version"]["type"],
+                "integer"
+            );
+        }
+
+        #[test]
+        fn json_schema_should_describe_serialized_type_tags() {
+            use vimwiki::{Description, ListItemSuffix, Placeholder};
+
+            let schema = serde_json::to_value(Ast::json_schema()).unwrap();
+            for (name, value) in [
+                ("Description", serde_json::to_value(Description::from("d"))),
+                (
+                    "ListItemSuffix",
+                    serde_json::to_value(ListItemSuffix::Paren),
+                ),
+                ("Placeholder", serde_json::to_value(Placeholder::NoHtml)),
+            ] {
+                let tag = &value.unwrap()["type"];
+                let variants =
+                    schema["definitions"][name]["oneOf"].as_array().unwrap();
+                assert!(
+                    variants
+                        .iter()
+                        .any(|x| &x["properties"]["type"]["enum"][0] == tag),
+                    "{} is missing {}",
+                    name,
+                    tag
+                );
+            }
+        }
+    }
+
     mod wiki_file {
         use super::*;
         use indoc::indoc;
@@ -392,6 +467,32 @@ mod tests {
             .expect("Failed to load file");
         }
 
+        #[test]
+        fn load_wiki_file_should_extract_metadata_with_or_without_cache() {
+            let file = make_file(indoc! {"
+                %title Some Title
+                %nohtml
+                %author Bob
+                %tags a b
+            "});
+            let cache = make_cache_dir();
+
+            for _ in 0..2 {
+                let metadata = WikiFile::load(
+                    file.as_ref(),
+                    cache.as_ref(),
+                    /* no_cache */ false,
+                )
+                .expect("Failed to load file")
+                .metadata;
+
+                assert_eq!(metadata.title.as_deref(), Some("Some Title"));
+                assert!(metadata.nohtml);
+                assert_eq!(metadata.get("author"), Some("Bob"));
+                assert_eq!(metadata.get_list("tags"), vec!["a", "b"]);
+            }
+        }
+
         #[test]
         fn load_wiki_file_should_support_definition_lists() {
             let file = make_file(indoc! {"
@@ -46,6 +46,10 @@ pub fn run(opt: Opt) -> Result<(), ExitCodes> {
                 load_html_config_and_ast(&opt.common, &cmd.extra_paths)?;
             subcommand::convert(cmd, opt.common, config, ast)
         }
+        Subcommand::Css(cmd) => {
+            let config = load_html_config(&opt.common, &[])?;
+            subcommand::css(cmd, opt.common, config)
+        }
         Subcommand::Format(cmd) => {
             let config = load_format_config(&opt.common)?;
             subcommand::format(cmd, opt.common, config)
@@ -60,6 +64,12 @@ pub fn run(opt: Opt) -> Result<(), ExitCodes> {
                 load_html_config_and_ast(&opt.common, &cmd.extra_paths)?;
             subcommand::inspect(cmd, opt.common, config, ast)
         }
+        Subcommand::Schema(cmd) => subcommand::schema(cmd, opt.common),
+        Subcommand::Lsp(cmd) => {
+            let config = load_html_config(&opt.common, &cmd.extra_paths)?;
+            let format_config = load_format_config(&opt.common)?;
+            subcommand::lsp(cmd, opt.common, config, format_config)
+        }
     };
 
     #[cfg(feature = "timekeeper")]
@@ -87,17 +97,21 @@ fn load_format_config(opt: &CommonOpt) -> Result<VimwikiConfig, ExitCodes> {
     }
 }
 
+fn load_html_config(
+    opt: &CommonOpt,
+    extra_paths: &[PathBuf],
+) -> Result<HtmlConfig, ExitCodes> {
+    utils::load_html_config(opt, extra_paths).map_err(|x| {
+        error!("Failed to load config: {}", x);
+        ExitCodes::FailedToLoadConfig
+    })
+}
+
 fn load_html_config_and_ast(
     opt: &CommonOpt,
     extra_paths: &[PathBuf],
 ) -> Result<(HtmlConfig, Ast), ExitCodes> {
-    let config = match utils::load_html_config(opt, extra_paths) {
-        Ok(config) => config,
-        Err(x) => {
-            error!("Failed to load config: {}", x);
-            return Err(ExitCodes::FailedToLoadConfig);
-        }
-    };
+    let config = load_html_config(opt, extra_paths)?;
 
     let ast = match Ast::load(
         &config,
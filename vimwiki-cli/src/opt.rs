@@ -1,3 +1,4 @@
+use crate::subcommand::{Glob, MetadataPattern};
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
 use std::path::PathBuf;
@@ -89,8 +90,11 @@ impl CommonOpt {
 #[derive(Debug, StructOpt)]
 pub enum Subcommand {
     Convert(ConvertSubcommand),
+    Css(CssSubcommand),
     Format(FormatSubcommand),
     Inspect(InspectSubcommand),
+    Lsp(LspSubcommand),
+    Schema(SchemaSubcommand),
     Serve(ServeSubcommand),
 }
 
@@ -99,8 +103,11 @@ impl Subcommand {
     pub fn extra_paths(&self) -> &[PathBuf] {
         match self {
             Self::Convert(x) => &x.extra_paths,
+            Self::Css(_) => &[],
             Self::Format(x) => &x.paths,
             Self::Inspect(x) => &x.extra_paths,
+            Self::Lsp(x) => &x.extra_paths,
+            Self::Schema(_) => &[],
             Self::Serve(x) => &x.extra_paths,
         }
     }
@@ -118,6 +125,54 @@ pub struct ConvertSubcommand {
     #[structopt(long)]
     pub include_vimwiki_css: bool,
 
+    /// Only converts pages whose path within their wiki matches one of these
+    /// globs such as `blog/**`, where a glob without `/` such as `*.wiki`
+    /// matches the file name of pages in any directory
+    #[structopt(long = "include-path", number_of_values = 1)]
+    pub include_paths: Vec<Glob>,
+
+    /// Skips pages whose path within their wiki matches any of these globs
+    #[structopt(long = "exclude-path", number_of_values = 1)]
+    pub exclude_paths: Vec<Glob>,
+
+    /// Only converts pages with one of these tags, either as `:tag:` or
+    /// within a `%tags` placeholder
+    #[structopt(long = "include-tag", number_of_values = 1)]
+    pub include_tags: Vec<String>,
+
+    /// Skips pages with any of these tags, either as `:tag:` or within a
+    /// `%tags` placeholder
+    #[structopt(long = "exclude-tag", number_of_values = 1)]
+    pub exclude_tags: Vec<String>,
+
+    /// Only converts pages with a placeholder matching one of these, where
+    /// `NAME` matches a placeholder such as `%public` whose value is not false
+    /// and `NAME=VALUE` matches a placeholder with the value (or list item)
+    #[structopt(long = "include-meta", number_of_values = 1)]
+    pub include_metadata: Vec<MetadataPattern>,
+
+    /// Skips pages with a placeholder matching any of these, such as
+    /// `draft` to skip pages with `%draft yes`
+    #[structopt(long = "exclude-meta", number_of_values = 1)]
+    pub exclude_metadata: Vec<MetadataPattern>,
+
+    /// Copies local files referenced by transclusions and `file:` or
+    /// `local:` links into the output directory of their wiki, mirroring
+    /// their location within the wiki, and points links to the copies
+    #[structopt(long)]
+    pub copy_assets: bool,
+
+    /// Like `--copy-assets`, but names the copies by the hash of their
+    /// content within the `assets` directory of the output (or the
+    /// `hashed_dir` of the assets config)
+    #[structopt(long)]
+    pub hash_assets: bool,
+
+    /// Also copies assets outside of the root of their wiki, such as
+    /// `[[file:/path/to/file]]`, which are skipped otherwise
+    #[structopt(long)]
+    pub allow_outside_assets: bool,
+
     /// Additional standalone files (or directories) to process
     #[structopt(name = "PATH", parse(from_os_str))]
     pub extra_paths: Vec<PathBuf>,
@@ -163,9 +218,55 @@ pub struct InspectSubcommand {
     #[structopt(short, long)]
     pub output: Option<PathBuf>,
 
-    /// JSON path to use for inspection
-    #[structopt(name = "JSONPATH")]
-    pub json_path: String,
+    /// If specified, QUERY is a selector over elements such as
+    /// `header[level=2] > link` or `list_item[todo=incomplete]`, outputting
+    /// each matching element alongside its file and line/column positions
+    #[structopt(short, long)]
+    pub select: bool,
+
+    /// JSON path to use for inspection, or a selector if `--select` is
+    /// specified
+    #[structopt(name = "QUERY")]
+    pub query: String,
+
+    /// Additional standalone files (or directories) to process
+    #[structopt(name = "PATH", parse(from_os_str))]
+    pub extra_paths: Vec<PathBuf>,
+}
+
+/// Print the stylesheet for code highlighted server-side using css classes
+#[derive(Debug, StructOpt)]
+pub struct CssSubcommand {
+    /// Name of the theme to produce styles for, defaulting to the theme
+    /// within the html code config
+    #[structopt(long)]
+    pub theme: Option<String>,
+
+    /// Directory containing `.tmTheme` files to load the theme from,
+    /// defaulting to the theme directory within the html code config
+    #[structopt(long, parse(from_os_str))]
+    pub theme_dir: Option<PathBuf>,
+
+    /// Writes to output file instead of stdout
+    #[structopt(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Print the json schema describing the output of inspect
+#[derive(Debug, StructOpt)]
+pub struct SchemaSubcommand {
+    /// Writes to output file instead of stdout
+    #[structopt(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Run a language server for vimwiki files that communicates over stdio
+#[derive(Debug, StructOpt)]
+pub struct LspSubcommand {
+    /// Communicate over stdio, which is the default and only transport;
+    /// accepted as many clients pass it when starting a server
+    #[structopt(long)]
+    pub stdio: bool,
 
     /// Additional standalone files (or directories) to process
     #[structopt(name = "PATH", parse(from_os_str))]
@@ -227,3 +328,15 @@ impl std::str::FromStr for IndexOrName {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lsp_subcommand_should_accept_stdio_flag() {
+        let cmd = LspSubcommand::from_iter_safe(&["lsp", "--stdio"]).unwrap();
+        assert!(cmd.stdio);
+        assert!(cmd.extra_paths.is_empty());
+    }
+}
@@ -0,0 +1,407 @@
+use crate::ConvertSubcommand;
+use std::{
+    fmt,
+    path::{Component, Path},
+    str::FromStr,
+};
+use vimwiki::{
+    Element, InlineElement, IntoChildren, Located, Page, PageMetadata,
+};
+
+/// Determines which pages are published when converting, where a page is
+/// published unless it has `%nohtml` or fails any of the configured filters
+#[derive(Debug, Default)]
+pub struct PublishFilter {
+    include_paths: Vec<Glob>,
+    exclude_paths: Vec<Glob>,
+    include_tags: Vec<String>,
+    exclude_tags: Vec<String>,
+    include_metadata: Vec<MetadataPattern>,
+    exclude_metadata: Vec<MetadataPattern>,
+}
+
+impl PublishFilter {
+    pub fn from_cmd(cmd: &ConvertSubcommand) -> Self {
+        Self {
+            include_paths: cmd.include_paths.clone(),
+            exclude_paths: cmd.exclude_paths.clone(),
+            include_tags: cmd.include_tags.clone(),
+            exclude_tags: cmd.exclude_tags.clone(),
+            include_metadata: cmd.include_metadata.clone(),
+            exclude_metadata: cmd.exclude_metadata.clone(),
+        }
+    }
+
+    /// Returns the reason the page should not be published, or none if it
+    /// should be published, where the path is that of the page within its
+    /// wiki
+    pub fn skip_reason(
+        &self,
+        path: &Path,
+        page: &Page,
+        metadata: &PageMetadata,
+    ) -> Option<String> {
+        if metadata.nohtml {
+            return Some("has %nohtml".to_string());
+        }
+
+        let path = to_slash_path(path);
+        if !self.include_paths.is_empty()
+            && !self.include_paths.iter().any(|x| x.matches(&path))
+        {
+            return Some("not matched by any included path".to_string());
+        }
+        if let Some(glob) = self.exclude_paths.iter().find(|x| x.matches(&path))
+        {
+            return Some(format!("matched by excluded path {}", glob));
+        }
+
+        if !self.include_tags.is_empty() || !self.exclude_tags.is_empty() {
+            let tags = collect_tags(page, metadata);
+            if !self.include_tags.is_empty()
+                && !self.include_tags.iter().any(|x| tags.contains(x))
+            {
+                return Some("has no included tag".to_string());
+            }
+            if let Some(tag) =
+                self.exclude_tags.iter().find(|x| tags.contains(x))
+            {
+                return Some(format!("has excluded tag {}", tag));
+            }
+        }
+
+        if !self.include_metadata.is_empty()
+            && !self.include_metadata.iter().any(|x| x.matches(metadata))
+        {
+            return Some("has no included metadata".to_string());
+        }
+        if let Some(pattern) =
+            self.exclude_metadata.iter().find(|x| x.matches(metadata))
+        {
+            return Some(format!("has excluded metadata {}", pattern));
+        }
+
+        None
+    }
+}
+
+/// Converts a relative path into a string using `/` to separate components
+fn to_slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|x| match x {
+            Component::Normal(x) => Some(x.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Collects the tags within the page such as `:draft:` alongside those
+/// listed by a `%tags` placeholder
+fn collect_tags(page: &Page, metadata: &PageMetadata) -> Vec<String> {
+    let mut tags: Vec<String> = metadata
+        .get_list("tags")
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let mut stack: Vec<Located<Element>> = page
+        .to_borrowed()
+        .into_children()
+        .into_iter()
+        .map(|x| x.map(Element::from))
+        .collect();
+    while let Some(located) = stack.pop() {
+        if let Element::Inline(InlineElement::Tags(x)) = located.as_inner() {
+            tags.extend(x.iter().map(|x| x.as_str().to_string()));
+        }
+        stack.extend(located.into_inner().into_children());
+    }
+
+    tags
+}
+
+/// Represents a pattern such as `blog/**/*.wiki` matched against the path of
+/// a page within its wiki, where `*` matches anything but `/`, `**` matches
+/// anything including `/`, and `?` matches any one character but `/`
+///
+/// A pattern without a `/` is matched against the file name of the page
+/// regardless of the directory containing it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    tokens: Vec<GlobToken>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum GlobToken {
+    Char(char),
+    AnyChar,
+    Any,
+    AnyPath,
+    AnyDirs,
+}
+
+impl Glob {
+    pub fn matches(&self, path: &str) -> bool {
+        let path = if self.pattern.contains('/') {
+            path
+        } else {
+            path.rsplit('/').next().unwrap_or(path)
+        };
+
+        let chars: Vec<char> = path.chars().collect();
+        glob_matches(&self.tokens, &chars)
+    }
+}
+
+fn glob_matches(tokens: &[GlobToken], text: &[char]) -> bool {
+    let (token, rest) = match tokens.split_first() {
+        Some(x) => x,
+        None => return text.is_empty(),
+    };
+
+    match token {
+        GlobToken::Char(c) => {
+            text.first() == Some(c) && glob_matches(rest, &text[1..])
+        }
+        GlobToken::AnyChar => {
+            matches!(text.first(), Some(c) if *c != '/')
+                && glob_matches(rest, &text[1..])
+        }
+        GlobToken::Any => {
+            let end = text.iter().position(|c| *c == '/').unwrap_or(text.len());
+            (0..=end).any(|i| glob_matches(rest, &text[i..]))
+        }
+        GlobToken::AnyPath => {
+            (0..=text.len()).any(|i| glob_matches(rest, &text[i..]))
+        }
+
+        // Matches zero or more complete directories, each ending with `/`
+        GlobToken::AnyDirs => {
+            glob_matches(rest, text)
+                || text
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c == '/')
+                    .any(|(i, _)| glob_matches(rest, &text[i + 1..]))
+        }
+    }
+}
+
+impl FromStr for Glob {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err("Glob cannot be empty".to_string());
+        }
+
+        let mut tokens = Vec::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        GlobToken::AnyDirs
+                    } else {
+                        GlobToken::AnyPath
+                    }
+                }
+                '*' => GlobToken::Any,
+                '?' => GlobToken::AnyChar,
+                c => GlobToken::Char(c),
+            });
+        }
+
+        Ok(Self {
+            pattern: s.to_string(),
+            tokens,
+        })
+    }
+}
+
+impl fmt::Display for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// Represents a pattern of `name` or `name=value` matched against the custom
+/// placeholders of a page, where `name` matches if the placeholder exists
+/// and is not false (such as `%draft` with `no` or `false`), and
+/// `name=value` matches if any value or item of the placeholder is `value`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataPattern {
+    name: String,
+    value: Option<String>,
+}
+
+impl MetadataPattern {
+    pub fn matches(&self, metadata: &PageMetadata) -> bool {
+        match self.value.as_deref() {
+            Some(value) => {
+                metadata
+                    .get_all(&self.name)
+                    .iter()
+                    .any(|x| x.trim() == value)
+                    || metadata.get_list(&self.name).contains(&value)
+            }
+            None => {
+                metadata.contains(&self.name)
+                    && metadata.get_bool(&self.name) != Some(false)
+            }
+        }
+    }
+}
+
+impl FromStr for MetadataPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name, Some(value.trim().to_string())),
+            None => (s, None),
+        };
+
+        let name = name.trim().trim_start_matches('%');
+        if name.is_empty() {
+            return Err(format!("Missing placeholder name in {:?}", s));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            value,
+        })
+    }
+}
+
+impl fmt::Display for MetadataPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.as_deref() {
+            Some(value) => write!(f, "{}={}", self.name, value),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use vimwiki::Language;
+
+    fn glob(s: &str) -> Glob {
+        s.parse().unwrap()
+    }
+
+    fn parse_page(text: &str) -> Page<'static> {
+        Language::from_vimwiki_str(text)
+            .parse::<Page>()
+            .unwrap()
+            .into_owned()
+    }
+
+    fn is_skipped(filter: &PublishFilter, path: &str, text: &str) -> bool {
+        let page = parse_page(text);
+        filter
+            .skip_reason(&PathBuf::from(path), &page, &page.metadata())
+            .is_some()
+    }
+
+    #[test]
+    fn glob_should_support_wildcards() {
+        assert!(glob("blog/*.wiki").matches("blog/post.wiki"));
+        assert!(!glob("blog/*.wiki").matches("blog/2021/post.wiki"));
+        assert!(glob("blog/**/*.wiki").matches("blog/post.wiki"));
+        assert!(glob("blog/**/*.wiki").matches("blog/2021/06/post.wiki"));
+        assert!(glob("blog/**").matches("blog/2021/post.wiki"));
+        assert!(!glob("blog/**").matches("notes/post.wiki"));
+        assert!(glob("post?.wiki").matches("a/b/post1.wiki"));
+        assert!(!glob("post?.wiki").matches("a/b/post10.wiki"));
+        assert!(glob("private*").matches("a/private-notes.wiki"));
+        assert!(!glob("a/private*").matches("b/a/private-notes.wiki"));
+    }
+
+    #[test]
+    fn metadata_pattern_should_match_by_name_or_value() {
+        let page = parse_page("%draft yes\n%public no\n%status wip, later\n");
+        let metadata = page.metadata();
+        let matches =
+            |s: &str| s.parse::<MetadataPattern>().unwrap().matches(&metadata);
+
+        assert!(matches("draft"));
+        assert!(matches("%draft"));
+        assert!(!matches("public"));
+        assert!(!matches("missing"));
+        assert!(matches("public=no"));
+        assert!(matches("status=wip"));
+        assert!(matches("status=later"));
+        assert!(!matches("status=done"));
+        assert!("=value".parse::<MetadataPattern>().is_err());
+    }
+
+    #[test]
+    fn skip_reason_should_skip_pages_with_nohtml() {
+        let filter = PublishFilter::default();
+        assert!(is_skipped(&filter, "a.wiki", "%nohtml\nSome text"));
+        assert!(!is_skipped(&filter, "a.wiki", "Some text"));
+    }
+
+    #[test]
+    fn skip_reason_should_apply_path_filters() {
+        let filter = PublishFilter {
+            include_paths: vec![glob("blog/**")],
+            exclude_paths: vec![glob("*.draft.wiki")],
+            ..Default::default()
+        };
+        assert!(!is_skipped(&filter, "blog/a.wiki", ""));
+        assert!(is_skipped(&filter, "notes/a.wiki", ""));
+        assert!(is_skipped(&filter, "blog/a.draft.wiki", ""));
+    }
+
+    #[test]
+    fn skip_reason_should_apply_tag_filters() {
+        let filter = PublishFilter {
+            exclude_tags: vec!["private".to_string()],
+            ..Default::default()
+        };
+        assert!(is_skipped(
+            &filter,
+            "a.wiki",
+            "= Header =\n- item :private:\n"
+        ));
+        assert!(is_skipped(&filter, "a.wiki", "%tags public private\n"));
+        assert!(!is_skipped(&filter, "a.wiki", ":public:\n"));
+
+        let filter = PublishFilter {
+            include_tags: vec!["public".to_string()],
+            ..Default::default()
+        };
+        assert!(!is_skipped(&filter, "a.wiki", ":public:\n"));
+        assert!(is_skipped(&filter, "a.wiki", "no tags\n"));
+    }
+
+    #[test]
+    fn skip_reason_should_apply_metadata_filters() {
+        let filter = PublishFilter {
+            include_metadata: vec!["status=published".parse().unwrap()],
+            exclude_metadata: vec!["draft".parse().unwrap()],
+            ..Default::default()
+        };
+        assert!(!is_skipped(&filter, "a.wiki", "%status published\n"));
+        assert!(!is_skipped(
+            &filter,
+            "a.wiki",
+            "%status published\n%draft false\n"
+        ));
+        assert!(is_skipped(
+            &filter,
+            "a.wiki",
+            "%status published\n%draft true\n"
+        ));
+        assert!(is_skipped(&filter, "a.wiki", "%status wip\n"));
+    }
+}
@@ -4,12 +4,41 @@ use std::{ffi::OsStr, io, path::Path};
 use vimwiki::*;
 use walkdir::WalkDir;
 
+mod filter;
+use filter::PublishFilter;
+pub use filter::{Glob, MetadataPattern};
+
+/// Settings that apply to every page being converted
+struct ConvertOptions<'a> {
+    cache: &'a Path,
+    no_cache: bool,
+    stdout: bool,
+    filter: PublishFilter,
+}
+
 pub fn convert(
     cmd: ConvertSubcommand,
     opt: CommonOpt,
-    config: HtmlConfig,
+    mut config: HtmlConfig,
     mut ast: Ast,
 ) -> io::Result<()> {
+    if cmd.copy_assets || cmd.hash_assets {
+        config.assets.enabled = true;
+    }
+    if cmd.hash_assets {
+        config.assets.hashed = true;
+    }
+    if cmd.allow_outside_assets {
+        config.assets.allow_outside_wiki = true;
+    }
+
+    let options = ConvertOptions {
+        cache: opt.cache.as_path(),
+        no_cache: opt.no_cache,
+        stdout: cmd.stdout,
+        filter: PublishFilter::from_cmd(&cmd),
+    };
+
     // Process all wikis that match the given filters if we aren't given
     // specific files/wikis to convert
     if cmd.extra_paths.is_empty() {
@@ -22,10 +51,8 @@ pub fn convert(
                 config.clone(),
                 &mut ast,
                 wiki.path.as_path(),
-                opt.cache.as_path(),
-                opt.no_cache,
-                cmd.stdout,
                 &wiki.ext,
+                &options,
             )?;
 
             // If writing to a file, we want to make sure there is a css
@@ -54,10 +81,8 @@ pub fn convert(
             config.clone(),
             &mut ast,
             path.as_path(),
-            opt.cache.as_path(),
-            opt.no_cache,
-            cmd.stdout,
             &HtmlWikiConfig::default_ext(),
+            &options,
         )?;
 
         // If writing to a file, we want to make sure there is a css
@@ -77,15 +102,13 @@ fn process_path(
     config: HtmlConfig,
     ast: &mut Ast,
     input_path: &Path,
-    cache: &Path,
-    no_cache: bool,
-    stdout: bool,
     ext: &str,
+    options: &ConvertOptions,
 ) -> io::Result<()> {
     trace!(
         "process_path(_, input_path = {:?}, stdout = {}, ext = {})",
         input_path,
-        stdout,
+        options.stdout,
         ext
     );
 
@@ -112,14 +135,7 @@ fn process_path(
             rt
         });
 
-        process_file(
-            config,
-            ast,
-            page_path.as_path(),
-            cache,
-            no_cache,
-            stdout,
-        )?;
+        process_file(config, ast, page_path.as_path(), options)?;
     }
 
     Ok(())
@@ -129,52 +145,73 @@ fn process_file(
     config: HtmlConfig,
     ast: &mut Ast,
     input_path: &Path,
-    cache: &Path,
-    no_cache: bool,
-    stdout: bool,
+    options: &ConvertOptions,
 ) -> io::Result<()> {
     trace!(
         "process_file(_, input_path = {:?}, stdout = {})",
         input_path,
-        stdout
+        options.stdout
     );
 
     // Go ahead and figure out the necessary wiki if we need it so that we
     // don't need to clone our entire config later
-    let maybe_wiki = if !stdout {
-        config.find_wiki_by_path(input_path).cloned()
+    let output_path = if !options.stdout {
+        Some(
+            config
+                .find_wiki_by_path(input_path)
+                .cloned()
+                .unwrap_or_default()
+                .make_output_path(input_path, "html"),
+        )
     } else {
         None
     };
 
-    // If we already have a file loaded at this path, use it
-    let html = if let Some(file) = ast.find_file_by_path(input_path) {
+    // If we already have a file loaded at this path, use it, otherwise we
+    // need to load the file
+    if ast.find_file_by_path(input_path).is_some() {
         debug!("{:?} :: loaded from cache!", input_path);
+    } else {
+        ast.load_file(input_path, options.cache, options.no_cache)?;
+    }
+    let file = ast.find_file_by_path(input_path).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "Loaded file is now missing")
+    })?;
 
-        file.data.to_html_page(config).map_err(|x| {
-            io::Error::new(io::ErrorKind::InvalidData, x.to_string())
-        })?
+    // Skip pages that are not to be published, removing any html that was
+    // previously generated for them so it is not left behind
+    let path_in_wiki = config.as_active_page_path_within_wiki();
+    let skip_reason =
+        options
+            .filter
+            .skip_reason(path_in_wiki, &file.data, &file.metadata);
+    if let Some(reason) = skip_reason {
+        info!("{:?} :: skipping as it {}", input_path, reason);
 
-    // Otherwise, we need to load the file
-    } else {
-        let file = ast.load_file(input_path, cache, no_cache)?;
+        if let Some(path) = output_path.filter(|x| x.is_file()) {
+            info!("Removing stale {:?}", path);
+            std::fs::remove_file(path)?;
+        }
 
-        file.data.to_html_page(config).map_err(|x| {
+        return Ok(());
+    }
+
+    let (html, report) =
+        file.data.to_html_page_with_report(config).map_err(|x| {
             io::Error::new(io::ErrorKind::InvalidData, x.to_string())
-        })?
-    };
+        })?;
     debug!("{:?} :: html generated!", input_path);
 
+    for stripped in report.stripped {
+        warn!("{:?} :: sanitized {}", input_path, stripped);
+    }
+
     // If told to print to stdout, do so
-    if stdout {
+    if options.stdout {
         println!("{}", html);
 
     // Otherwise, we generate files based on resolved output paths
-    } else {
-        let path = maybe_wiki
-            .unwrap_or_default()
-            .make_output_path(input_path, "html");
-
+    } else if let Some(path) = output_path {
         info!("Writing to {:?}", path);
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
@@ -182,5 +219,41 @@ fn process_file(
         std::fs::write(path, html)?;
     }
 
+    for asset in report.assets {
+        if !asset.exists() {
+            warn!("{:?} :: missing asset {:?}", input_path, asset.source);
+        } else if !options.stdout {
+            copy_asset(&asset)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies an asset to its location within the html output unless the copy
+/// is already up to date
+fn copy_asset(asset: &HtmlAsset) -> io::Result<()> {
+    if asset.source == asset.output {
+        return Ok(());
+    }
+
+    let is_up_to_date = match (asset.source.metadata(), asset.output.metadata())
+    {
+        (Ok(src), Ok(out)) => {
+            src.len() == out.len() && src.modified()? <= out.modified()?
+        }
+        _ => false,
+    };
+    if is_up_to_date {
+        debug!("{:?} :: asset is up to date", asset.output);
+        return Ok(());
+    }
+
+    info!("Copying {:?} to {:?}", asset.source, asset.output);
+    if let Some(parent) = asset.output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(&asset.source, &asset.output)?;
+
     Ok(())
 }
@@ -0,0 +1,33 @@
+use crate::{CommonOpt, CssSubcommand};
+use std::{fs, io};
+use vimwiki::{highlight_css, HtmlConfig};
+
+pub fn css(
+    cmd: CssSubcommand,
+    _opt: CommonOpt,
+    config: HtmlConfig,
+) -> io::Result<()> {
+    let CssSubcommand {
+        theme,
+        theme_dir,
+        output,
+    } = cmd;
+
+    let mut code = config.code;
+    if let Some(theme) = theme {
+        code.theme = theme;
+    }
+    if let Some(theme_dir) = theme_dir {
+        code.theme_dir = Some(theme_dir);
+    }
+
+    let css = highlight_css(&code)
+        .map_err(|x| io::Error::new(io::ErrorKind::InvalidInput, x))?;
+
+    if let Some(path) = output {
+        fs::write(path, css)
+    } else {
+        print!("{}", css);
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{Ast, CommonOpt, InspectSubcommand};
+use jsonpath_lib as jsonpath;
+use serde::Serialize;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+use vimwiki::{
+    ColumnEncoding, Element, HtmlConfig, LineColumn, LineIndex, Located,
+};
+
+mod selector;
+use selector::{kind_of, ElementTree, SelectorList};
+
+pub fn inspect(
+    cmd: InspectSubcommand,
+    _opt: CommonOpt,
+    _config: HtmlConfig,
+    ast: Ast,
+) -> io::Result<()> {
+    let InspectSubcommand {
+        output,
+        select,
+        query,
+        ..
+    } = cmd;
+
+    let values = if select {
+        select_elements(&ast, query.as_str())?
+    } else {
+        let ast_json = serde_json::to_value(ast).map_err(io::Error::from)?;
+        jsonpath::select(&ast_json, query.as_str())
+            .map_err(|x| {
+                io::Error::new(io::ErrorKind::InvalidData, x.to_string())
+            })?
+            .into_iter()
+            .cloned()
+            .collect()
+    };
+
+    if let Some(path) = output {
+        let file = fs::File::create(path)?;
+        let mut writer = io::BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &values)
+            .map_err(io::Error::from)?;
+        writer.flush()?;
+        Ok(())
+    } else {
+        let stdout = io::stdout();
+        serde_json::to_writer_pretty(stdout, &values).map_err(io::Error::from)
+    }
+}
+
+/// Represents an element matched by a selector
+#[derive(Serialize)]
+struct Match<'a> {
+    wiki: usize,
+    path: &'a Path,
+    kind: &'static str,
+
+    /// One-based line and column (in characters) where the element starts
+    start: Option<Position>,
+
+    /// One-based line and column (in characters) just past the end of the
+    /// element
+    end: Option<Position>,
+
+    element: &'a Located<Element<'a>>,
+}
+
+#[derive(Serialize)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl From<LineColumn> for Position {
+    fn from(x: LineColumn) -> Self {
+        Self {
+            line: x.line + 1,
+            column: x.column + 1,
+        }
+    }
+}
+
+/// Evaluates the selector against every file of the ast, returning each
+/// matching element as json in the order it appears
+fn select_elements(
+    ast: &Ast,
+    selector: &str,
+) -> io::Result<Vec<serde_json::Value>> {
+    let selectors: SelectorList = selector
+        .parse()
+        .map_err(|x| io::Error::new(io::ErrorKind::InvalidInput, x))?;
+
+    let mut values = Vec::new();
+    for wiki in ast.wikis.iter() {
+        for file in wiki.files.iter() {
+            let tree = ElementTree::new(&file.data);
+            let mut index = None;
+
+            for element in tree.select(&selectors) {
+                // Only read the file to figure out positions once something
+                // within it has matched
+                if index.is_none() {
+                    let text = fs::read_to_string(&file.path)?;
+                    index = Some(
+                        LineIndex::new(&text)
+                            .with_encoding(ColumnEncoding::Char),
+                    );
+                }
+                let range =
+                    index.as_ref().and_then(|index| element.line_range(index));
+
+                let (start, end) = match range {
+                    Some(range) => {
+                        (Some(range.start.into()), Some(range.end.into()))
+                    }
+                    None => (None, None),
+                };
+
+                values.push(
+                    serde_json::to_value(Match {
+                        wiki: wiki.index,
+                        path: file.path.as_path(),
+                        kind: kind_of(element.as_inner()),
+                        start,
+                        end,
+                        element,
+                    })
+                    .map_err(io::Error::from)?,
+                );
+            }
+        }
+    }
+
+    Ok(values)
+}
@@ -0,0 +1,903 @@
+use std::{error::Error, fmt, str::FromStr};
+use vimwiki::{
+    BlockElement, DecoratedText, Element, InlineBlockElement, InlineElement,
+    IntoChildren, LinkData, ListItemTodoStatus, Located, Page, Placeholder,
+};
+
+/// Kinds of elements that can be selected, named after the type used when
+/// serializing each element
+const KINDS: &[&str] = &[
+    "blockquote",
+    "code_block",
+    "definition_list",
+    "divider",
+    "header",
+    "list",
+    "math_block",
+    "paragraph",
+    "placeholder",
+    "table",
+    "text",
+    "decorated_text",
+    "keyword",
+    "link",
+    "tags",
+    "code",
+    "math",
+    "comment",
+    "list_item",
+    "term",
+    "definition",
+];
+
+/// Represents one or more selectors separated by commas, matching an element
+/// if any of the selectors match it
+///
+/// Selectors resemble those of CSS, made up of element kinds such as
+/// `header` or `*` for any kind, attribute predicates such as `[level=2]`,
+/// the pseudo-classes `:has(...)` and `:not(...)`, and the combinators
+/// ` ` (descendant), `>` (child), `+` (next sibling) and `~` (subsequent
+/// sibling)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectorList(Vec<Selector>);
+
+/// Represents a chain of compound selectors joined by combinators, matching
+/// an element against the last compound selector
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Selector {
+    compounds: Vec<Compound>,
+
+    /// Combinator between each compound selector and the one before it,
+    /// having one less entry than `compounds`
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+/// Represents an optional kind of element alongside predicates that must
+/// all be true for an element to match
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Compound {
+    kind: Option<&'static str>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Predicate {
+    Attr {
+        name: String,
+        test: Option<(AttrOp, String)>,
+    },
+    Has(SelectorList),
+    Not(SelectorList),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum AttrOp {
+    /// Any value equals (`=`)
+    Equals,
+
+    /// No value equals (`!=`)
+    NotEquals,
+
+    /// Any value contains (`*=`)
+    Contains,
+
+    /// Any value starts with (`^=`)
+    StartsWith,
+
+    /// Any value ends with (`$=`)
+    EndsWith,
+}
+
+impl AttrOp {
+    fn test(self, values: &[String], expected: &str) -> bool {
+        match self {
+            Self::Equals => values.iter().any(|x| x == expected),
+            Self::NotEquals => values.iter().all(|x| x != expected),
+            Self::Contains => values.iter().any(|x| x.contains(expected)),
+            Self::StartsWith => values.iter().any(|x| x.starts_with(expected)),
+            Self::EndsWith => values.iter().any(|x| x.ends_with(expected)),
+        }
+    }
+}
+
+/// Represents an error encountered when parsing a selector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseSelectorError {
+    /// Byte offset within the selector where the error occurred
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseSelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {} of selector", self.message, self.offset)
+    }
+}
+
+impl Error for ParseSelectorError {}
+
+impl FromStr for SelectorList {
+    type Err = ParseSelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let list = parser.selector_list()?;
+        match parser.peek() {
+            None => Ok(list),
+            Some(c) => Err(parser.error(format!("Unexpected `{}`", c))),
+        }
+    }
+}
+
+/// Parses selectors by recursively descending through the input
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseSelectorError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected `{}`", c)))
+        }
+    }
+
+    /// Skips whitespace, returning true if any was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+        self.pos > start
+    }
+
+    fn error(&self, message: String) -> ParseSelectorError {
+        ParseSelectorError {
+            offset: self.pos,
+            message,
+        }
+    }
+
+    fn ident(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            self.bump();
+        }
+        if self.pos > start {
+            Some(&self.input[start..self.pos])
+        } else {
+            None
+        }
+    }
+
+    fn selector_list(&mut self) -> Result<SelectorList, ParseSelectorError> {
+        let mut selectors = vec![self.selector()?];
+        while self.eat(',') {
+            selectors.push(self.selector()?);
+        }
+        Ok(SelectorList(selectors))
+    }
+
+    fn selector(&mut self) -> Result<Selector, ParseSelectorError> {
+        self.skip_whitespace();
+        let mut selector = Selector {
+            compounds: vec![self.compound()?],
+            combinators: Vec::new(),
+        };
+
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = match self.peek() {
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::NextSibling,
+                Some('~') => Combinator::SubsequentSibling,
+                None | Some(',') | Some(')') => break,
+                Some(_) if had_whitespace => Combinator::Descendant,
+                Some(c) => {
+                    return Err(self.error(format!("Unexpected `{}`", c)))
+                }
+            };
+            if combinator != Combinator::Descendant {
+                self.bump();
+                self.skip_whitespace();
+            }
+
+            selector.combinators.push(combinator);
+            selector.compounds.push(self.compound()?);
+        }
+
+        Ok(selector)
+    }
+
+    fn compound(&mut self) -> Result<Compound, ParseSelectorError> {
+        let start = self.pos;
+        let mut compound = Compound::default();
+
+        if !self.eat('*') {
+            if let Some(name) = self.ident() {
+                compound.kind = Some(
+                    KINDS.iter().copied().find(|x| *x == name).ok_or_else(
+                        || ParseSelectorError {
+                            offset: start,
+                            message: format!("Unknown element kind `{}`", name),
+                        },
+                    )?,
+                );
+            }
+        }
+
+        loop {
+            if self.eat('[') {
+                compound.predicates.push(self.attribute()?);
+            } else if self.eat(':') {
+                compound.predicates.push(self.pseudo_class()?);
+            } else {
+                break;
+            }
+        }
+
+        if self.pos == start {
+            Err(self
+                .error("Expected element kind, `*`, `[` or `:`".to_string()))
+        } else {
+            Ok(compound)
+        }
+    }
+
+    /// Parses the remainder of an attribute predicate after its `[`
+    fn attribute(&mut self) -> Result<Predicate, ParseSelectorError> {
+        self.skip_whitespace();
+        let name = self
+            .ident()
+            .ok_or_else(|| self.error("Expected attribute name".to_string()))?
+            .to_string();
+        self.skip_whitespace();
+
+        let test = if self.peek() == Some(']') {
+            None
+        } else {
+            let op = self.attr_op()?;
+            self.skip_whitespace();
+            Some((op, self.value(']')?))
+        };
+
+        self.skip_whitespace();
+        self.expect(']')?;
+        Ok(Predicate::Attr { name, test })
+    }
+
+    fn attr_op(&mut self) -> Result<AttrOp, ParseSelectorError> {
+        let op = match self.peek() {
+            Some('=') => AttrOp::Equals,
+            Some('!') => AttrOp::NotEquals,
+            Some('*') => AttrOp::Contains,
+            Some('^') => AttrOp::StartsWith,
+            Some('$') => AttrOp::EndsWith,
+            _ => {
+                return Err(self.error(
+                    "Expected one of `=`, `!=`, `*=`, `^=` or `$=`".to_string(),
+                ))
+            }
+        };
+        self.bump();
+        if op != AttrOp::Equals {
+            self.expect('=')?;
+        }
+        Ok(op)
+    }
+
+    /// Parses a value that is either quoted or continues until the
+    /// terminator, trimming any trailing whitespace
+    fn value(
+        &mut self,
+        terminator: char,
+    ) -> Result<String, ParseSelectorError> {
+        match self.peek() {
+            Some(quote @ '"') | Some(quote @ '\'') => {
+                self.bump();
+                let mut value = String::new();
+                loop {
+                    match self.bump() {
+                        Some('\\') => match self.bump() {
+                            Some(c) => value.push(c),
+                            None => break,
+                        },
+                        Some(c) if c == quote => return Ok(value),
+                        Some(c) => value.push(c),
+                        None => break,
+                    }
+                }
+                Err(self.error(format!("Missing closing {}", quote)))
+            }
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != terminator) {
+                    self.bump();
+                }
+                let value = self.input[start..self.pos].trim_end();
+                if value.is_empty() {
+                    Err(self.error("Expected value".to_string()))
+                } else {
+                    Ok(value.to_string())
+                }
+            }
+        }
+    }
+
+    /// Parses the remainder of a pseudo-class after its `:`
+    fn pseudo_class(&mut self) -> Result<Predicate, ParseSelectorError> {
+        let start = self.pos;
+        let name = self.ident().unwrap_or_default();
+        self.expect('(')?;
+
+        let predicate = match name {
+            "has" => Predicate::Has(self.has_argument()?),
+            "not" => Predicate::Not(self.selector_list()?),
+            _ => {
+                return Err(ParseSelectorError {
+                    offset: start,
+                    message: format!("Unknown pseudo-class `:{}`", name),
+                })
+            }
+        };
+
+        self.skip_whitespace();
+        self.expect(')')?;
+        Ok(predicate)
+    }
+
+    /// Parses the selectors of `:has(...)`, also accepting a lone attribute
+    /// test such as `:has(tag=foo)` as shorthand for `:has(*[tag=foo])`
+    fn has_argument(&mut self) -> Result<SelectorList, ParseSelectorError> {
+        let start = self.pos;
+        self.skip_whitespace();
+        if let Some(name) = self.ident() {
+            self.skip_whitespace();
+            let rest = &self.input[self.pos..];
+            if ["=", "!=", "*=", "^=", "$="]
+                .iter()
+                .any(|x| rest.starts_with(x))
+            {
+                let op = self.attr_op()?;
+                self.skip_whitespace();
+                let value = self.value(')')?;
+                return Ok(SelectorList(vec![Selector {
+                    compounds: vec![Compound {
+                        kind: None,
+                        predicates: vec![Predicate::Attr {
+                            name: name.to_string(),
+                            test: Some((op, value)),
+                        }],
+                    }],
+                    combinators: Vec::new(),
+                }]));
+            }
+        }
+
+        self.pos = start;
+        self.selector_list()
+    }
+}
+
+/// Represents the elements of a page flattened in the order they appear,
+/// keeping track of the parent and children of each element so selectors
+/// can be evaluated in every direction
+pub struct ElementTree<'a> {
+    nodes: Vec<Node<'a>>,
+    roots: Vec<usize>,
+}
+
+struct Node<'a> {
+    element: Located<Element<'a>>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl<'a> ElementTree<'a> {
+    pub fn new(page: &'a Page<'_>) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+
+        for element in page.to_borrowed().into_children() {
+            let idx = tree.insert(element.map(Element::from), None);
+            tree.roots.push(idx);
+        }
+
+        tree
+    }
+
+    fn insert(
+        &mut self,
+        element: Located<Element<'a>>,
+        parent: Option<usize>,
+    ) -> usize {
+        let idx = self.nodes.len();
+        let mut children = element.clone().into_inner().into_children();
+
+        // Some children such as the cells of a table are not kept in order
+        children.sort_by_key(|x| x.region().offset());
+
+        self.nodes.push(Node {
+            element,
+            parent,
+            children: Vec::new(),
+        });
+
+        for child in children {
+            let child_idx = self.insert(child, Some(idx));
+            self.nodes[idx].children.push(child_idx);
+        }
+
+        idx
+    }
+
+    /// Returns all elements matching the selectors in the order they appear
+    pub fn select<'b>(
+        &'b self,
+        selectors: &'b SelectorList,
+    ) -> impl Iterator<Item = &'b Located<Element<'a>>> + 'b {
+        (0..self.nodes.len())
+            .filter(move |idx| self.matches(*idx, selectors))
+            .map(move |idx| &self.nodes[idx].element)
+    }
+
+    fn matches(&self, idx: usize, selectors: &SelectorList) -> bool {
+        selectors.0.iter().any(|selector| {
+            self.matches_at(idx, selector, selector.compounds.len() - 1)
+        })
+    }
+
+    /// Checks if the element matches the compound selector at `pos` and
+    /// the elements around it match every compound selector before it
+    fn matches_at(&self, idx: usize, selector: &Selector, pos: usize) -> bool {
+        if !self.matches_compound(idx, &selector.compounds[pos]) {
+            return false;
+        }
+
+        if pos == 0 {
+            return true;
+        }
+
+        let matches_prev = |idx: usize| self.matches_at(idx, selector, pos - 1);
+        match selector.combinators[pos - 1] {
+            Combinator::Child => {
+                self.nodes[idx].parent.is_some_and(matches_prev)
+            }
+            Combinator::Descendant => self.ancestors(idx).any(matches_prev),
+            Combinator::NextSibling => self
+                .prev_siblings(idx)
+                .last()
+                .copied()
+                .is_some_and(matches_prev),
+            Combinator::SubsequentSibling => {
+                self.prev_siblings(idx).iter().copied().any(matches_prev)
+            }
+        }
+    }
+
+    fn matches_compound(&self, idx: usize, compound: &Compound) -> bool {
+        let element = self.nodes[idx].element.as_inner();
+        if let Some(kind) = compound.kind {
+            if kind_of(element) != kind {
+                return false;
+            }
+        }
+
+        compound.predicates.iter().all(|predicate| match predicate {
+            Predicate::Attr { name, test } => {
+                let values = attributes_of(element, name);
+                match test {
+                    Some((op, expected)) => op.test(&values, expected),
+                    None => !values.is_empty(),
+                }
+            }
+            Predicate::Has(selectors) => {
+                self.descendants(idx).any(|x| self.matches(x, selectors))
+            }
+            Predicate::Not(selectors) => !self.matches(idx, selectors),
+        })
+    }
+
+    fn ancestors(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.nodes[idx].parent, move |x| {
+            self.nodes[*x].parent
+        })
+    }
+
+    fn descendants(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = self.nodes[idx].children.clone();
+        std::iter::from_fn(move || {
+            let next = stack.pop()?;
+            stack.extend(self.nodes[next].children.iter().copied());
+            Some(next)
+        })
+    }
+
+    /// Returns the siblings that appear before the element
+    fn prev_siblings(&self, idx: usize) -> &[usize] {
+        let siblings = match self.nodes[idx].parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        };
+        let pos = siblings.iter().position(|x| *x == idx).unwrap_or(0);
+        &siblings[..pos]
+    }
+}
+
+/// Returns the kind of the element, matching the type used when serialized
+pub fn kind_of(element: &Element<'_>) -> &'static str {
+    match element {
+        Element::Block(x) => match x {
+            BlockElement::Blockquote(_) => "blockquote",
+            BlockElement::CodeBlock(_) => "code_block",
+            BlockElement::DefinitionList(_) => "definition_list",
+            BlockElement::Divider(_) => "divider",
+            BlockElement::Header(_) => "header",
+            BlockElement::List(_) => "list",
+            BlockElement::MathBlock(_) => "math_block",
+            BlockElement::Paragraph(_) => "paragraph",
+            BlockElement::Placeholder(_) => "placeholder",
+            BlockElement::Table(_) => "table",
+        },
+        Element::Inline(x) => match x {
+            InlineElement::Text(_) => "text",
+            InlineElement::DecoratedText(_) => "decorated_text",
+            InlineElement::Keyword(_) => "keyword",
+            InlineElement::Link(_) => "link",
+            InlineElement::Tags(_) => "tags",
+            InlineElement::Code(_) => "code",
+            InlineElement::Math(_) => "math",
+            InlineElement::Comment(_) => "comment",
+        },
+        Element::InlineBlock(x) => match x {
+            InlineBlockElement::ListItem(_) => "list_item",
+            InlineBlockElement::Term(_) => "term",
+            InlineBlockElement::Definition(_) => "definition",
+        },
+    }
+}
+
+/// Returns the values of the named attribute of an element, which is empty
+/// if the element does not have the attribute
+fn attributes_of(element: &Element<'_>, name: &str) -> Vec<String> {
+    let value = match (element, name) {
+        (_, "text") => text_of(element),
+        (Element::Block(BlockElement::Header(x)), "level") => {
+            Some(x.level.to_string())
+        }
+        (Element::Block(BlockElement::Header(x)), "centered") => {
+            Some(x.centered.to_string())
+        }
+        (Element::Block(BlockElement::Table(x)), "centered") => {
+            Some(x.centered.to_string())
+        }
+        (Element::Block(BlockElement::CodeBlock(x)), "language") => {
+            x.language.as_ref().map(ToString::to_string)
+        }
+        (Element::Block(BlockElement::MathBlock(x)), "environment") => {
+            x.environment.as_ref().map(ToString::to_string)
+        }
+        (Element::Block(BlockElement::Placeholder(x)), _) => {
+            placeholder_attribute(x, name)
+        }
+        (Element::Inline(InlineElement::DecoratedText(x)), "style") => Some(
+            match x {
+                DecoratedText::Bold(_) => "bold",
+                DecoratedText::Italic(_) => "italic",
+                DecoratedText::Strikeout(_) => "strikeout",
+                DecoratedText::Superscript(_) => "superscript",
+                DecoratedText::Subscript(_) => "subscript",
+            }
+            .to_string(),
+        ),
+        (Element::Inline(InlineElement::Tags(x)), "tag") => {
+            return x.iter().map(|x| x.as_str().to_string()).collect();
+        }
+        (Element::Inline(InlineElement::Link(x)), _) => match name {
+            "kind" => Some(
+                match x {
+                    vimwiki::Link::Wiki { .. } => "wiki",
+                    vimwiki::Link::IndexedInterWiki { .. } => {
+                        "indexed_inter_wiki"
+                    }
+                    vimwiki::Link::NamedInterWiki { .. } => "named_inter_wiki",
+                    vimwiki::Link::Diary { .. } => "diary",
+                    vimwiki::Link::Raw { .. } => "raw",
+                    vimwiki::Link::Transclusion { .. } => "transclusion",
+                }
+                .to_string(),
+            ),
+            "uri" => Some(x.data().to_decoded_uri_string()),
+            "scheme" => x.scheme().map(|x| x.as_str().to_string()),
+            "anchor" => x
+                .data()
+                .fragment_str()
+                .map(|x| LinkData::decode_uri(x.as_bytes())),
+            "description" => x.description().map(ToString::to_string),
+            "date" => x.date().map(|x| x.to_string()),
+            "wiki" => x
+                .index()
+                .map(|x| x.to_string())
+                .or_else(|| x.name().map(ToString::to_string)),
+            _ => None,
+        },
+        (Element::InlineBlock(InlineBlockElement::ListItem(x)), _) => {
+            match name {
+                "todo" => x.attributes.todo_status.map(|x| {
+                    match x {
+                        ListItemTodoStatus::Incomplete => "incomplete",
+                        ListItemTodoStatus::PartiallyComplete1 => {
+                            "partially_complete1"
+                        }
+                        ListItemTodoStatus::PartiallyComplete2 => {
+                            "partially_complete2"
+                        }
+                        ListItemTodoStatus::PartiallyComplete3 => {
+                            "partially_complete3"
+                        }
+                        ListItemTodoStatus::Complete => "complete",
+                        ListItemTodoStatus::Rejected => "rejected",
+                    }
+                    .to_string()
+                }),
+                "pos" => Some(x.pos.to_string()),
+                "ordered" => Some(x.is_ordered().to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    value.into_iter().collect()
+}
+
+fn placeholder_attribute(
+    placeholder: &Placeholder<'_>,
+    name: &str,
+) -> Option<String> {
+    match (placeholder, name) {
+        (_, "kind") => Some(
+            match placeholder {
+                Placeholder::Title(_) => "title",
+                Placeholder::NoHtml => "no_html",
+                Placeholder::Template(_) => "template",
+                Placeholder::Date(_) => "date",
+                Placeholder::Other { .. } => "other",
+            }
+            .to_string(),
+        ),
+        (Placeholder::Other { name, .. }, "name") => Some(name.to_string()),
+        (Placeholder::Title(x), "value")
+        | (Placeholder::Template(x), "value")
+        | (Placeholder::Other { value: x, .. }, "value") => Some(x.to_string()),
+        (Placeholder::Date(x), "value") => Some(x.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the text of an element, which is only available for elements
+/// made up of text
+fn text_of(element: &Element<'_>) -> Option<String> {
+    match element {
+        Element::Block(x) => block_text_of(x),
+        Element::Inline(x) => Some(x.to_string()),
+        Element::InlineBlock(InlineBlockElement::ListItem(x)) => {
+            let lines: Vec<String> =
+                x.contents.iter().filter_map(|x| block_text_of(x)).collect();
+            Some(lines.join("\n"))
+        }
+        Element::InlineBlock(InlineBlockElement::Term(x))
+        | Element::InlineBlock(InlineBlockElement::Definition(x)) => {
+            Some(x.to_string())
+        }
+    }
+}
+
+fn block_text_of(element: &BlockElement<'_>) -> Option<String> {
+    match element {
+        BlockElement::Header(x) => Some(x.to_string().trim().to_string()),
+        BlockElement::Paragraph(x) => Some(x.to_string()),
+        BlockElement::Blockquote(x) => Some(x.to_string()),
+        BlockElement::CodeBlock(x) => Some(x.lines.join("\n")),
+        BlockElement::MathBlock(x) => Some(x.lines.join("\n")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use vimwiki::Language;
+
+    fn parse_page(text: &str) -> Page<'static> {
+        let page: Page = Language::from_vimwiki_str(text).parse().unwrap();
+        page.into_owned()
+    }
+
+    /// Selects elements from the page, returning the kind and text of each
+    fn select(page: &Page<'_>, selector: &str) -> Vec<(String, String)> {
+        let selectors: SelectorList = selector.parse().unwrap();
+        ElementTree::new(page)
+            .select(&selectors)
+            .map(|x| {
+                (
+                    kind_of(x.as_inner()).to_string(),
+                    text_of(x.as_inner()).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    fn texts(page: &Page<'_>, selector: &str) -> Vec<String> {
+        select(page, selector).into_iter().map(|x| x.1).collect()
+    }
+
+    #[test]
+    fn parse_should_fail_for_unknown_element_kind() {
+        let err = "header > linkz".parse::<SelectorList>().unwrap_err();
+        assert_eq!(err.offset, 9);
+        assert!(err.message.contains("linkz"), "{}", err);
+    }
+
+    #[test]
+    fn parse_should_fail_for_unknown_pseudo_class() {
+        let err = "header:first(x)".parse::<SelectorList>().unwrap_err();
+        assert_eq!(err.offset, 7);
+    }
+
+    #[test]
+    fn parse_should_fail_for_unterminated_predicates() {
+        assert!("header[level=2".parse::<SelectorList>().is_err());
+        assert!("header:has(link".parse::<SelectorList>().is_err());
+        assert!("header[level='2]".parse::<SelectorList>().is_err());
+        assert!("header >".parse::<SelectorList>().is_err());
+        assert!("header)".parse::<SelectorList>().is_err());
+    }
+
+    #[test]
+    fn select_should_match_by_kind_and_attributes() {
+        let page = parse_page(indoc! {"
+            = One =
+            == Two ==
+            == Three ==
+        "});
+
+        assert_eq!(texts(&page, "header[level=2]"), vec!["Two", "Three"]);
+        assert_eq!(texts(&page, "header[level!=2]"), vec!["One"]);
+        assert_eq!(texts(&page, "header[text^=T][text$=ee]"), vec!["Three"]);
+        assert_eq!(texts(&page, "header[text*='hre']"), vec!["Three"]);
+        assert_eq!(texts(&page, "*[level]").len(), 3);
+        assert!(texts(&page, "header[missing]").is_empty());
+    }
+
+    #[test]
+    fn select_should_match_todo_status_of_list_items() {
+        let page = parse_page(indoc! {"
+            - [ ] first
+            - [X] second
+            - third
+        "});
+
+        assert_eq!(texts(&page, "list_item[todo=incomplete]"), vec!["first"]);
+        assert_eq!(texts(&page, "list_item:not([todo])"), vec!["third"]);
+        assert_eq!(
+            texts(&page, "list_item[todo=complete], list_item[pos=2]"),
+            vec!["second", "third"]
+        );
+    }
+
+    #[test]
+    fn select_should_support_child_and_descendant_combinators() {
+        let page = parse_page(indoc! {"
+            == [[Inside]] ==
+            - *[[Nested]]*
+            - [[Outside]]
+        "});
+
+        assert_eq!(
+            select(&page, "header[level=2] > link"),
+            vec![("link".to_string(), "Inside".to_string())]
+        );
+        assert_eq!(texts(&page, "list link"), vec!["Nested", "Outside"]);
+        assert_eq!(
+            texts(&page, "list_item > paragraph > link"),
+            vec!["Outside"]
+        );
+    }
+
+    #[test]
+    fn select_should_support_sibling_combinators() {
+        let page = parse_page(indoc! {"
+            = Work =
+            :work:
+
+            - [ ] a
+
+            = Home =
+
+            - [ ] b
+        "});
+
+        assert_eq!(
+            texts(&page, "header:has(text=Home) + list list_item"),
+            vec!["b"]
+        );
+        assert_eq!(
+            texts(&page, "paragraph:has(tag=work) ~ list list_item[todo]"),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            texts(&page, "header:has(text=Work) + paragraph"),
+            vec![":work:"]
+        );
+    }
+
+    #[test]
+    fn select_should_support_has_with_full_selectors() {
+        let page = parse_page(indoc! {"
+            - [[a]] :foo:
+            - [[b]]
+            - c
+        "});
+
+        assert_eq!(texts(&page, "list_item:has(tag=foo)").len(), 1);
+        assert_eq!(texts(&page, "list_item:has(link)").len(), 2);
+        assert_eq!(
+            texts(&page, "list_item:has(paragraph > link[uri=b])"),
+            vec!["b"]
+        );
+        assert_eq!(texts(&page, "list_item:not(:has(link))"), vec!["c"]);
+    }
+
+    #[test]
+    fn select_should_expose_attributes_of_links() {
+        let page = parse_page(indoc! {"
+            [[page#anchor|desc]] [[wn.other:page]] [[diary:2021-01-02]]
+            https://example.com
+        "});
+
+        assert_eq!(texts(&page, "link[anchor=anchor]"), vec!["desc"]);
+        assert_eq!(texts(&page, "link[description=desc]"), vec!["desc"]);
+        assert_eq!(
+            texts(&page, "link[kind=named_inter_wiki][wiki=other]").len(),
+            1
+        );
+        assert_eq!(texts(&page, "link[date=2021-01-02]").len(), 1);
+        assert_eq!(texts(&page, "link[scheme=https]").len(), 1);
+    }
+}
@@ -0,0 +1,925 @@
+use super::{
+    outline::{HeaderInfo, LinkInfo},
+    position::{offset_to_position, position_to_offset, region_to_range},
+    workspace::{Document, Target, Workspace},
+};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity,
+    DocumentChangeOperation, DocumentChanges, DocumentSymbol, Hover,
+    HoverContents, Location, MarkupContent, MarkupKind, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, RenameFile,
+    ResourceOp, SymbolKind, TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Component, Path, PathBuf},
+};
+use vimwiki::{Language, Link, Page, Region, ToVimwikiString};
+
+/// Maximum number of lines to include when previewing a page on hover
+const PREVIEW_LINES: usize = 20;
+
+/// Produces diagnostics for a document, covering failures to parse it and
+/// links to pages, anchors or wikis that do not exist
+pub fn diagnostics(ws: &Workspace, path: &Path) -> Vec<Diagnostic> {
+    let doc = match ws.get(path) {
+        Some(doc) => doc,
+        None => return Vec::new(),
+    };
+
+    let mut diagnostics = Vec::new();
+    if let Some(error) = doc.error.as_ref() {
+        let start = offset_to_position(&doc.index, error.offset);
+        let end = Position::new(start.line + 1, 0);
+        diagnostics.push(Diagnostic {
+            range: Range::new(start, end),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some(String::from("vimwiki")),
+            message: error.message.trim_end().to_string(),
+            ..Default::default()
+        });
+    }
+
+    for info in doc.outline.links.iter() {
+        let message = match ws.resolve(path, &info.link) {
+            None => continue,
+            Some(Err(x)) => x,
+            Some(Ok(target)) => match check_target(ws, &target) {
+                Some(x) => x,
+                None => continue,
+            },
+        };
+
+        diagnostics.push(Diagnostic {
+            range: region_to_range(&doc.index, info.region),
+            severity: Some(DiagnosticSeverity::WARNING),
+            source: Some(String::from("vimwiki")),
+            message,
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// Returns a description of why a link target is broken, or None if the
+/// page (and anchor) it targets exists
+fn check_target(ws: &Workspace, target: &Target) -> Option<String> {
+    if !ws.exists(&target.path) {
+        return Some(format!(
+            "Page {} does not exist",
+            ws.page_name(&target.path)
+        ));
+    }
+
+    let anchor = target.anchor.as_deref()?;
+    let found = ws
+        .document(&target.path)
+        .is_some_and(|doc| doc.outline.find_anchor(anchor).is_some());
+    if found {
+        None
+    } else {
+        Some(format!(
+            "Anchor {} does not exist in page {}",
+            anchor,
+            ws.page_name(&target.path)
+        ))
+    }
+}
+
+/// Finds the location targeted by the link at the given position
+pub fn definition(
+    ws: &Workspace,
+    path: &Path,
+    position: Position,
+) -> Option<Location> {
+    let doc = ws.get(path)?;
+    let offset = position_to_offset(&doc.index, position);
+    let info = doc.outline.link_at(offset)?;
+    let target = ws.resolve(path, &info.link)?.ok()?;
+
+    let range = target
+        .anchor
+        .as_deref()
+        .and_then(|anchor| {
+            let doc = ws.document(&target.path)?;
+            let region = doc.outline.find_anchor(anchor)?;
+            Some(region_to_range(&doc.index, region))
+        })
+        .unwrap_or_default();
+
+    Some(Location::new(
+        Url::from_file_path(&target.path).ok()?,
+        range,
+    ))
+}
+
+/// Finds all links referencing the header at the given position, the target
+/// of the link at the given position, or otherwise the page itself
+pub fn references(
+    ws: &Workspace,
+    path: &Path,
+    position: Position,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let doc = match ws.get(path) {
+        Some(doc) => doc,
+        None => return Vec::new(),
+    };
+    let offset = position_to_offset(&doc.index, position);
+
+    let target = if let Some(header) = doc.outline.header_at(offset) {
+        Target {
+            path: path.to_path_buf(),
+            anchor: Some(header.text.to_string()),
+        }
+    } else if let Some(Some(Ok(target))) = doc
+        .outline
+        .link_at(offset)
+        .map(|info| ws.resolve(path, &info.link))
+    {
+        target
+    } else {
+        Target {
+            path: path.to_path_buf(),
+            anchor: None,
+        }
+    };
+
+    let mut locations = Vec::new();
+    if include_declaration {
+        if let Some(doc) = ws.document(&target.path) {
+            let region = match target.anchor.as_deref() {
+                Some(anchor) => doc.outline.find_anchor(anchor),
+                None => Some(Region::new(0, 0)),
+            };
+            if let (Some(region), Ok(uri)) =
+                (region, Url::from_file_path(&target.path))
+            {
+                locations.push(Location::new(
+                    uri,
+                    region_to_range(&doc.index, region),
+                ));
+            }
+        }
+    }
+
+    for (src, doc, info, _) in links_to(ws, &target) {
+        if let Ok(uri) = Url::from_file_path(src) {
+            locations.push(Location::new(
+                uri,
+                region_to_range(&doc.index, info.region),
+            ));
+        }
+    }
+
+    locations
+}
+
+/// Finds all links across the workspace that resolve to the target, where
+/// a target without an anchor matches links to any part of the page
+fn links_to<'a>(
+    ws: &'a Workspace,
+    target: &Target,
+) -> Vec<(&'a PathBuf, &'a Document, &'a LinkInfo, Target)> {
+    let mut links = Vec::new();
+    for (src, doc) in ws.documents() {
+        for info in doc.outline.links.iter() {
+            if let Some(Ok(t)) = ws.resolve(src, &info.link) {
+                if t.path == target.path
+                    && (target.anchor.is_none() || t.anchor == target.anchor)
+                {
+                    links.push((src, doc, info, t));
+                }
+            }
+        }
+    }
+
+    links.sort_by_key(|(src, _, info, _)| (*src, info.region.offset()));
+    links
+}
+
+/// Produces a hierarchy of symbols from the headers of a document, where
+/// each symbol spans the header and the content that follows it up to the
+/// next header of the same or higher level
+pub fn document_symbols(ws: &Workspace, path: &Path) -> Vec<DocumentSymbol> {
+    let doc = match ws.get(path) {
+        Some(doc) => doc,
+        None => return Vec::new(),
+    };
+    let headers = doc.outline.headers.as_slice();
+
+    fn attach(
+        stack: &mut [(usize, DocumentSymbol)],
+        roots: &mut Vec<DocumentSymbol>,
+        symbol: DocumentSymbol,
+    ) {
+        match stack.last_mut() {
+            Some((_, parent)) => {
+                parent.children.get_or_insert_with(Vec::new).push(symbol)
+            }
+            None => roots.push(symbol),
+        }
+    }
+
+    let mut roots = Vec::new();
+    let mut stack: Vec<(usize, DocumentSymbol)> = Vec::new();
+    for (i, header) in headers.iter().enumerate() {
+        while stack
+            .last()
+            .is_some_and(|(level, _)| *level >= header.level)
+        {
+            let (_, symbol) = stack.pop().unwrap();
+            attach(&mut stack, &mut roots, symbol);
+        }
+
+        let end = headers[i + 1..]
+            .iter()
+            .find(|x| x.level <= header.level)
+            .map_or(doc.text.len(), |x| x.region.offset());
+        let start = header.region.offset();
+
+        #[allow(deprecated)]
+        let symbol = DocumentSymbol {
+            name: header.text.to_string(),
+            detail: Some("=".repeat(header.level)),
+            kind: SymbolKind::NAMESPACE,
+            tags: None,
+            deprecated: None,
+            range: region_to_range(&doc.index, Region::new(start, end - start)),
+            selection_range: region_to_range(&doc.index, header.content_region),
+            children: None,
+        };
+        stack.push((header.level, symbol));
+    }
+
+    while let Some((_, symbol)) = stack.pop() {
+        attach(&mut stack, &mut roots, symbol);
+    }
+
+    roots
+}
+
+/// Completes page names after `[[`, anchors after `[[page#` and tags after
+/// a `:` that starts a word
+pub fn completion(
+    ws: &Workspace,
+    path: &Path,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let doc = match ws.get(path) {
+        Some(doc) => doc,
+        None => return Vec::new(),
+    };
+    let offset = position_to_offset(&doc.index, position);
+    let line_start = doc
+        .index
+        .line_of(offset)
+        .and_then(|line| doc.index.line_region(line))
+        .map_or(0, |x| x.offset());
+    let prefix = &doc.text[line_start..offset];
+
+    let in_link = prefix
+        .rfind("[[")
+        .map(|i| &prefix[i + 2..])
+        .filter(|x| !x.contains("]]") && !x.contains('|'));
+
+    if let Some(link) = in_link {
+        match link.find('#') {
+            Some(i) => anchor_completions(ws, path, &link[..i]),
+            None => page_completions(ws, path),
+        }
+    } else if prefix
+        .rsplit(char::is_whitespace)
+        .next()
+        .is_some_and(|word| word.starts_with(':'))
+    {
+        tag_completions(ws)
+    } else {
+        Vec::new()
+    }
+}
+
+fn page_completions(ws: &Workspace, path: &Path) -> Vec<CompletionItem> {
+    let wiki = ws.wiki_for(path);
+
+    // Pages within the root of the wiki can be linked by name, while pages
+    // elsewhere link to other pages from the root of the wiki
+    let at_root = path.parent() == Some(wiki.path.as_path());
+
+    let mut names = BTreeSet::new();
+    for (page, _) in ws.documents() {
+        if page.as_path() != path && wiki.path_within(page).is_some() {
+            names.insert(ws.page_name(page));
+        }
+    }
+
+    names
+        .into_iter()
+        .map(|name| CompletionItem {
+            label: if at_root { name } else { format!("/{}", name) },
+            kind: Some(CompletionItemKind::FILE),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn anchor_completions(
+    ws: &Workspace,
+    path: &Path,
+    page: &str,
+) -> Vec<CompletionItem> {
+    let target = if page.is_empty() {
+        path.to_path_buf()
+    } else {
+        match parse_link(&format!("[[{}]]", page))
+            .and_then(|link| ws.resolve(path, &link))
+        {
+            Some(Ok(target)) => target.path,
+            _ => return Vec::new(),
+        }
+    };
+
+    let doc = match ws.document(&target) {
+        Some(doc) => doc,
+        None => return Vec::new(),
+    };
+
+    doc.outline
+        .headers
+        .iter()
+        .map(|header| CompletionItem {
+            label: header.text.to_string(),
+            kind: Some(CompletionItemKind::REFERENCE),
+            detail: Some("=".repeat(header.level)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn tag_completions(ws: &Workspace) -> Vec<CompletionItem> {
+    let tags: BTreeSet<&str> = ws
+        .documents()
+        .flat_map(|(_, doc)| doc.outline.tags.iter())
+        .flat_map(|tags| tags.names.iter().map(String::as_str))
+        .collect();
+
+    tags.into_iter()
+        .map(|tag| CompletionItem {
+            label: tag.to_string(),
+            kind: Some(CompletionItemKind::CONSTANT),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Parses text as a single link
+fn parse_link(text: &str) -> Option<Link<'static>> {
+    let page: Page = Language::from_vimwiki_str(text).parse().ok()?;
+    super::outline::Outline::new(&page)
+        .links
+        .into_iter()
+        .next()
+        .map(|x| x.link)
+}
+
+/// Previews the page (or section of the page) targeted by the link at the
+/// given position
+pub fn hover(ws: &Workspace, path: &Path, position: Position) -> Option<Hover> {
+    let doc = ws.get(path)?;
+    let offset = position_to_offset(&doc.index, position);
+    let info = doc.outline.link_at(offset)?;
+    let range = Some(region_to_range(&doc.index, info.region));
+
+    let value = match ws.resolve(path, &info.link)? {
+        Err(x) => x,
+        Ok(target) => match check_target(ws, &target) {
+            Some(x) => x,
+            None => {
+                let target_doc = ws.document(&target.path)?;
+                let start = target
+                    .anchor
+                    .as_deref()
+                    .and_then(|x| target_doc.outline.find_anchor(x))
+                    .map_or(0, |x| x.offset());
+                let preview = target_doc.text[start..]
+                    .lines()
+                    .take(PREVIEW_LINES)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                format!(
+                    "**{}**\n\n```vimwiki\n{}\n```",
+                    ws.page_name(&target.path),
+                    preview
+                )
+            }
+        },
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range,
+    })
+}
+
+/// Renames the header at the given position, or the page targeted by the
+/// link at the given position, updating all links that reference it
+pub fn rename(
+    ws: &Workspace,
+    path: &Path,
+    position: Position,
+    new_name: &str,
+) -> Result<WorkspaceEdit, String> {
+    let doc = ws.get(path).ok_or("Document is not loaded")?;
+    let offset = position_to_offset(&doc.index, position);
+
+    if let Some(header) = doc.outline.header_at(offset) {
+        Ok(rename_header(ws, path, doc, header, new_name))
+    } else if let Some(info) = doc.outline.link_at(offset) {
+        match ws.resolve(path, &info.link) {
+            Some(Ok(target)) => rename_page(ws, &target.path, new_name),
+            Some(Err(x)) => Err(x),
+            None => Err(String::from("Link does not target a wiki page")),
+        }
+    } else {
+        Err(String::from(
+            "Rename is only supported on headers and links",
+        ))
+    }
+}
+
+fn rename_header(
+    ws: &Workspace,
+    path: &Path,
+    doc: &Document,
+    header: &HeaderInfo,
+    new_name: &str,
+) -> WorkspaceEdit {
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    let mut push = |path: &Path, edit: TextEdit| {
+        if let Ok(uri) = Url::from_file_path(path) {
+            changes.entry(uri).or_default().push(edit);
+        }
+    };
+
+    push(
+        path,
+        TextEdit::new(
+            region_to_range(&doc.index, header.content_region),
+            new_name.to_string(),
+        ),
+    );
+
+    let target = Target {
+        path: path.to_path_buf(),
+        anchor: Some(header.text.to_string()),
+    };
+    for (src, doc, info, _) in links_to(ws, &target) {
+        let parts = LinkParts::new(&doc.text, info.region);
+        if let Some(region) = parts.and_then(|x| x.anchor) {
+            push(
+                src,
+                TextEdit::new(
+                    region_to_range(&doc.index, region),
+                    new_name.to_string(),
+                ),
+            );
+        }
+    }
+
+    WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }
+}
+
+fn rename_page(
+    ws: &Workspace,
+    path: &Path,
+    new_name: &str,
+) -> Result<WorkspaceEdit, String> {
+    let wiki = ws.wiki_for(path);
+    let new_path = ws.page_path(&wiki, new_name);
+    let new_name = ws.page_name(&new_path);
+    let to_uri = |path: &Path| {
+        Url::from_file_path(path)
+            .map_err(|_| format!("Invalid path {:?}", path))
+    };
+
+    let target = Target {
+        path: path.to_path_buf(),
+        anchor: None,
+    };
+
+    let mut edits: Vec<(&PathBuf, TextEdit)> = Vec::new();
+    for (src, doc, info, _) in links_to(ws, &target) {
+        let parts = match LinkParts::new(&doc.text, info.region) {
+            Some(parts) => parts,
+            None => continue,
+        };
+
+        // Links to anchors within the same page have no page to rename
+        let page = match parts.page {
+            Some(page) if !page.is_empty() => page,
+            _ => continue,
+        };
+
+        let old = &doc.text[page.offset()..page.offset() + page.len()];
+        let text = if let Some(i) = old.find(':').filter(|_| {
+            matches!(
+                info.link,
+                Link::IndexedInterWiki { .. } | Link::NamedInterWiki { .. }
+            )
+        }) {
+            format!("{}{}", &old[..=i], new_name)
+        } else if old.starts_with('/') {
+            format!("/{}", new_name)
+        } else {
+            let dir = src.parent().unwrap_or_else(|| Path::new(""));
+            relative_path(dir, &new_path.with_extension(""))
+        };
+
+        edits.push((
+            src,
+            TextEdit::new(region_to_range(&doc.index, page), text),
+        ));
+    }
+
+    let mut operations = Vec::new();
+    let mut i = 0;
+    while i < edits.len() {
+        let src = edits[i].0;
+        let mut text_edits = Vec::new();
+        while i < edits.len() && edits[i].0 == src {
+            text_edits.push(OneOf::Left(edits[i].1.clone()));
+            i += 1;
+        }
+
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier {
+                uri: to_uri(src)?,
+                version: None,
+            },
+            edits: text_edits,
+        }));
+    }
+
+    operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+        RenameFile {
+            old_uri: to_uri(path)?,
+            new_uri: to_uri(&new_path)?,
+            options: None,
+            annotation_id: None,
+        },
+    )));
+
+    Ok(WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    })
+}
+
+/// Represents the regions of the page and anchor portions of the source
+/// text of a link in the form `[[page#anchor|description]]`
+struct LinkParts {
+    page: Option<Region>,
+    anchor: Option<Region>,
+}
+
+impl LinkParts {
+    fn new(text: &str, region: Region) -> Option<Self> {
+        let src = &text[region.offset()..region.offset() + region.len()];
+        let inner = src.strip_prefix("[[")?.strip_suffix("]]")?;
+        let start = region.offset() + 2;
+        let target = &inner[..inner.find('|').unwrap_or(inner.len())];
+
+        let page_len = target.find('#').unwrap_or(target.len());
+        let anchor = target
+            .rfind('#')
+            .map(|i| Region::new(start + i + 1, target.len() - i - 1));
+
+        Some(Self {
+            page: Some(Region::new(start, page_len)),
+            anchor,
+        })
+    }
+}
+
+/// Produces a relative path from a directory to a file using `/` as the
+/// separator, as used within links
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    (common..from.len())
+        .map(|_| String::from(".."))
+        .chain(
+            to[common..]
+                .iter()
+                .map(|c| c.as_os_str().to_string_lossy().to_string()),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Formats an entire document, returning no edits if it is already formatted
+pub fn formatting(
+    ws: &Workspace,
+    path: &Path,
+) -> Result<Vec<TextEdit>, String> {
+    let doc = ws.get(path).ok_or("Document is not loaded")?;
+    let page = doc.page.as_ref().ok_or("Document failed to parse")?;
+    let formatted = page
+        .to_vimwiki_string(ws.format_config.clone())
+        .map_err(|x| x.to_string())?;
+
+    if formatted == doc.text {
+        Ok(Vec::new())
+    } else {
+        Ok(vec![TextEdit::new(
+            Range::new(
+                Position::new(0, 0),
+                offset_to_position(&doc.index, doc.text.len()),
+            ),
+            formatted,
+        )])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use vimwiki::{HtmlConfig, HtmlWikiConfig, VimwikiConfig};
+
+    struct Fixture {
+        _dir: tempfile::TempDir,
+        root: PathBuf,
+        ws: Workspace,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().expect("Failed to create temp dir");
+            let root = dir.path().canonicalize().unwrap();
+            fs::create_dir(root.join("sub")).unwrap();
+            fs::write(
+                root.join("index.wiki"),
+                "= Index =\n\n[[Page]] [[Page#Section|desc]] [[Missing]]\n\
+                 [[#Index]] [[Page#Nope]] :tag1:\n",
+            )
+            .unwrap();
+            fs::write(
+                root.join("Page.wiki"),
+                "= Title =\n\n== Section ==\ncontent\n\n[[index]]\n",
+            )
+            .unwrap();
+            fs::write(
+                root.join("sub").join("Deep.wiki"),
+                "[[../Page#Section]] [[/Page]]\n",
+            )
+            .unwrap();
+
+            let config = HtmlConfig {
+                wikis: vec![HtmlWikiConfig {
+                    path: root.to_path_buf(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            let mut ws = Workspace::new(config, VimwikiConfig::default());
+            ws.load_wikis();
+
+            Self {
+                _dir: dir,
+                root,
+                ws,
+            }
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.root.join(name)
+        }
+
+        fn uri(&self, name: &str) -> Url {
+            Url::from_file_path(self.path(name)).unwrap()
+        }
+
+        /// Returns the position of the first occurrence of `needle` within
+        /// the named document, offset by `delta` bytes
+        fn position(&self, name: &str, needle: &str, delta: usize) -> Position {
+            let doc = self.ws.get(&self.path(name)).unwrap();
+            let offset = doc.text.find(needle).unwrap() + delta;
+            offset_to_position(&doc.index, offset)
+        }
+    }
+
+    #[test]
+    fn diagnostics_should_report_missing_pages_and_anchors() {
+        let f = Fixture::new();
+        let found = diagnostics(&f.ws, &f.path("index.wiki"));
+        assert_eq!(
+            found
+                .iter()
+                .map(|x| (x.range.start, x.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Position::new(2, 31), "Page Missing does not exist"),
+                (
+                    Position::new(3, 11),
+                    "Anchor Nope does not exist in page Page"
+                ),
+            ]
+        );
+        assert!(diagnostics(&f.ws, &f.path("Page.wiki")).is_empty());
+    }
+
+    #[test]
+    fn definition_should_locate_target_page_and_anchor() {
+        let f = Fixture::new();
+        let position = f.position("index.wiki", "[[Page#Section", 3);
+        let location = definition(&f.ws, &f.path("index.wiki"), position);
+        assert_eq!(
+            location,
+            Some(Location::new(
+                f.uri("Page.wiki"),
+                Range::new(Position::new(2, 0), Position::new(3, 0))
+            ))
+        );
+
+        let position = f.position("index.wiki", "[[Page]]", 3);
+        let location = definition(&f.ws, &f.path("index.wiki"), position);
+        assert_eq!(
+            location,
+            Some(Location::new(f.uri("Page.wiki"), Range::default()))
+        );
+    }
+
+    #[test]
+    fn references_should_find_links_to_header_or_page() {
+        let f = Fixture::new();
+        let position = f.position("Page.wiki", "Section", 0);
+        let locations =
+            references(&f.ws, &f.path("Page.wiki"), position, false);
+        assert_eq!(
+            locations.iter().map(|x| &x.uri).collect::<Vec<_>>(),
+            vec![&f.uri("index.wiki"), &f.uri("sub/Deep.wiki")]
+        );
+
+        let position = f.position("Page.wiki", "content", 0);
+        let locations = references(&f.ws, &f.path("Page.wiki"), position, true);
+        assert_eq!(locations.len(), 6);
+        assert_eq!(locations[0].uri, f.uri("Page.wiki"));
+    }
+
+    #[test]
+    fn document_symbols_should_nest_headers_by_level() {
+        let f = Fixture::new();
+        let symbols = document_symbols(&f.ws, &f.path("Page.wiki"));
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "Title");
+        assert_eq!(symbols[0].range.end, Position::new(6, 0));
+
+        let children = symbols[0].children.as_ref().unwrap();
+        assert_eq!(children[0].name, "Section");
+        assert_eq!(
+            children[0].selection_range,
+            Range::new(Position::new(2, 3), Position::new(2, 10))
+        );
+    }
+
+    #[test]
+    fn completion_should_offer_pages_anchors_and_tags() {
+        let mut f = Fixture::new();
+        let path = f.path("new.wiki");
+        f.ws.open(path.clone(), String::from("[[\n[[Page#\n:ta\n"));
+        let labels = |line| {
+            completion(&f.ws, &path, Position::new(line, 10))
+                .into_iter()
+                .map(|x| x.label)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(labels(0), vec!["Page", "index", "sub/Deep"]);
+        assert_eq!(labels(1), vec!["Title", "Section"]);
+        assert_eq!(labels(2), vec!["tag1"]);
+    }
+
+    #[test]
+    fn hover_should_preview_target_page() {
+        let f = Fixture::new();
+        let position = f.position("index.wiki", "[[Page#Section", 3);
+        let hover = hover(&f.ws, &f.path("index.wiki"), position).unwrap();
+        match hover.contents {
+            HoverContents::Markup(x) => {
+                assert_eq!(
+                    x.value,
+                    "**Page**\n\n```vimwiki\n== Section ==\ncontent\n\n[[index]]\n```"
+                );
+            }
+            x => panic!("Unexpected hover: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn rename_should_update_header_and_links_to_it() {
+        let f = Fixture::new();
+        let position = f.position("Page.wiki", "Section", 0);
+        let edit =
+            rename(&f.ws, &f.path("Page.wiki"), position, "Part").unwrap();
+        let changes = edit.changes.unwrap();
+
+        assert_eq!(
+            changes[&f.uri("Page.wiki")],
+            vec![TextEdit::new(
+                Range::new(Position::new(2, 3), Position::new(2, 10)),
+                String::from("Part")
+            )]
+        );
+        assert_eq!(
+            changes[&f.uri("index.wiki")],
+            vec![TextEdit::new(
+                Range::new(Position::new(2, 16), Position::new(2, 23)),
+                String::from("Part")
+            )]
+        );
+        assert_eq!(changes[&f.uri("sub/Deep.wiki")].len(), 1);
+    }
+
+    #[test]
+    fn rename_should_update_links_to_page_and_move_file() {
+        let f = Fixture::new();
+        let position = f.position("index.wiki", "[[Page]]", 3);
+        let edit =
+            rename(&f.ws, &f.path("index.wiki"), position, "New Page").unwrap();
+
+        let operations = match edit.document_changes {
+            Some(DocumentChanges::Operations(x)) => x,
+            x => panic!("Unexpected changes: {:?}", x),
+        };
+
+        let mut texts = Vec::new();
+        for op in operations.iter() {
+            match op {
+                DocumentChangeOperation::Edit(x) => {
+                    for edit in x.edits.iter() {
+                        match edit {
+                            OneOf::Left(x) => texts.push(x.new_text.as_str()),
+                            OneOf::Right(x) => {
+                                texts.push(x.text_edit.new_text.as_str())
+                            }
+                        }
+                    }
+                }
+                DocumentChangeOperation::Op(ResourceOp::Rename(x)) => {
+                    assert_eq!(x.old_uri, f.uri("Page.wiki"));
+                    assert_eq!(x.new_uri, f.uri("New Page.wiki"));
+                }
+                x => panic!("Unexpected operation: {:?}", x),
+            }
+        }
+
+        assert_eq!(
+            texts,
+            vec![
+                "New Page",
+                "New Page",
+                "New Page",
+                "../New Page",
+                "/New Page"
+            ]
+        );
+    }
+
+    #[test]
+    fn formatting_should_replace_document_when_changed() {
+        let mut f = Fixture::new();
+        let path = f.path("new.wiki");
+        f.ws.open(path.clone(), String::from("=   Header   =\n"));
+
+        let edits = formatting(&f.ws, &path).unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.end, Position::new(1, 0));
+
+        f.ws.open(path.clone(), edits[0].new_text.clone());
+        assert_eq!(formatting(&f.ws, &path).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn relative_path_should_walk_up_to_common_ancestor() {
+        assert_eq!(
+            relative_path(Path::new("/a/b/c"), Path::new("/a/d/e")),
+            "../../d/e"
+        );
+        assert_eq!(relative_path(Path::new("/a"), Path::new("/a/b")), "b");
+    }
+}
@@ -0,0 +1,393 @@
+mod handlers;
+mod outline;
+mod position;
+mod workspace;
+
+use crate::{CommonOpt, LspSubcommand};
+use log::*;
+use lsp_server::{
+    Connection, ErrorCode, Message, Notification, Request, Response,
+};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as _, PublishDiagnostics,
+    },
+    request::{
+        Completion, DocumentSymbolRequest, Formatting, GotoDefinition,
+        HoverRequest, References, Rename, Request as _,
+    },
+    CompletionOptions, CompletionResponse, DocumentSymbolResponse,
+    GotoDefinitionResponse, HoverProviderCapability, InitializeParams, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use std::{io, path::PathBuf};
+use vimwiki::{HtmlConfig, VimwikiConfig};
+use workspace::Workspace;
+
+pub fn lsp(
+    _cmd: LspSubcommand,
+    _opt: CommonOpt,
+    config: HtmlConfig,
+    format_config: VimwikiConfig,
+) -> io::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(connection, Workspace::new(config, format_config))?;
+    io_threads.join()
+}
+
+/// Performs the initialization handshake with the client and then processes
+/// messages until the client shuts down the server
+fn serve(connection: Connection, mut workspace: Workspace) -> io::Result<()> {
+    let capabilities = serde_json::to_value(capabilities())?;
+    let params = connection.initialize(capabilities).map_err(to_io_error)?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+
+    // Treat each workspace folder that is not within a wiki as a wiki itself
+    #[allow(deprecated)]
+    let roots = params
+        .workspace_folders
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| x.uri)
+        .chain(params.root_uri);
+    for root in roots.filter_map(|x| x.to_file_path().ok()) {
+        workspace.add_root(&root);
+    }
+    workspace.load_wikis();
+
+    Server {
+        connection,
+        workspace,
+    }
+    .run()
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![
+                String::from("["),
+                String::from("#"),
+                String::from(":"),
+            ]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+struct Server {
+    connection: Connection,
+    workspace: Workspace,
+}
+
+impl Server {
+    fn run(&mut self) -> io::Result<()> {
+        while let Ok(msg) = self.connection.receiver.recv() {
+            match msg {
+                Message::Request(req) => {
+                    if self
+                        .connection
+                        .handle_shutdown(&req)
+                        .map_err(to_io_error)?
+                    {
+                        return Ok(());
+                    }
+
+                    let res = self.handle_request(req);
+                    self.send(Message::Response(res))?;
+                }
+                Message::Notification(n) => {
+                    if let Err(x) = self.handle_notification(n) {
+                        error!("Failed to handle notification: {}", x);
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send(&self, msg: Message) -> io::Result<()> {
+        self.connection.sender.send(msg).map_err(|x| {
+            io::Error::new(io::ErrorKind::BrokenPipe, x.to_string())
+        })
+    }
+
+    fn handle_request(&self, req: Request) -> Response {
+        trace!("handle_request(method = {})", req.method);
+        let ws = &self.workspace;
+
+        match req.method.as_str() {
+            GotoDefinition::METHOD => respond::<GotoDefinition>(req, |p| {
+                let p = p.text_document_position_params;
+                let path = to_path(&p.text_document.uri)?;
+                Ok(handlers::definition(ws, &path, p.position)
+                    .map(GotoDefinitionResponse::Scalar))
+            }),
+            References::METHOD => respond::<References>(req, |p| {
+                let path =
+                    to_path(&p.text_document_position.text_document.uri)?;
+                Ok(Some(handlers::references(
+                    ws,
+                    &path,
+                    p.text_document_position.position,
+                    p.context.include_declaration,
+                )))
+            }),
+            DocumentSymbolRequest::METHOD => {
+                respond::<DocumentSymbolRequest>(req, |p| {
+                    let path = to_path(&p.text_document.uri)?;
+                    Ok(Some(DocumentSymbolResponse::Nested(
+                        handlers::document_symbols(ws, &path),
+                    )))
+                })
+            }
+            Completion::METHOD => respond::<Completion>(req, |p| {
+                let p = p.text_document_position;
+                let path = to_path(&p.text_document.uri)?;
+                Ok(Some(CompletionResponse::Array(handlers::completion(
+                    ws, &path, p.position,
+                ))))
+            }),
+            HoverRequest::METHOD => respond::<HoverRequest>(req, |p| {
+                let p = p.text_document_position_params;
+                let path = to_path(&p.text_document.uri)?;
+                Ok(handlers::hover(ws, &path, p.position))
+            }),
+            Rename::METHOD => respond::<Rename>(req, |p| {
+                let position = p.text_document_position;
+                let path = to_path(&position.text_document.uri)?;
+                handlers::rename(ws, &path, position.position, &p.new_name)
+                    .map(Some)
+            }),
+            Formatting::METHOD => respond::<Formatting>(req, |p| {
+                let path = to_path(&p.text_document.uri)?;
+                handlers::formatting(ws, &path).map(Some)
+            }),
+            _ => Response::new_err(
+                req.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {}", req.method),
+            ),
+        }
+    }
+
+    fn handle_notification(&mut self, n: Notification) -> io::Result<()> {
+        trace!("handle_notification(method = {})", n.method);
+
+        match n.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let p = extract::<DidOpenTextDocument>(n)?;
+                let path =
+                    to_path(&p.text_document.uri).map_err(to_io_error)?;
+                self.workspace.open(path.clone(), p.text_document.text);
+                self.publish_diagnostics(p.text_document.uri, path)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let p = extract::<DidChangeTextDocument>(n)?;
+                let path =
+                    to_path(&p.text_document.uri).map_err(to_io_error)?;
+                for change in p.content_changes {
+                    let region = match (change.range, self.workspace.get(&path))
+                    {
+                        (Some(range), Some(doc)) => {
+                            Some(position::range_to_region(&doc.index, range))
+                        }
+                        _ => None,
+                    };
+                    self.workspace.edit(&path, region, change.text);
+                }
+                self.publish_diagnostics(p.text_document.uri, path)?;
+            }
+            DidCloseTextDocument::METHOD => {
+                let p = extract::<DidCloseTextDocument>(n)?;
+                let path =
+                    to_path(&p.text_document.uri).map_err(to_io_error)?;
+                self.workspace.close(&path);
+                self.send(Message::Notification(Notification::new(
+                    PublishDiagnostics::METHOD.to_string(),
+                    PublishDiagnosticsParams::new(
+                        p.text_document.uri,
+                        Vec::new(),
+                        None,
+                    ),
+                )))?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn publish_diagnostics(&self, uri: Url, path: PathBuf) -> io::Result<()> {
+        let diagnostics = handlers::diagnostics(&self.workspace, &path);
+        self.send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams::new(uri, diagnostics, None),
+        )))
+    }
+}
+
+/// Extracts the parameters of a request and produces a response from the
+/// result of `f`
+fn respond<R>(
+    req: Request,
+    f: impl FnOnce(R::Params) -> Result<R::Result, String>,
+) -> Response
+where
+    R: lsp_types::request::Request,
+{
+    let id = req.id.clone();
+    match req.extract::<R::Params>(R::METHOD) {
+        Ok((id, params)) => match f(params) {
+            Ok(result) => Response::new_ok(id, result),
+            Err(x) => Response::new_err(id, ErrorCode::RequestFailed as i32, x),
+        },
+        Err(x) => Response::new_err(
+            id,
+            ErrorCode::InvalidParams as i32,
+            x.to_string(),
+        ),
+    }
+}
+
+fn extract<N>(n: Notification) -> io::Result<N::Params>
+where
+    N: lsp_types::notification::Notification,
+{
+    n.extract::<N::Params>(N::METHOD)
+        .map_err(|x| io::Error::new(io::ErrorKind::InvalidData, x.to_string()))
+}
+
+fn to_path(uri: &Url) -> Result<PathBuf, String> {
+    uri.to_file_path()
+        .map_err(|_| format!("Unsupported uri: {}", uri))
+}
+
+fn to_io_error(x: impl ToString) -> io::Error {
+    io::Error::other(x.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::RequestId;
+    use lsp_types::{
+        notification::{Exit, Initialized},
+        request::{Initialize, Shutdown},
+        ClientCapabilities, DidOpenTextDocumentParams, DocumentSymbolParams,
+        InitializedParams, TextDocumentIdentifier, TextDocumentItem,
+    };
+
+    #[test]
+    fn serve_should_respond_to_requests_until_shutdown() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().canonicalize().unwrap().join("page.wiki");
+        let uri = Url::from_file_path(&path).unwrap();
+
+        let (client, server) = Connection::memory();
+        let handle = std::thread::spawn(move || {
+            let ws =
+                Workspace::new(HtmlConfig::default(), VimwikiConfig::default());
+            serve(server, ws)
+        });
+
+        let request = |id: i32, method: &str, params: serde_json::Value| {
+            client
+                .sender
+                .send(Message::Request(Request::new(
+                    RequestId::from(id),
+                    method.to_string(),
+                    params,
+                )))
+                .unwrap();
+        };
+        let notify = |method: &str, params: serde_json::Value| {
+            client
+                .sender
+                .send(Message::Notification(Notification::new(
+                    method.to_string(),
+                    params,
+                )))
+                .unwrap();
+        };
+
+        #[allow(deprecated)]
+        let params = InitializeParams {
+            capabilities: ClientCapabilities::default(),
+            ..Default::default()
+        };
+        request(1, Initialize::METHOD, serde_json::to_value(params).unwrap());
+        match client.receiver.recv().unwrap() {
+            Message::Response(x) => assert!(x.error.is_none(), "{:?}", x),
+            x => panic!("Unexpected message: {:?}", x),
+        }
+        notify(
+            Initialized::METHOD,
+            serde_json::to_value(InitializedParams {}).unwrap(),
+        );
+
+        notify(
+            DidOpenTextDocument::METHOD,
+            serde_json::to_value(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    uri.clone(),
+                    String::from("vimwiki"),
+                    0,
+                    String::from("= Header =\n[[missing]]\n"),
+                ),
+            })
+            .unwrap(),
+        );
+        match client.receiver.recv().unwrap() {
+            Message::Notification(x) => {
+                assert_eq!(x.method, PublishDiagnostics::METHOD);
+                let params: PublishDiagnosticsParams =
+                    serde_json::from_value(x.params).unwrap();
+                assert_eq!(params.diagnostics.len(), 1);
+            }
+            x => panic!("Unexpected message: {:?}", x),
+        }
+
+        request(
+            2,
+            DocumentSymbolRequest::METHOD,
+            serde_json::to_value(DocumentSymbolParams {
+                text_document: TextDocumentIdentifier::new(uri),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap(),
+        );
+        match client.receiver.recv().unwrap() {
+            Message::Response(x) => {
+                let symbols: DocumentSymbolResponse =
+                    serde_json::from_value(x.result.unwrap()).unwrap();
+                match symbols {
+                    DocumentSymbolResponse::Nested(x) => {
+                        assert_eq!(x[0].name, "Header")
+                    }
+                    x => panic!("Unexpected symbols: {:?}", x),
+                }
+            }
+            x => panic!("Unexpected message: {:?}", x),
+        }
+
+        request(3, Shutdown::METHOD, serde_json::Value::Null);
+        notify(Exit::METHOD, serde_json::Value::Null);
+        handle.join().unwrap().expect("Server failed");
+    }
+}
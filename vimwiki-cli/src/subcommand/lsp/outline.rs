@@ -0,0 +1,185 @@
+use vimwiki::{
+    BlockElement, Element, InlineElement, IntoChildren, Link, LinkData,
+    Located, Page, Region,
+};
+
+/// Represents a header found within a page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderInfo {
+    pub level: usize,
+    pub text: String,
+
+    /// Region of the entire header including its `=` markers
+    pub region: Region,
+
+    /// Region of just the text of the header
+    pub content_region: Region,
+}
+
+/// Represents a link found within a page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkInfo {
+    pub region: Region,
+    pub link: Link<'static>,
+}
+
+/// Represents a set of tags (`:tag1:tag2:`) found within a page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagsInfo {
+    pub region: Region,
+    pub names: Vec<String>,
+}
+
+/// Represents the elements of a page that are of interest to the language
+/// server, listed in the order they appear within the page
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Outline {
+    pub headers: Vec<HeaderInfo>,
+    pub links: Vec<LinkInfo>,
+    pub tags: Vec<TagsInfo>,
+}
+
+impl Outline {
+    pub fn new(page: &Page<'_>) -> Self {
+        let mut outline = Self::default();
+
+        // Walk depth-first through the page, pushing children in reverse so
+        // that they are popped in the order they appear
+        let mut stack: Vec<Located<Element>> = page
+            .to_borrowed()
+            .into_children()
+            .into_iter()
+            .rev()
+            .map(|x| x.map(Element::from))
+            .collect();
+
+        while let Some(located) = stack.pop() {
+            let region = located.region();
+            match located.as_inner() {
+                Element::Block(BlockElement::Header(header)) => {
+                    let regions: Vec<Region> =
+                        header.content.iter().map(|x| x.region()).collect();
+                    let content_region = match (
+                        regions.iter().map(|x| x.offset()).min(),
+                        regions.iter().map(|x| x.offset() + x.len()).max(),
+                    ) {
+                        (Some(start), Some(end)) => {
+                            Region::new(start, end - start)
+                        }
+                        _ => Region::new(region.offset(), 0),
+                    };
+
+                    outline.headers.push(HeaderInfo {
+                        level: header.level,
+                        text: header.content.to_string().trim().to_string(),
+                        region,
+                        content_region,
+                    });
+                }
+                Element::Inline(InlineElement::Link(link)) => {
+                    outline.links.push(LinkInfo {
+                        region,
+                        link: link.clone().into_owned(),
+                    });
+                }
+                Element::Inline(InlineElement::Tags(tags)) => {
+                    outline.tags.push(TagsInfo {
+                        region,
+                        names: tags
+                            .iter()
+                            .map(|x| x.as_str().to_string())
+                            .collect(),
+                    });
+                }
+                _ => {}
+            }
+
+            stack
+                .extend(located.into_inner().into_children().into_iter().rev());
+        }
+
+        outline
+    }
+
+    /// Finds the link containing the given byte offset
+    pub fn link_at(&self, offset: usize) -> Option<&LinkInfo> {
+        self.links.iter().find(|x| x.region.contains(offset))
+    }
+
+    /// Finds the header containing the given byte offset
+    pub fn header_at(&self, offset: usize) -> Option<&HeaderInfo> {
+        self.headers.iter().find(|x| x.region.contains(offset))
+    }
+
+    /// Finds the region of the header or tag that an anchor refers to
+    pub fn find_anchor(&self, anchor: &str) -> Option<Region> {
+        self.headers
+            .iter()
+            .find(|x| x.text == anchor)
+            .map(|x| x.region)
+            .or_else(|| {
+                self.tags
+                    .iter()
+                    .find(|x| x.names.iter().any(|name| name == anchor))
+                    .map(|x| x.region)
+            })
+    }
+}
+
+/// Returns the decoded final part of a link's anchor, which is the header or
+/// tag being linked (e.g. `Header 2` from `page#Header 1#Header 2`)
+pub fn link_anchor(data: &LinkData<'_>) -> Option<String> {
+    data.to_anchor()
+        .and_then(|anchor| {
+            anchor
+                .iter()
+                .last()
+                .map(|x| LinkData::decode_uri(x.as_bytes()))
+        })
+        .filter(|x| !x.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vimwiki::Language;
+
+    fn outline(text: &str) -> Outline {
+        let page: Page = Language::from_vimwiki_str(text).parse().unwrap();
+        Outline::new(&page)
+    }
+
+    #[test]
+    fn new_should_collect_headers_links_and_tags_in_order() {
+        let text = "= One =\n- [[Page#Some Header]]\n== Two ==\n:a:b:\n";
+        let outline = outline(text);
+
+        assert_eq!(
+            outline
+                .headers
+                .iter()
+                .map(|x| (x.level, x.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(1, "One"), (2, "Two")]
+        );
+        assert_eq!(
+            &text[outline.headers[1].content_region.offset()..][..3],
+            "Two"
+        );
+
+        assert_eq!(outline.links.len(), 1);
+        assert_eq!(
+            link_anchor(outline.links[0].link.data()).as_deref(),
+            Some("Some Header")
+        );
+        assert_eq!(
+            outline.link_at(12).map(|x| x.region),
+            Some(outline.links[0].region)
+        );
+
+        assert_eq!(outline.tags[0].names, vec!["a", "b"]);
+        assert_eq!(outline.find_anchor("b"), Some(outline.tags[0].region));
+        assert_eq!(outline.find_anchor("One"), Some(outline.headers[0].region));
+        assert_eq!(outline.find_anchor("Three"), None);
+    }
+}
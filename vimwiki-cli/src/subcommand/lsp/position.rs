@@ -0,0 +1,85 @@
+use lsp_types::{Position, Range};
+use vimwiki::{LineColumn, LineIndex, Region};
+
+/// Converts a byte offset into a position using the index, which is expected
+/// to measure columns in UTF-16 code units as is the default for LSP; offsets
+/// that do not land on a valid position are moved back to the nearest one
+pub fn offset_to_position(index: &LineIndex, offset: usize) -> Position {
+    let position = (0..=offset.min(index.len()))
+        .rev()
+        .find_map(|offset| index.line_col(offset))
+        .unwrap_or_default();
+
+    Position::new(position.line as u32, position.column as u32)
+}
+
+/// Converts a position into a byte offset using the index, clamping to the
+/// end of the line or text and moving back from the middle of a character
+pub fn position_to_offset(index: &LineIndex, position: Position) -> usize {
+    let line = position.line as usize;
+    let line_region = match index.line_region(line) {
+        Some(region) => region,
+        None => return index.len(),
+    };
+
+    // NOTE: A line never has more columns than bytes, so clamping first
+    //       keeps columns far past the end of the line from being searched
+    let column = (position.character as usize).min(line_region.len());
+    (0..=column)
+        .rev()
+        .find_map(|column| index.offset(LineColumn::new(line, column)))
+        .map_or(line_region.offset(), |offset| {
+            offset.min(line_region.offset() + line_region.len())
+        })
+}
+
+/// Converts a region into a range of positions
+pub fn region_to_range(index: &LineIndex, region: Region) -> Range {
+    Range::new(
+        offset_to_position(index, region.offset()),
+        offset_to_position(index, region.offset() + region.len()),
+    )
+}
+
+/// Converts a range of positions into a region
+pub fn range_to_region(index: &LineIndex, range: Range) -> Region {
+    let start = position_to_offset(index, range.start);
+    let end = position_to_offset(index, range.end).max(start);
+    Region::new(start, end - start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vimwiki::ColumnEncoding;
+
+    fn index(text: &str) -> LineIndex {
+        LineIndex::new(text).with_encoding(ColumnEncoding::Utf16)
+    }
+
+    #[test]
+    fn offset_to_position_should_count_columns_in_utf16() {
+        let index = index("ab\n𝄞x\n");
+        assert_eq!(offset_to_position(&index, 1), Position::new(0, 1));
+        assert_eq!(offset_to_position(&index, 3), Position::new(1, 0));
+        assert_eq!(offset_to_position(&index, 5), Position::new(1, 0));
+        assert_eq!(offset_to_position(&index, 7), Position::new(1, 2));
+        assert_eq!(offset_to_position(&index, 100), Position::new(2, 0));
+    }
+
+    #[test]
+    fn position_to_offset_should_be_inverse_of_offset_to_position() {
+        let index = index("ab\n𝄞x\n");
+        for offset in [0, 1, 2, 3, 7, 8, 9] {
+            let position = offset_to_position(&index, offset);
+            assert_eq!(position_to_offset(&index, position), offset);
+        }
+
+        // Positions past the end of a line or the text are clamped, and
+        // positions within a character move back to its start
+        assert_eq!(position_to_offset(&index, Position::new(0, 10)), 2);
+        assert_eq!(position_to_offset(&index, Position::new(10, 0)), 9);
+        assert_eq!(position_to_offset(&index, Position::new(1, 1)), 3);
+        assert_eq!(position_to_offset(&index, Position::new(0, u32::MAX)), 2);
+    }
+}
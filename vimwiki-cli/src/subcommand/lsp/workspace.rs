@@ -0,0 +1,488 @@
+use super::outline::{link_anchor, Outline};
+use log::*;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ffi::OsStr,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use vimwiki::{
+    ColumnEncoding, HtmlConfig, HtmlWikiConfig, Language, LineIndex, Link,
+    LinkData, Page, Region, TextEdit, VimwikiConfig,
+};
+use walkdir::WalkDir;
+
+/// Represents a failure to parse the text of a document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentError {
+    pub offset: usize,
+    pub message: String,
+}
+
+/// Represents a vimwiki file known to the language server, either opened by
+/// the client or loaded from disk as part of a wiki
+#[derive(Clone, Debug)]
+pub struct Document {
+    pub text: String,
+    pub index: LineIndex,
+    pub page: Option<Page<'static>>,
+    pub error: Option<DocumentError>,
+    pub outline: Outline,
+    pub open: bool,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let mut doc = Self {
+            index: LineIndex::new(&text).with_encoding(ColumnEncoding::Utf16),
+            text,
+            page: None,
+            error: None,
+            outline: Outline::default(),
+            open: false,
+        };
+        let result = parse(&doc.text);
+        doc.set_page(result);
+        doc
+    }
+
+    /// Applies a change to the document, replacing the text within `region`
+    /// or the entire text if no region is provided, and then updating the
+    /// page by reparsing only what was changed where possible
+    pub fn edit(&mut self, region: Option<Region>, text: String) {
+        let region = match region {
+            Some(region) => region,
+            None => {
+                *self = Self {
+                    open: self.open,
+                    ..Self::new(text)
+                };
+                return;
+            }
+        };
+
+        let edit = TextEdit::new(region, text);
+        let text = edit.apply(&self.text);
+        let result = match self.page.as_ref() {
+            Some(page) => match page.reparse(&text, &edit) {
+                Ok(x) => Ok(x.page.into_owned()),
+                Err(_) => parse(&text),
+            },
+            None => parse(&text),
+        };
+
+        self.text = text;
+        self.index =
+            LineIndex::new(&self.text).with_encoding(ColumnEncoding::Utf16);
+        self.set_page(result);
+    }
+
+    fn set_page(&mut self, result: Result<Page<'static>, DocumentError>) {
+        match result {
+            Ok(page) => {
+                self.outline = Outline::new(&page);
+                self.page = Some(page);
+                self.error = None;
+            }
+            Err(x) => {
+                self.outline = Outline::default();
+                self.page = None;
+                self.error = Some(x);
+            }
+        }
+    }
+}
+
+fn parse(text: &str) -> Result<Page<'static>, DocumentError> {
+    Language::from_vimwiki_str(text)
+        .parse::<Page>()
+        .map(Page::into_owned)
+        .map_err(|x| DocumentError {
+            offset: x.offset(),
+            message: x.to_string(),
+        })
+}
+
+/// Represents the destination of a link
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub path: PathBuf,
+    pub anchor: Option<String>,
+}
+
+/// Represents the collection of wikis and documents known to the language
+/// server
+pub struct Workspace {
+    pub config: HtmlConfig,
+    pub format_config: VimwikiConfig,
+    documents: HashMap<PathBuf, Document>,
+}
+
+impl Workspace {
+    pub fn new(config: HtmlConfig, format_config: VimwikiConfig) -> Self {
+        Self {
+            config,
+            format_config,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Adds a temporary wiki rooted at `path` if it is not already part of
+    /// a wiki, returning true if added
+    pub fn add_root(&mut self, path: &Path) -> bool {
+        if self.config.find_wiki_by_path(path).is_some() {
+            return false;
+        }
+
+        debug!("Creating temporary wiki for {:?}", path);
+        self.config.wikis.push(HtmlWikiConfig {
+            path: path.to_path_buf(),
+            ..Default::default()
+        });
+        true
+    }
+
+    /// Loads all pages from each wiki that are not already known
+    pub fn load_wikis(&mut self) {
+        for wiki in self.config.wikis.iter() {
+            debug!("Loading wiki {:?} from {:?}", wiki.name, wiki.path);
+            for entry in WalkDir::new(wiki.path.as_path())
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| {
+                    e.file_type().is_file()
+                        && e.path().extension().and_then(OsStr::to_str)
+                            == Some(wiki.ext.as_str())
+                })
+            {
+                if self.documents.contains_key(entry.path()) {
+                    continue;
+                }
+
+                match fs::read_to_string(entry.path()) {
+                    Ok(text) => {
+                        self.documents.insert(
+                            entry.path().to_path_buf(),
+                            Document::new(text),
+                        );
+                    }
+                    Err(x) => {
+                        error!("Failed to load {:?}: {}", entry.path(), x)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks a document as opened by the client using the client's text
+    pub fn open(&mut self, path: PathBuf, text: String) {
+        let mut doc = Document::new(text);
+        doc.open = true;
+        self.documents.insert(path, doc);
+    }
+
+    /// Applies a change to an open document
+    pub fn edit(&mut self, path: &Path, region: Option<Region>, text: String) {
+        if let Some(doc) = self.documents.get_mut(path) {
+            doc.edit(region, text);
+        }
+    }
+
+    /// Marks a document as closed by the client, reverting to what is on
+    /// disk or forgetting the document if it no longer exists
+    pub fn close(&mut self, path: &Path) {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                self.documents
+                    .insert(path.to_path_buf(), Document::new(text));
+            }
+            Err(_) => {
+                self.documents.remove(path);
+            }
+        }
+    }
+
+    /// Returns a known document
+    pub fn get(&self, path: &Path) -> Option<&Document> {
+        self.documents.get(path)
+    }
+
+    /// Returns a known document, falling back to loading it from disk
+    pub fn document(&self, path: &Path) -> Option<Cow<'_, Document>> {
+        self.documents.get(path).map(Cow::Borrowed).or_else(|| {
+            fs::read_to_string(path)
+                .ok()
+                .map(|text| Cow::Owned(Document::new(text)))
+        })
+    }
+
+    /// Returns whether or not a page exists at the given path
+    pub fn exists(&self, path: &Path) -> bool {
+        self.documents.contains_key(path) || path.is_file()
+    }
+
+    /// Iterates over all known documents
+    pub fn documents(&self) -> impl Iterator<Item = (&PathBuf, &Document)> {
+        self.documents.iter()
+    }
+
+    /// Returns the wiki containing the file at `path`, or a temporary wiki
+    /// rooted at the file's directory
+    pub fn wiki_for(&self, path: &Path) -> Cow<'_, HtmlWikiConfig> {
+        match self.config.find_wiki_by_path(path) {
+            Some(wiki) => Cow::Borrowed(wiki),
+            None => Cow::Owned(HtmlWikiConfig {
+                path: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                ext: path
+                    .extension()
+                    .and_then(OsStr::to_str)
+                    .map(ToString::to_string)
+                    .unwrap_or_else(HtmlWikiConfig::default_ext),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Returns the name of the page at `path`, which is its path relative to
+    /// the root of its wiki without an extension (e.g. `dir/page`)
+    pub fn page_name(&self, path: &Path) -> String {
+        let wiki = self.wiki_for(path);
+        let path = wiki.path_within(path).unwrap_or(path).with_extension("");
+        path.components()
+            .filter_map(|c| match c {
+                Component::Normal(x) => Some(x.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Returns the path of the page with the given name within a wiki
+    pub fn page_path(&self, wiki: &HtmlWikiConfig, name: &str) -> PathBuf {
+        with_ext(
+            normalize_path(&wiki.path.join(make_relative(Path::new(name)))),
+            &wiki.ext,
+        )
+    }
+
+    /// Resolves the page (and optional anchor) targeted by a link found
+    /// within the file at `src`, returning None if the link does not target
+    /// a wiki page or an error if the link references a wiki that does not
+    /// exist
+    pub fn resolve(
+        &self,
+        src: &Path,
+        link: &Link<'_>,
+    ) -> Option<Result<Target, String>> {
+        let data = link.data();
+        let (wiki, path) = match link {
+            Link::Wiki { data } if data.is_local_anchor() => {
+                return Some(Ok(Target {
+                    path: src.to_path_buf(),
+                    anchor: link_anchor(data),
+                }));
+            }
+            Link::Wiki { data } if data.scheme().is_none() => {
+                let wiki = self.wiki_for(src);
+                let path = link_path(data);
+                let path = if data.uri_ref.path().is_absolute() {
+                    wiki.path.join(path)
+                } else {
+                    src.parent().unwrap_or_else(|| Path::new("")).join(path)
+                };
+                (wiki, path)
+            }
+            Link::IndexedInterWiki { index, data } => {
+                match self.config.find_wiki_by_index(*index as usize) {
+                    Some(wiki) => {
+                        (Cow::Borrowed(wiki), wiki.path.join(link_path(data)))
+                    }
+                    None => {
+                        return Some(Err(format!(
+                            "No wiki with index {}",
+                            index
+                        )))
+                    }
+                }
+            }
+            Link::NamedInterWiki { name, data } => {
+                match self.config.find_wiki_by_name(name) {
+                    Some(wiki) => {
+                        (Cow::Borrowed(wiki), wiki.path.join(link_path(data)))
+                    }
+                    None => {
+                        return Some(Err(format!("No wiki named {}", name)))
+                    }
+                }
+            }
+            Link::Diary { date, data } => {
+                let wiki = self.wiki_for(src);
+                let path = wiki
+                    .path
+                    .join(wiki.diary_rel_path.as_path())
+                    .join(date.to_string());
+                return Some(Ok(Target {
+                    path: with_ext(path, &wiki.ext),
+                    anchor: link_anchor(data),
+                }));
+            }
+            _ => return None,
+        };
+
+        let mut path = normalize_path(&path);
+        if data.is_path_dir() {
+            path.push("index");
+        }
+
+        Some(Ok(Target {
+            path: with_ext(path, &wiki.ext),
+            anchor: link_anchor(data),
+        }))
+    }
+}
+
+/// Produces the decoded, relative path of a link
+fn link_path(data: &LinkData<'_>) -> PathBuf {
+    let path = data.to_path_buf();
+    make_relative(Path::new(&LinkData::decode_uri(
+        path.to_string_lossy().as_bytes(),
+    )))
+}
+
+/// Strips any root or prefix from a path
+fn make_relative(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, Component::RootDir | Component::Prefix(_)))
+        .collect()
+}
+
+/// Appends the extension to a path unless it already has it
+fn with_ext(path: PathBuf, ext: &str) -> PathBuf {
+    if path.extension().and_then(OsStr::to_str) == Some(ext) {
+        path
+    } else {
+        let mut path = path.into_os_string();
+        path.push(".");
+        path.push(ext);
+        PathBuf::from(path)
+    }
+}
+
+/// Removes `.` and `..` from a path without touching the filesystem
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut ret = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                ret.pop();
+            }
+            c => ret.push(c.as_os_str()),
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> Workspace {
+        let config = HtmlConfig {
+            wikis: vec![
+                HtmlWikiConfig {
+                    path: PathBuf::from("/wiki"),
+                    ..Default::default()
+                },
+                HtmlWikiConfig {
+                    path: PathBuf::from("/other"),
+                    name: Some(String::from("other")),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        Workspace::new(config, VimwikiConfig::default())
+    }
+
+    fn resolve(ws: &Workspace, link: &str) -> Option<Result<Target, String>> {
+        let page: Page = Language::from_vimwiki_str(link).parse().unwrap();
+        let outline = Outline::new(&page);
+        ws.resolve(Path::new("/wiki/dir/page.wiki"), &outline.links[0].link)
+    }
+
+    fn target(
+        path: &str,
+        anchor: Option<&str>,
+    ) -> Option<Result<Target, String>> {
+        Some(Ok(Target {
+            path: PathBuf::from(path),
+            anchor: anchor.map(ToString::to_string),
+        }))
+    }
+
+    #[test]
+    fn resolve_should_support_relative_and_absolute_wiki_links() {
+        let ws = workspace();
+        assert_eq!(
+            resolve(&ws, "[[Some Page]]"),
+            target("/wiki/dir/Some Page.wiki", None)
+        );
+        assert_eq!(
+            resolve(&ws, "[[../top#Header 1]]"),
+            target("/wiki/top.wiki", Some("Header 1"))
+        );
+        assert_eq!(
+            resolve(&ws, "[[/sub/]]"),
+            target("/wiki/sub/index.wiki", None)
+        );
+        assert_eq!(
+            resolve(&ws, "[[#Local]]"),
+            target("/wiki/dir/page.wiki", Some("Local"))
+        );
+        assert_eq!(resolve(&ws, "[[https://example.com]]"), None);
+    }
+
+    #[test]
+    fn resolve_should_support_interwiki_and_diary_links() {
+        let ws = workspace();
+        assert_eq!(
+            resolve(&ws, "[[wiki1:Page]]"),
+            target("/other/Page.wiki", None)
+        );
+        assert_eq!(
+            resolve(&ws, "[[wn.other:a/b]]"),
+            target("/other/a/b.wiki", None)
+        );
+        assert_eq!(
+            resolve(&ws, "[[diary:2021-03-04]]"),
+            target("/wiki/diary/2021-03-04.wiki", None)
+        );
+        assert!(matches!(resolve(&ws, "[[wiki9:Page]]"), Some(Err(_))));
+        assert!(matches!(resolve(&ws, "[[wn.missing:Page]]"), Some(Err(_))));
+    }
+
+    #[test]
+    fn page_name_should_be_relative_to_wiki_root_without_extension() {
+        let ws = workspace();
+        assert_eq!(ws.page_name(Path::new("/wiki/dir/page.wiki")), "dir/page");
+        assert_eq!(ws.page_name(Path::new("/elsewhere/page.wiki")), "page");
+        assert_eq!(
+            ws.page_path(&ws.config.wikis[0], "dir/page"),
+            PathBuf::from("/wiki/dir/page.wiki")
+        );
+    }
+
+    #[test]
+    fn edit_should_reparse_document() {
+        let mut doc = Document::new(String::from("= One =\n\n[[a]]\n"));
+        doc.edit(Some(Region::new(2, 3)), String::from("Two"));
+        assert_eq!(doc.text, "= Two =\n\n[[a]]\n");
+        assert_eq!(doc.outline.headers[0].text, "Two");
+        assert_eq!(doc.outline.links[0].region.offset(), 9);
+
+        doc.edit(None, String::from("text"));
+        assert!(doc.outline.links.is_empty());
+    }
+}
@@ -1,9 +1,15 @@
 mod convert;
+mod css;
 mod format;
 mod inspect;
+mod lsp;
+mod schema;
 mod serve;
 
-pub use convert::convert;
+pub use convert::{convert, Glob, MetadataPattern};
+pub use css::css;
 pub use format::format;
 pub use inspect::inspect;
+pub use lsp::lsp;
+pub use schema::schema;
 pub use serve::serve;
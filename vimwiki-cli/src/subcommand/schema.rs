@@ -0,0 +1,22 @@
+use crate::{Ast, CommonOpt, SchemaSubcommand};
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+pub fn schema(cmd: SchemaSubcommand, _opt: CommonOpt) -> io::Result<()> {
+    let SchemaSubcommand { output } = cmd;
+    let schema = Ast::json_schema();
+
+    if let Some(path) = output {
+        let file = fs::File::create(path)?;
+        let mut writer = io::BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &schema)
+            .map_err(io::Error::from)?;
+        writer.flush()?;
+        Ok(())
+    } else {
+        let stdout = io::stdout();
+        serde_json::to_writer_pretty(stdout, &schema).map_err(io::Error::from)
+    }
+}
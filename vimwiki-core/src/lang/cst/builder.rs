@@ -0,0 +1,248 @@
+use super::{
+    CstElement, CstNode, CstNodeKind, CstToken, CstTokenKind, Trivia,
+    TriviaKind,
+};
+use crate::lang::elements::{Element, IntoChildren, Located, Page, Region};
+
+/// Builds the root node of a tree from the original text and its page
+pub fn build<'a>(text: &'a str, page: &Page<'_>) -> CstNode<'a> {
+    let children = page
+        .elements
+        .iter()
+        .map(|x| x.as_ref().map(|x| Element::from(x.to_borrowed())))
+        .collect();
+
+    let mut root = node(
+        text,
+        CstNodeKind::Page,
+        Region::new(0, text.len()),
+        children,
+    );
+
+    // NOTE: The end-of-input token always exists so that whitespace at the
+    //       end of the text (or text that is only whitespace) has a token
+    //       to be attached to as trivia
+    root.children.push(CstElement::Token(CstToken {
+        kind: CstTokenKind::Eof,
+        text: "",
+        region: Region::new(text.len(), 0),
+        leading_trivia: Vec::new(),
+        trailing_trivia: Vec::new(),
+    }));
+
+    attach_trivia(text, &mut root);
+    root
+}
+
+/// Builds a node spanning `region`, converting the text between children
+/// into syntax tokens. Nodes other than the page that have no children are
+/// treated as a single content token.
+fn node<'a>(
+    text: &'a str,
+    kind: CstNodeKind,
+    region: Region,
+    mut children: Vec<Located<Element<'_>>>,
+) -> CstNode<'a> {
+    let start = region.offset();
+    let end = region.offset() + region.len();
+
+    // NOTE: Children are not guaranteed to be in order (e.g. table cells are
+    //       stored by column), so we sort them by their position first
+    children.sort_by_key(|x| x.region().offset());
+
+    let mut elements = Vec::new();
+    let mut cursor = start;
+    for child in children {
+        let r = child.region();
+        let (child_start, child_end) = (r.offset(), r.offset() + r.len());
+
+        // Skip children that are empty or overlap with something already
+        // processed; their text is picked up as syntax tokens instead
+        if r.is_empty() || child_start < cursor || child_end > end {
+            continue;
+        }
+
+        push_tokens(text, cursor, child_start, &mut elements);
+
+        let kind = CstNodeKind::from(child.as_inner());
+        let children = child.into_inner().into_children();
+        elements.push(CstElement::Node(node(text, kind, r, children)));
+        cursor = child_end;
+    }
+
+    if elements.is_empty() && kind != CstNodeKind::Page {
+        let content = &text[start..end];
+        let trimmed = content.trim_start();
+        let offset = start + (content.len() - trimmed.len());
+        let trimmed = trimmed.trim_end();
+
+        if !trimmed.is_empty() {
+            elements.push(CstElement::Token(CstToken {
+                kind: CstTokenKind::Content,
+                text: trimmed,
+                region: Region::new_at_depth(
+                    offset,
+                    trimmed.len(),
+                    region.depth(),
+                ),
+                leading_trivia: Vec::new(),
+                trailing_trivia: Vec::new(),
+            }));
+        }
+    } else {
+        push_tokens(text, cursor, end, &mut elements);
+    }
+
+    CstNode {
+        kind,
+        region,
+        children: elements,
+    }
+}
+
+/// Converts each run of non-whitespace characters between `start` and `end`
+/// into a syntax token
+fn push_tokens<'a>(
+    text: &'a str,
+    start: usize,
+    end: usize,
+    elements: &mut Vec<CstElement<'a>>,
+) {
+    let mut run_start = None;
+    for (i, c) in text[start..end]
+        .char_indices()
+        .map(|(i, c)| (start + i, c))
+        .chain(std::iter::once((end, ' ')))
+    {
+        match (run_start, c.is_whitespace()) {
+            (None, false) => run_start = Some(i),
+            (Some(s), true) => {
+                elements.push(CstElement::Token(CstToken {
+                    kind: CstTokenKind::Syntax,
+                    text: &text[s..i],
+                    region: Region::new(s, i - s),
+                    leading_trivia: Vec::new(),
+                    trailing_trivia: Vec::new(),
+                }));
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Assigns the whitespace between tokens as trivia, where whitespace after
+/// a token up to and including the first newline belongs to that token and
+/// everything else belongs to the token that follows
+fn attach_trivia<'a>(text: &'a str, root: &mut CstNode<'a>) {
+    let ranges: Vec<(usize, usize)> = root
+        .tokens()
+        .map(|x| (x.region.offset(), x.region.offset() + x.region.len()))
+        .collect();
+
+    let mut trivia = vec![(Vec::new(), Vec::new()); ranges.len()];
+    let mut cursor = 0;
+    for (i, (start, end)) in ranges.into_iter().enumerate() {
+        let mut gap_start = cursor;
+        if i > 0 {
+            let gap = &text[cursor..start];
+            let len = gap.find('\n').map(|x| x + 1).unwrap_or(gap.len());
+            trivia[i - 1].1 = split_trivia(text, cursor, cursor + len);
+            gap_start = cursor + len;
+        }
+
+        trivia[i].0 = split_trivia(text, gap_start, start);
+        cursor = end;
+    }
+
+    fn assign<'a>(
+        node: &mut CstNode<'a>,
+        trivia: &mut impl Iterator<Item = (Vec<Trivia<'a>>, Vec<Trivia<'a>>)>,
+    ) {
+        for child in node.children.iter_mut() {
+            match child {
+                CstElement::Node(x) => assign(x, trivia),
+                CstElement::Token(x) => {
+                    let (leading, trailing) = trivia.next().unwrap_or_default();
+                    x.leading_trivia = leading;
+                    x.trailing_trivia = trailing;
+                }
+            }
+        }
+    }
+
+    assign(root, &mut trivia.into_iter());
+}
+
+/// Splits whitespace between `start` and `end` into newlines and runs of
+/// other whitespace
+fn split_trivia(text: &str, start: usize, end: usize) -> Vec<Trivia<'_>> {
+    let s = &text[start..end];
+    let bytes = s.as_bytes();
+    let mut trivia = Vec::new();
+    let mut i = 0;
+
+    while i < s.len() {
+        let (kind, len) = if bytes[i..].starts_with(b"\r\n") {
+            (TriviaKind::Newline, 2)
+        } else if bytes[i] == b'\n' {
+            (TriviaKind::Newline, 1)
+        } else {
+            let len = match s[i..].find('\n') {
+                Some(n) if bytes[i + n - 1] == b'\r' => n - 1,
+                Some(n) => n,
+                None => s.len() - i,
+            };
+            (TriviaKind::Whitespace, len)
+        };
+
+        trivia.push(Trivia {
+            kind,
+            text: &s[i..i + len],
+            region: Region::new(start + i, len),
+        });
+        i += len;
+    }
+
+    trivia
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_trivia_should_separate_newlines_from_other_whitespace() {
+        let text = "a \t\n\r\n  \r\nb";
+        let trivia = split_trivia(text, 1, text.len() - 1);
+        assert_eq!(
+            trivia
+                .iter()
+                .map(|x| (x.kind(), x.text()))
+                .collect::<Vec<_>>(),
+            vec![
+                (TriviaKind::Whitespace, " \t"),
+                (TriviaKind::Newline, "\n"),
+                (TriviaKind::Newline, "\r\n"),
+                (TriviaKind::Whitespace, "  "),
+                (TriviaKind::Newline, "\r\n"),
+            ]
+        );
+        assert_eq!(trivia[3].region(), Region::new(6, 2));
+    }
+
+    #[test]
+    fn push_tokens_should_produce_a_token_per_run_of_non_whitespace() {
+        let text = "= abc  de=";
+        let mut elements = Vec::new();
+        push_tokens(text, 0, text.len(), &mut elements);
+        assert_eq!(
+            elements
+                .iter()
+                .map(|x| x.as_token().unwrap().text())
+                .collect::<Vec<_>>(),
+            vec!["=", "abc", "de="]
+        );
+        assert_eq!(elements[2].as_token().unwrap().region(), Region::new(7, 3));
+    }
+}
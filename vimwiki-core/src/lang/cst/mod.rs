@@ -0,0 +1,583 @@
+use crate::lang::{
+    elements::{
+        BlockElement, Comment, Element, InlineBlockElement, InlineElement,
+        Page, Region,
+    },
+    parsers::{self, vimwiki, Span},
+};
+use std::fmt;
+
+mod builder;
+
+/// Represents a lossless, concrete syntax tree built on top of a parsed
+/// [`Page`]. Every byte of the original input belongs to exactly one token
+/// or piece of trivia, meaning that the original text can be reproduced
+/// exactly while the [`Page`] remains available for structured access.
+///
+/// ## Examples
+///
+/// ```
+/// use vimwiki::{Cst, CstNodeKind, Language};
+///
+/// let input = "= Header =\n\n\nsome   *bold*  text\n";
+/// let cst: Cst = Language::from_vimwiki_str(input).parse().unwrap();
+///
+/// // Source can be reproduced byte-for-byte
+/// assert_eq!(cst.to_source(), input);
+///
+/// // A single node can be rewritten while leaving all other bytes alone
+/// let bold = cst
+///     .root()
+///     .descendants()
+///     .find(|node| node.kind() == CstNodeKind::DecoratedText)
+///     .unwrap();
+/// assert_eq!(
+///     cst.replace_node(bold, "_italic_"),
+///     "= Header =\n\n\nsome   _italic_  text\n",
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cst<'a> {
+    text: &'a str,
+    page: Page<'a>,
+    root: CstNode<'a>,
+}
+
+impl<'a> Cst<'a> {
+    /// Constructs a new tree by parsing the text, deriving the page from
+    /// the same parse so that the regions of the page always lie within it
+    pub(crate) fn new(text: &'a str) -> Result<Self, parsers::Error<'a>> {
+        let (_, page) = vimwiki::page(Span::from(text))?;
+        let root = builder::build(text, &page);
+        Ok(Self { text, page, root })
+    }
+
+    /// Returns the original text used to construct the tree
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns the page (AST) associated with the tree
+    pub fn page(&self) -> &Page<'a> {
+        &self.page
+    }
+
+    /// Consumes the tree and returns the page (AST) within
+    pub fn into_page(self) -> Page<'a> {
+        self.page
+    }
+
+    /// Returns the root node of the tree, which represents the entire page
+    pub fn root(&self) -> &CstNode<'a> {
+        &self.root
+    }
+
+    /// Returns all tokens within the tree in the order they appear in the
+    /// original text, ending with a [`CstTokenKind::Eof`] token
+    pub fn tokens(&self) -> impl Iterator<Item = &CstToken<'a>> {
+        self.root.tokens()
+    }
+
+    /// Reconstructs the original text from the tokens and trivia of the tree
+    pub fn to_source(&self) -> String {
+        self.root.to_string()
+    }
+
+    /// Produces a copy of the original text where the bytes covered by
+    /// `region` have been swapped out with `replacement`
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the region falls outside of the original text or does not
+    /// start and end on character boundaries
+    pub fn replace(&self, region: Region, replacement: &str) -> String {
+        let start = region.offset();
+        let end = start + region.len();
+
+        let mut s = String::with_capacity(
+            self.text.len() - region.len() + replacement.len(),
+        );
+        s.push_str(&self.text[..start]);
+        s.push_str(replacement);
+        s.push_str(&self.text[end..]);
+        s
+    }
+
+    /// Produces a copy of the original text where the node's region (not
+    /// including the trivia surrounding it) has been swapped out with
+    /// `replacement`
+    pub fn replace_node(
+        &self,
+        node: &CstNode<'a>,
+        replacement: &str,
+    ) -> String {
+        self.replace(node.region(), replacement)
+    }
+}
+
+impl fmt::Display for Cst<'_> {
+    /// Writes out the original text that was used to build the tree
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)
+    }
+}
+
+/// Represents the kind of element that a [`CstNode`] wraps
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CstNodeKind {
+    Page,
+
+    // Block elements
+    Blockquote,
+    CodeBlock,
+    DefinitionList,
+    Divider,
+    Header,
+    List,
+    MathBlock,
+    Paragraph,
+    Placeholder,
+    Table,
+
+    // Inline block elements
+    ListItem,
+    Term,
+    Definition,
+
+    // Inline elements
+    Text,
+    DecoratedText,
+    Keyword,
+    Link,
+    Tags,
+    CodeInline,
+    MathInline,
+    Comment,
+}
+
+impl<'a> From<&Element<'a>> for CstNodeKind {
+    fn from(element: &Element<'a>) -> Self {
+        match element {
+            Element::Block(x) => match x {
+                BlockElement::Blockquote(_) => Self::Blockquote,
+                BlockElement::CodeBlock(_) => Self::CodeBlock,
+                BlockElement::DefinitionList(_) => Self::DefinitionList,
+                BlockElement::Divider(_) => Self::Divider,
+                BlockElement::Header(_) => Self::Header,
+                BlockElement::List(_) => Self::List,
+                BlockElement::MathBlock(_) => Self::MathBlock,
+                BlockElement::Paragraph(_) => Self::Paragraph,
+                BlockElement::Placeholder(_) => Self::Placeholder,
+                BlockElement::Table(_) => Self::Table,
+            },
+            Element::InlineBlock(x) => match x {
+                InlineBlockElement::ListItem(_) => Self::ListItem,
+                InlineBlockElement::Term(_) => Self::Term,
+                InlineBlockElement::Definition(_) => Self::Definition,
+            },
+            Element::Inline(x) => match x {
+                InlineElement::Text(_) => Self::Text,
+                InlineElement::DecoratedText(_) => Self::DecoratedText,
+                InlineElement::Keyword(_) => Self::Keyword,
+                InlineElement::Link(_) => Self::Link,
+                InlineElement::Tags(_) => Self::Tags,
+                InlineElement::Code(_) => Self::CodeInline,
+                InlineElement::Math(_) => Self::MathInline,
+                InlineElement::Comment(Comment::Line(_))
+                | InlineElement::Comment(Comment::MultiLine(_)) => {
+                    Self::Comment
+                }
+            },
+        }
+    }
+}
+
+/// Represents a node within a [`Cst`], which maps to an element of the
+/// [`Page`] and contains other nodes and tokens
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CstNode<'a> {
+    kind: CstNodeKind,
+    region: Region,
+    children: Vec<CstElement<'a>>,
+}
+
+impl<'a> CstNode<'a> {
+    /// Returns the kind of element represented by the node
+    pub fn kind(&self) -> CstNodeKind {
+        self.kind
+    }
+
+    /// Returns the region of the element represented by the node, which
+    /// excludes any trivia that precedes or follows it
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Returns the region of the node including the leading trivia of its
+    /// first token and the trailing trivia of its last token
+    pub fn full_region(&self) -> Region {
+        let mut tokens = self.tokens();
+        match tokens.next() {
+            Some(first) => {
+                let start = first.full_region().offset();
+                let last = tokens.last().unwrap_or(first).full_region();
+                Region::new_at_depth(
+                    start,
+                    last.offset() + last.len() - start,
+                    self.region.depth(),
+                )
+            }
+            None => self.region,
+        }
+    }
+
+    /// Returns the nodes and tokens directly within this node
+    pub fn children(&self) -> &[CstElement<'a>] {
+        &self.children
+    }
+
+    /// Returns an iterator over this node and all nodes beneath it in
+    /// depth-first order
+    pub fn descendants(&self) -> impl Iterator<Item = &CstNode<'a>> {
+        let mut nodes = Vec::new();
+        fn inner<'a, 'b>(
+            node: &'b CstNode<'a>,
+            nodes: &mut Vec<&'b CstNode<'a>>,
+        ) {
+            nodes.push(node);
+            for child in node.children.iter() {
+                if let CstElement::Node(x) = child {
+                    inner(x, nodes);
+                }
+            }
+        }
+        inner(self, &mut nodes);
+        nodes.into_iter()
+    }
+
+    /// Returns an iterator over all tokens beneath this node in the order
+    /// they appear in the original text
+    pub fn tokens(&self) -> impl Iterator<Item = &CstToken<'a>> {
+        let mut tokens = Vec::new();
+        fn inner<'a, 'b>(
+            node: &'b CstNode<'a>,
+            tokens: &mut Vec<&'b CstToken<'a>>,
+        ) {
+            for child in node.children.iter() {
+                match child {
+                    CstElement::Node(x) => inner(x, tokens),
+                    CstElement::Token(x) => tokens.push(x),
+                }
+            }
+        }
+        inner(self, &mut tokens);
+        tokens.into_iter()
+    }
+}
+
+impl fmt::Display for CstNode<'_> {
+    /// Writes out all tokens of the node, including their trivia
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.tokens() {
+            write!(f, "{}", token)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents either a node or a token within a [`Cst`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CstElement<'a> {
+    Node(CstNode<'a>),
+    Token(CstToken<'a>),
+}
+
+impl<'a> CstElement<'a> {
+    pub fn as_node(&self) -> Option<&CstNode<'a>> {
+        match self {
+            Self::Node(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_token(&self) -> Option<&CstToken<'a>> {
+        match self {
+            Self::Token(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+/// Represents the kind of text captured by a [`CstToken`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CstTokenKind {
+    /// Text of an element that has no nested elements, such as plain text,
+    /// links, or the body of a code block
+    Content,
+
+    /// Markup belonging to an element that surrounds its nested elements,
+    /// such as the `=` of a header or the `*` of bold text
+    Syntax,
+
+    /// Empty token at the end of the input, used to hold any trailing trivia
+    Eof,
+}
+
+/// Represents a non-whitespace piece of text within a [`Cst`] alongside
+/// the whitespace that precedes and follows it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CstToken<'a> {
+    kind: CstTokenKind,
+    text: &'a str,
+    region: Region,
+    leading_trivia: Vec<Trivia<'a>>,
+    trailing_trivia: Vec<Trivia<'a>>,
+}
+
+impl<'a> CstToken<'a> {
+    /// Returns the kind of the token
+    pub fn kind(&self) -> CstTokenKind {
+        self.kind
+    }
+
+    /// Returns the text of the token, excluding trivia
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns the region of the token, excluding trivia
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Returns the region of the token including its leading and trailing
+    /// trivia
+    pub fn full_region(&self) -> Region {
+        let start = self
+            .leading_trivia
+            .first()
+            .map(|x| x.region.offset())
+            .unwrap_or_else(|| self.region.offset());
+        let end = self
+            .trailing_trivia
+            .last()
+            .map(|x| x.region.offset() + x.region.len())
+            .unwrap_or_else(|| self.region.offset() + self.region.len());
+        Region::new_at_depth(start, end - start, self.region.depth())
+    }
+
+    /// Returns the whitespace and newlines directly before the token
+    pub fn leading_trivia(&self) -> &[Trivia<'a>] {
+        &self.leading_trivia
+    }
+
+    /// Returns the whitespace up to and including the first newline directly
+    /// after the token
+    pub fn trailing_trivia(&self) -> &[Trivia<'a>] {
+        &self.trailing_trivia
+    }
+}
+
+impl fmt::Display for CstToken<'_> {
+    /// Writes out the token's leading trivia, text, and trailing trivia
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in self.leading_trivia.iter() {
+            write!(f, "{}", trivia)?;
+        }
+
+        write!(f, "{}", self.text)?;
+
+        for trivia in self.trailing_trivia.iter() {
+            write!(f, "{}", trivia)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the kind of whitespace captured by [`Trivia`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TriviaKind {
+    /// One or more whitespace characters that are not line terminations
+    Whitespace,
+
+    /// A single `\n` or `\r\n` line termination
+    Newline,
+}
+
+/// Represents whitespace within a [`Cst`] that has no bearing on the
+/// elements of the page, but is needed to reproduce the original text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trivia<'a> {
+    kind: TriviaKind,
+    text: &'a str,
+    region: Region,
+}
+
+impl<'a> Trivia<'a> {
+    /// Returns the kind of the trivia
+    pub fn kind(&self) -> TriviaKind {
+        self.kind
+    }
+
+    /// Returns the text of the trivia
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns the region of the trivia
+    pub fn region(&self) -> Region {
+        self.region
+    }
+}
+
+impl fmt::Display for Trivia<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::Language;
+
+    fn parse(input: &str) -> Cst<'_> {
+        Language::from_vimwiki_str(input).parse().unwrap()
+    }
+
+    #[test]
+    fn to_source_should_reproduce_input_exactly() {
+        let input = indoc::indoc! {"
+            %title Some Title
+
+              = *Header* =  \r
+
+            some   *bold* text
+            more [[link|desc]]
+
+
+            - item 1
+              - [ ] sub
+            * other
+
+            |a|b|
+            |-|-|
+            |c|d|
+            {{{py
+            code
+            }}}
+            term:: def
+            > quote
+            ----
+
+
+        "};
+        assert_eq!(parse(input).to_source(), input);
+    }
+
+    #[test]
+    fn to_source_should_support_empty_and_whitespace_only_input() {
+        assert_eq!(parse("").to_source(), "");
+        assert_eq!(parse(" \n\n\t").to_source(), " \n\n\t");
+
+        let cst = parse(" \n\n\t");
+        let tokens: Vec<&CstToken> = cst.tokens().collect();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind(), CstTokenKind::Eof);
+        assert_eq!(tokens[0].leading_trivia().len(), 4);
+    }
+
+    #[test]
+    fn tokens_should_split_syntax_from_content() {
+        let cst = parse("= *Header* =\n");
+        let tokens: Vec<(CstTokenKind, &str)> =
+            cst.tokens().map(|x| (x.kind(), x.text())).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (CstTokenKind::Syntax, "="),
+                (CstTokenKind::Syntax, "*"),
+                (CstTokenKind::Content, "Header"),
+                (CstTokenKind::Syntax, "*"),
+                (CstTokenKind::Syntax, "="),
+                (CstTokenKind::Eof, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn trailing_trivia_should_stop_after_first_newline() {
+        let cst = parse("one  \n\n  \ntwo");
+        let tokens: Vec<&CstToken> = cst.tokens().collect();
+
+        assert_eq!(tokens[0].text(), "one");
+        assert_eq!(
+            tokens[0]
+                .trailing_trivia()
+                .iter()
+                .map(Trivia::text)
+                .collect::<Vec<_>>(),
+            vec!["  ", "\n"]
+        );
+
+        assert_eq!(tokens[1].text(), "two");
+        assert_eq!(
+            tokens[1]
+                .leading_trivia()
+                .iter()
+                .map(|x| (x.kind(), x.text()))
+                .collect::<Vec<_>>(),
+            vec![
+                (TriviaKind::Newline, "\n"),
+                (TriviaKind::Whitespace, "  "),
+                (TriviaKind::Newline, "\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nodes_should_mirror_page_elements() {
+        let cst = parse("- item *bold*\n\n|a|b|\n");
+        let kinds: Vec<CstNodeKind> =
+            cst.root().descendants().map(CstNode::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CstNodeKind::Page,
+                CstNodeKind::List,
+                CstNodeKind::ListItem,
+                CstNodeKind::Paragraph,
+                CstNodeKind::Text,
+                CstNodeKind::DecoratedText,
+                CstNodeKind::Text,
+                CstNodeKind::Table,
+                CstNodeKind::Text,
+                CstNodeKind::Text,
+            ]
+        );
+    }
+
+    #[test]
+    fn full_region_should_include_trivia() {
+        let cst = parse("\n\n= header =  \n");
+        let header = cst.root().descendants().nth(1).unwrap();
+        assert_eq!(header.kind(), CstNodeKind::Header);
+        assert_eq!(header.region(), Region::new(2, 13));
+        assert_eq!(header.full_region(), Region::new(0, 15));
+    }
+
+    #[test]
+    fn replace_node_should_leave_other_bytes_untouched() {
+        let input = "  * one\n\n\n  * two\n\n  ";
+        let cst = parse(input);
+        let item = cst
+            .root()
+            .descendants()
+            .filter(|x| x.kind() == CstNodeKind::Text)
+            .nth(1)
+            .unwrap();
+        assert_eq!(cst.replace_node(item, "2"), "  * one\n\n\n  * 2\n\n  ");
+    }
+}
@@ -15,6 +15,8 @@ use std::{borrow::Cow, collections::HashMap, fmt, iter::FromIterator};
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CodeBlock<'a> {
     /// Represents the language associated with the code block if it has one
     pub language: Option<Cow<'a, str>>,
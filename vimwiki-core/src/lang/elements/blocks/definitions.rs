@@ -1,7 +1,7 @@
 use crate::{
     lang::elements::{
         InlineBlockElement, InlineElement, InlineElementContainer,
-        IntoChildren, Located, Region, Text,
+        IntoChildren, Located, Region, ShiftRegions, Text,
     },
     ElementLike, StrictEq,
 };
@@ -36,6 +36,8 @@ use std::{
 #[display(fmt = "{}", _0)]
 #[into_iterator(owned, ref, ref_mut)]
 #[serde(transparent)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DefinitionListValue<'a>(
     /// Represents the inner type that the definition list value wraps
     InlineElementContainer<'a>,
@@ -119,6 +121,12 @@ impl<'a> From<&'a str> for DefinitionListValue<'a> {
     }
 }
 
+impl<'a> ShiftRegions for DefinitionListValue<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.0.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for DefinitionListValue<'a> {
     /// Performs strict_eq on inner container
     #[inline]
@@ -150,8 +158,12 @@ pub type Definition<'a> = DefinitionListValue<'a>;
     Serialize,
     Deserialize,
 )]
-#[into_iterator(owned, ref, ref_mut)]
-pub struct DefinitionBundle<'a>(Vec<Located<Definition<'a>>>);
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DefinitionBundle<'a> {
+    #[into_iterator(owned, ref, ref_mut)]
+    definitions: Vec<Located<Definition<'a>>>,
+}
 
 impl ElementLike for DefinitionBundle<'_> {}
 
@@ -175,22 +187,22 @@ impl<'a> DefinitionBundle<'a> {
 
     /// Returns total elements contained within container
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.definitions.len()
     }
 
     /// Returns true if container has no elements
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.definitions.is_empty()
     }
 
     /// Returns reference to element at specified index, if it exists
     pub fn get(&self, idx: usize) -> Option<&Located<Definition<'a>>> {
-        self.0.get(idx)
+        self.definitions.get(idx)
     }
 
     /// Returns the definitions contained within the bundle
     pub fn into_definitions(self) -> Vec<Located<Definition<'a>>> {
-        self.0
+        self.definitions
     }
 }
 
@@ -218,7 +230,13 @@ impl<'a> IntoChildren for DefinitionBundle<'a> {
     type Child = Located<Definition<'a>>;
 
     fn into_children(self) -> Vec<Self::Child> {
-        self.0
+        self.definitions
+    }
+}
+
+impl<'a> ShiftRegions for DefinitionBundle<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.definitions.shift_regions(delta);
     }
 }
 
@@ -226,7 +244,7 @@ impl<'a> StrictEq for DefinitionBundle<'a> {
     /// Performs strict_eq on all definitions
     #[inline]
     fn strict_eq(&self, other: &Self) -> bool {
-        self.0.strict_eq(&other.0)
+        self.definitions.strict_eq(&other.definitions)
     }
 }
 
@@ -241,6 +259,8 @@ impl<'a> fmt::Display for DefinitionBundle<'a> {
 
 /// Represents a term and its associated definitions
 #[derive(Constructor, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TermAndDefinitions<'a> {
     pub term: Located<Term<'a>>,
     pub definitions: Located<DefinitionBundle<'a>>,
@@ -265,6 +285,13 @@ impl TermAndDefinitions<'_> {
     }
 }
 
+impl<'a> ShiftRegions for TermAndDefinitions<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.term.shift_regions(delta);
+        self.definitions.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for TermAndDefinitions<'a> {
     #[inline]
     fn strict_eq(&self, other: &Self) -> bool {
@@ -301,6 +328,8 @@ impl<'a> IntoChildren for TermAndDefinitions<'a> {
     Deserialize,
     IntoIterator,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DefinitionList<'a> {
     #[into_iterator(owned, ref, ref_mut)]
     pub items: Vec<Located<TermAndDefinitions<'a>>>,
@@ -394,6 +423,12 @@ impl<'a> FromIterator<(Located<Term<'a>>, Located<DefinitionBundle<'a>>)>
     }
 }
 
+impl<'a> ShiftRegions for DefinitionList<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.items.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for DefinitionList<'a> {
     /// Performs strict_eq on inner mapping
     fn strict_eq(&self, other: &Self) -> bool {
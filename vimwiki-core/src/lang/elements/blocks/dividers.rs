@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 #[derive(
     Constructor, Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Divider;
 
 impl ElementLike for Divider {}
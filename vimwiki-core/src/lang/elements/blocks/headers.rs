@@ -1,6 +1,7 @@
 use crate::{
     lang::elements::{
         InlineElement, InlineElementContainer, IntoChildren, Located,
+        ShiftRegions,
     },
     ElementLike, StrictEq,
 };
@@ -21,6 +22,8 @@ use std::fmt;
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Header<'a> {
     /// Represents the content contained within the header
     #[index]
@@ -79,6 +82,12 @@ impl<'a> IntoChildren for Header<'a> {
     }
 }
 
+impl<'a> ShiftRegions for Header<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.content.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for Header<'a> {
     /// Performs strict_eq on level, centered status, and content
     fn strict_eq(&self, other: &Self) -> bool {
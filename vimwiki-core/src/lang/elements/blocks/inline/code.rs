@@ -17,6 +17,8 @@ use std::borrow::Cow;
     Deserialize,
 )]
 #[as_ref(forward)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CodeInline<'a>(
     /// Represents the text contained within the inline code snippet
     Cow<'a, str>,
@@ -19,6 +19,8 @@ use std::{borrow::Cow, iter::FromIterator};
     Deserialize,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Comment<'a> {
     Line(LineComment<'a>),
     MultiLine(MultiLineComment<'a>),
@@ -65,6 +67,8 @@ impl<'a> StrictEq for Comment<'a> {
 )]
 #[as_ref(forward)]
 #[display(fmt = "{}", "_0.trim()")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LineComment<'a>(
     /// Represents the text represented as a comment
     Cow<'a, str>,
@@ -136,6 +140,8 @@ impl<'a> StrictEq for LineComment<'a> {
 #[as_ref(forward)]
 #[display(fmt = "{}", "_0.join(\"\n\")")]
 #[into_iterator(owned, ref, ref_mut)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MultiLineComment<'a>(
     /// Represents the lines of text represented as a comment
     Vec<Cow<'a, str>>,
@@ -23,6 +23,8 @@ use uriparse::{Fragment, FragmentError};
     Deserialize,
 )]
 #[into_iterator(owned, ref, ref_mut)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Anchor<'a>(
     /// Represents the individual parts of the anchor
     Vec<Cow<'a, str>>,
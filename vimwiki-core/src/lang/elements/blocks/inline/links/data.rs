@@ -16,7 +16,17 @@ use uriparse::{Fragment, Scheme, URIReference, URIReferenceError};
 /// Represents data for a link to some content, described through a combination
 /// of a URI reference and some arbitrary description
 #[derive(Constructor, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LinkData<'a> {
+    #[cfg_attr(
+        feature = "typescript",
+        ts(as = "super::uri_defs::UriReferenceDef")
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "super::uri_defs::UriReferenceDef")
+    )]
     pub uri_ref: URIReference<'a>,
     pub description: Option<Description<'a>>,
     pub properties: Option<HashMap<Cow<'a, str>, Cow<'a, str>>>,
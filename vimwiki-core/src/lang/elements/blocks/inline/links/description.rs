@@ -20,6 +20,8 @@ use uriparse::{URIReference, URIReferenceError};
     Deserialize,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Description<'a> {
     Text(Cow<'a, str>),
     TransclusionLink(Box<LinkData<'a>>),
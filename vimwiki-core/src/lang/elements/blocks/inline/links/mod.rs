@@ -19,6 +19,9 @@ pub use description::Description;
 mod data;
 pub use data::LinkData;
 
+#[cfg(any(feature = "schema", feature = "typescript"))]
+mod uri_defs;
+
 /// Represents some kind of link in a document
 #[derive(
     Clone,
@@ -32,6 +35,8 @@ pub use data::LinkData;
     Deserialize,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Link<'a> {
     /// Represents a traditional link within a wiki
     #[display(fmt = "{}", data)]
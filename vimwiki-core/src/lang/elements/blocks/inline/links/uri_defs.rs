@@ -0,0 +1,205 @@
+//! Mirrors of the serialized form of `uriparse` types, used to describe
+//! links in generated typescript definitions and json schemas
+
+/// Mirrors the serialized form of `uriparse::URIReference`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIReference")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIReference")
+)]
+#[allow(dead_code)]
+pub struct UriReferenceDef {
+    authority: Option<AuthorityDef>,
+    fragment: Option<FragmentDef>,
+    path: PathDef,
+    query: Option<QueryDef>,
+    scheme: Option<SchemeDef>,
+}
+
+/// Mirrors the serialized form of `uriparse::Authority`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIAuthority")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIAuthority")
+)]
+#[allow(dead_code)]
+struct AuthorityDef {
+    host: HostDef,
+    password: Option<PasswordDef>,
+    port: Option<u16>,
+    username: Option<UsernameDef>,
+}
+
+/// Mirrors the serialized form of `uriparse::Host`
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS), ts(rename = "URIHost"))]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIHost")
+)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
+enum HostDef {
+    IPv4Address(String),
+    IPv6Address(String),
+    RegisteredName(RegisteredNameDef),
+}
+
+/// Mirrors the serialized form of `uriparse::RegisteredName`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIRegisteredName")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIRegisteredName")
+)]
+#[allow(dead_code)]
+struct RegisteredNameDef {
+    normalized: bool,
+    registered_name: String,
+}
+
+/// Mirrors the serialized form of `uriparse::Password`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIPassword")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIPassword")
+)]
+#[allow(dead_code)]
+struct PasswordDef {
+    normalized: bool,
+    password: String,
+}
+
+/// Mirrors the serialized form of `uriparse::Username`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIUsername")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIUsername")
+)]
+#[allow(dead_code)]
+struct UsernameDef {
+    normalized: bool,
+    username: String,
+}
+
+/// Mirrors the serialized form of `uriparse::Fragment`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIFragment")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIFragment")
+)]
+#[allow(dead_code)]
+struct FragmentDef {
+    fragment: String,
+    normalized: bool,
+}
+
+/// Mirrors the serialized form of `uriparse::Path`
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS), ts(rename = "URIPath"))]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIPath")
+)]
+#[allow(dead_code)]
+struct PathDef {
+    absolute: bool,
+    double_dot_segment_count: u16,
+    leading_double_dot_segment_count: u16,
+    segments: Vec<SegmentDef>,
+    single_dot_segment_count: u16,
+    unnormalized_count: u16,
+}
+
+/// Mirrors the serialized form of `uriparse::Segment`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URISegment")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URISegment")
+)]
+#[allow(dead_code)]
+struct SegmentDef {
+    normalized: bool,
+    segment: String,
+}
+
+/// Mirrors the serialized form of `uriparse::Query`
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS), ts(rename = "URIQuery"))]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIQuery")
+)]
+#[allow(dead_code)]
+struct QueryDef {
+    normalized: bool,
+    query: String,
+}
+
+/// Mirrors the serialized form of `uriparse::Scheme`, where registered
+/// schemes such as `HTTPS` serialize as their name
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIScheme", untagged)
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIScheme", untagged)
+)]
+#[allow(dead_code, non_snake_case)]
+enum SchemeDef {
+    Registered(String),
+    Unregistered { Unregistered: UnregisteredSchemeDef },
+}
+
+/// Mirrors the serialized form of `uriparse::UnregisteredScheme`
+#[cfg_attr(
+    feature = "typescript",
+    derive(ts_rs::TS),
+    ts(rename = "URIUnregisteredScheme")
+)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "URIUnregisteredScheme")
+)]
+#[allow(dead_code)]
+struct UnregisteredSchemeDef {
+    normalized: bool,
+    scheme: String,
+}
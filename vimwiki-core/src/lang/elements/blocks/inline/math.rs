@@ -16,6 +16,8 @@ use std::borrow::Cow;
     Deserialize,
 )]
 #[as_ref(forward)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MathInline<'a>(
     /// Represents the text contained within the inline math snippet
     Cow<'a, str>,
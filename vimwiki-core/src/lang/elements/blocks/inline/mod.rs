@@ -1,5 +1,5 @@
 use crate::{
-    lang::elements::{IntoChildren, Located},
+    lang::elements::{IntoChildren, Located, ShiftRegions},
     ElementLike, StrictEq,
 };
 use derive_more::{
@@ -26,6 +26,8 @@ pub use typefaces::*;
     Clone, Debug, Display, From, Eq, PartialEq, Hash, Serialize, Deserialize,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum InlineElement<'a> {
     Text(Text<'a>),
     DecoratedText(DecoratedText<'a>),
@@ -82,6 +84,14 @@ impl<'a> IntoChildren for InlineElement<'a> {
     }
 }
 
+impl<'a> ShiftRegions for InlineElement<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        if let Self::DecoratedText(x) = self {
+            x.shift_regions(delta);
+        }
+    }
+}
+
 impl<'a> StrictEq for InlineElement<'a> {
     /// Performs strict_eq check on matching inner variants
     fn strict_eq(&self, other: &Self) -> bool {
@@ -114,6 +124,8 @@ impl<'a> StrictEq for InlineElement<'a> {
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct InlineElementContainer<'a> {
     #[into_iterator(owned, ref, ref_mut)]
     elements: Vec<Located<InlineElement<'a>>>,
@@ -207,6 +219,12 @@ impl<'a> FromIterator<InlineElementContainer<'a>>
     }
 }
 
+impl<'a> ShiftRegions for InlineElementContainer<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.elements.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for InlineElementContainer<'a> {
     /// Performs strict_eq check on inner elements
     fn strict_eq(&self, other: &Self) -> bool {
@@ -36,6 +36,8 @@ use std::{borrow::Cow, fmt, iter::FromIterator};
 )]
 #[as_ref(forward)]
 #[into_iterator(owned, ref, ref_mut)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Tags<'a>(
     /// Represents the tags contained within the tag set
     Vec<Tag<'a>>,
@@ -145,6 +147,8 @@ impl<'a> StrictEq for Tags<'a> {
     Deserialize,
 )]
 #[as_ref(forward)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Tag<'a>(Cow<'a, str>);
 
 impl ElementLike for Tag<'_> {}
@@ -1,7 +1,7 @@
 use crate::{
     lang::elements::{
         AsChildrenMutSlice, AsChildrenSlice, InlineElement, IntoChildren, Link,
-        Located,
+        Located, ShiftRegions,
     },
     ElementLike, StrictEq,
 };
@@ -23,6 +23,8 @@ use std::{borrow::Cow, fmt};
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Text<'a>(Cow<'a, str>);
 
 impl ElementLike for Text<'_> {}
@@ -98,6 +100,8 @@ impl<'a> StrictEq for Text<'a> {
     Deserialize,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum DecoratedTextContent<'a> {
     Text(Text<'a>),
     DecoratedText(DecoratedText<'a>),
@@ -186,6 +190,14 @@ impl<'a> IntoChildren for DecoratedTextContent<'a> {
     }
 }
 
+impl<'a> ShiftRegions for DecoratedTextContent<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        if let Self::DecoratedText(x) = self {
+            x.shift_regions(delta);
+        }
+    }
+}
+
 impl<'a> StrictEq for DecoratedTextContent<'a> {
     /// Performs strict_eq check on matching inner variants
     fn strict_eq(&self, other: &Self) -> bool {
@@ -204,6 +216,8 @@ impl<'a> StrictEq for DecoratedTextContent<'a> {
     Clone, Debug, Eq, PartialEq, Hash, IsVariant, Serialize, Deserialize,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum DecoratedText<'a> {
     Bold(Vec<Located<DecoratedTextContent<'a>>>),
     Italic(Vec<Located<DecoratedTextContent<'a>>>),
@@ -400,6 +414,14 @@ impl<'a> fmt::Display for DecoratedText<'a> {
     }
 }
 
+impl<'a> ShiftRegions for DecoratedText<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        for content in self.as_children_mut_slice() {
+            content.shift_regions(delta);
+        }
+    }
+}
+
 impl<'a> StrictEq for DecoratedText<'a> {
     /// Performs strict_eq check on matching inner variants
     fn strict_eq(&self, other: &Self) -> bool {
@@ -419,6 +441,8 @@ impl<'a> StrictEq for DecoratedText<'a> {
     Copy, Clone, Debug, Display, Eq, PartialEq, Hash, Serialize, Deserialize,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Keyword {
     #[display(fmt = "TODO")]
     Todo,
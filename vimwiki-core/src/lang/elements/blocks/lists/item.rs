@@ -1,6 +1,7 @@
 use crate::{
     lang::elements::{
         BlockElement, Element, IntoChildren, ListItemContents, Located,
+        ShiftRegions,
     },
     ElementLike, StrictEq,
 };
@@ -23,6 +24,8 @@ use std::borrow::Cow;
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ListItem<'a> {
     /// Represents the type of list item
     pub ty: ListItemType<'a>,
@@ -75,6 +78,12 @@ impl<'a> IntoChildren for ListItem<'a> {
     }
 }
 
+impl<'a> ShiftRegions for ListItem<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.contents.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for ListItem<'a> {
     /// Performs a strict_eq check against eqivalent variants
     fn strict_eq(&self, other: &Self) -> bool {
@@ -242,6 +251,8 @@ impl<'a> ListItem<'a> {
 /// Represents a suffix such as . or ) used after beginning of list item
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ListItemSuffix {
     None,
     Period,
@@ -273,6 +284,8 @@ impl StrictEq for ListItemSuffix {
 
 #[derive(Clone, Debug, From, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ListItemType<'a> {
     Ordered(OrderedListItemType),
     Unordered(UnorderedListItemType<'a>),
@@ -331,6 +344,8 @@ impl<'a> StrictEq for ListItemType<'a> {
 /// Represents the type associated with an unordered item
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum UnorderedListItemType<'a> {
     /// -
     Hyphen,
@@ -403,6 +418,8 @@ impl<'a> Default for UnorderedListItemType<'a> {
 /// Represents the type associated with an ordered item
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum OrderedListItemType {
     /// 1
     Number,
@@ -491,6 +508,8 @@ impl StrictEq for OrderedListItemType {
 /// Represents the todo status for a list item
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ListItemTodoStatus {
     /// Flags list item as a TODO item that has not been completed
     Incomplete,
@@ -522,6 +541,8 @@ impl StrictEq for ListItemTodoStatus {
 #[derive(
     Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ListItemAttributes {
     /// The TODO status for a list item, if it has been associated with TODO
     pub todo_status: Option<ListItemTodoStatus>,
@@ -1,7 +1,7 @@
 use crate::{
     lang::elements::{
         AsChildrenMutSlice, AsChildrenSlice, BlockElement, Element,
-        InlineBlockElement, IntoChildren, Located,
+        InlineBlockElement, IntoChildren, Located, ShiftRegions,
     },
     ElementLike, StrictEq,
 };
@@ -29,6 +29,8 @@ pub use item::*;
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct List<'a> {
     /// Represents items contained within the list
     #[index]
@@ -159,6 +161,12 @@ impl<'a> FromIterator<Located<ListItem<'a>>> for List<'a> {
     }
 }
 
+impl<'a> ShiftRegions for List<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.items.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for List<'a> {
     /// Performs a strict_eq check against list items
     fn strict_eq(&self, other: &Self) -> bool {
@@ -186,6 +194,8 @@ impl<'a> StrictEq for List<'a> {
 )]
 #[as_ref(forward)]
 #[into_iterator(owned, ref, ref_mut)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ListItemContents<'a>(Vec<Located<BlockElement<'a>>>);
 
 impl ElementLike for ListItemContents<'_> {}
@@ -280,6 +290,12 @@ impl<'a> FromIterator<Located<BlockElement<'a>>> for ListItemContents<'a> {
     }
 }
 
+impl<'a> ShiftRegions for ListItemContents<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.0.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for ListItemContents<'a> {
     /// Performs a strict_eq check against inner contents
     fn strict_eq(&self, other: &Self) -> bool {
@@ -16,6 +16,8 @@ use std::{borrow::Cow, fmt, iter::FromIterator};
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MathBlock<'a> {
     /// Represents the lines of text contained within the math block
     #[index]
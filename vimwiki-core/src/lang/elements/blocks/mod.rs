@@ -1,5 +1,5 @@
 use crate::{
-    lang::elements::{Element, IntoChildren, Located},
+    lang::elements::{Element, IntoChildren, Located, ShiftRegions},
     ElementLike, StrictEq,
 };
 use derive_more::{From, IsVariant};
@@ -33,6 +33,8 @@ pub use tables::*;
     Clone, Debug, From, Eq, PartialEq, Serialize, Deserialize, IsVariant,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum BlockElement<'a> {
     Blockquote(Blockquote<'a>),
     CodeBlock(CodeBlock<'a>),
@@ -331,6 +333,19 @@ impl<'a> IntoChildren for BlockElement<'a> {
     }
 }
 
+impl<'a> ShiftRegions for BlockElement<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        match self {
+            Self::DefinitionList(x) => x.shift_regions(delta),
+            Self::Header(x) => x.shift_regions(delta),
+            Self::List(x) => x.shift_regions(delta),
+            Self::Paragraph(x) => x.shift_regions(delta),
+            Self::Table(x) => x.shift_regions(delta),
+            _ => {}
+        }
+    }
+}
+
 impl<'a> StrictEq for BlockElement<'a> {
     /// Performs strict_eq check on matching inner variants
     fn strict_eq(&self, other: &Self) -> bool {
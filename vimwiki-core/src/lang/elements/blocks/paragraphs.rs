@@ -1,6 +1,7 @@
 use crate::{
     lang::elements::{
         InlineElement, InlineElementContainer, IntoChildren, Located,
+        ShiftRegions,
     },
     ElementLike, StrictEq,
 };
@@ -20,6 +21,8 @@ use std::{fmt, iter::FromIterator};
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Paragraph<'a> {
     /// Represents the lines of content contained within the paragraph
     #[index]
@@ -92,6 +95,12 @@ impl<'a> FromIterator<InlineElementContainer<'a>> for Paragraph<'a> {
     }
 }
 
+impl<'a> ShiftRegions for Paragraph<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.lines.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for Paragraph<'a> {
     /// Performs strict_eq on content
     fn strict_eq(&self, other: &Self) -> bool {
@@ -1,6 +1,7 @@
 use crate::{
     lang::elements::{
         InlineElement, InlineElementContainer, IntoChildren, Located,
+        ShiftRegions,
     },
     ElementLike, StrictEq,
 };
@@ -23,6 +24,8 @@ use std::{num::ParseIntError, str::FromStr};
     Deserialize,
 )]
 #[display(fmt = "{},{}", row, col)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CellPos {
     /// Represents the row number of a cell starting from 0
     pub row: usize,
@@ -70,11 +73,21 @@ impl FromStr for CellPos {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, IntoIterator, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Table<'a> {
     /// Represents the table's data (cells) as a mapping between a cell's
     /// position and its actual content (private)
     #[into_iterator(owned, ref, ref_mut)]
     #[serde(with = "serde_with::rust::map_as_tuple_list")]
+    #[cfg_attr(
+        feature = "typescript",
+        ts(as = "Vec<(CellPos, Located<Cell<'a>>)>")
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "Vec<(CellPos, Located<Cell<'a>>)>")
+    )]
     cells: HashMap<CellPos, Located<Cell<'a>>>,
 
     /// Represents the total rows contained in the table (private)
@@ -342,6 +355,14 @@ impl<'a> IntoChildren for Table<'a> {
     }
 }
 
+impl<'a> ShiftRegions for Table<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        for cell in self.cells.values_mut() {
+            cell.shift_regions(delta);
+        }
+    }
+}
+
 impl<'a> StrictEq for Table<'a> {
     /// Performs strict_eq on cells and centered status
     fn strict_eq(&self, other: &Self) -> bool {
@@ -359,6 +380,8 @@ impl<'a> StrictEq for Table<'a> {
     Clone, Debug, From, Eq, PartialEq, Hash, Serialize, Deserialize, IsVariant,
 )]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Cell<'a> {
     Content(InlineElementContainer<'a>),
     Span(CellSpan),
@@ -426,6 +449,14 @@ impl<'a> IntoChildren for Cell<'a> {
     }
 }
 
+impl<'a> ShiftRegions for Cell<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        if let Self::Content(x) = self {
+            x.shift_regions(delta);
+        }
+    }
+}
+
 impl<'a> StrictEq for Cell<'a> {
     /// Performs strict_eq on cell content
     fn strict_eq(&self, other: &Self) -> bool {
@@ -440,6 +471,8 @@ impl<'a> StrictEq for Cell<'a> {
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CellSpan {
     FromLeft,
     FromAbove,
@@ -454,6 +487,8 @@ impl StrictEq for CellSpan {
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ColumnAlign {
     None,
     Left,
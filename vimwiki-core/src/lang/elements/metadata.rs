@@ -0,0 +1,225 @@
+use crate::{BlockElement, Page, Placeholder};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Represents the metadata of a page as declared by its placeholders, such as
+/// `%title My Page` or `%author Bob`, which can be used as front matter
+///
+/// Later placeholders take precedence over earlier ones when a single value
+/// is requested, while every value of a repeated placeholder is kept
+///
+/// ## Examples
+///
+/// ```
+/// use vimwiki::{Language, Page, PageMetadata};
+///
+/// let page: Page = Language::from_vimwiki_str(
+///     "%title My Page\n%tags a b\n%tags c\n%draft yes\n%weight 3\n",
+/// )
+/// .parse()
+/// .unwrap();
+///
+/// let metadata = page.metadata();
+/// assert_eq!(metadata.title.as_deref(), Some("My Page"));
+/// assert_eq!(metadata.get("tags"), Some("c"));
+/// assert_eq!(metadata.get_list("tags"), vec!["a", "b", "c"]);
+/// assert_eq!(metadata.get_bool("draft"), Some(true));
+/// assert_eq!(metadata.get_parsed::<u32>("weight"), Some(Ok(3)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PageMetadata {
+    /// Title of the page from `%title`
+    pub title: Option<String>,
+
+    /// Date of the page from `%date`
+    pub date: Option<NaiveDate>,
+
+    /// Name of the template to use for the page from `%template`
+    pub template: Option<String>,
+
+    /// Whether or not the page should be excluded from html output,
+    /// from `%nohtml`
+    pub nohtml: bool,
+
+    /// Values of all other placeholders by name, in the order they appear
+    pub values: BTreeMap<String, Vec<String>>,
+}
+
+impl PageMetadata {
+    /// Extracts the metadata from the placeholders within a page
+    pub fn from_page(page: &Page) -> Self {
+        let mut metadata = Self::default();
+        for element in page.elements() {
+            if let BlockElement::Placeholder(x) = element.as_inner() {
+                metadata.insert(x);
+            }
+        }
+        metadata
+    }
+
+    /// Updates the metadata with the details of the placeholder
+    pub fn insert(&mut self, placeholder: &Placeholder) {
+        match placeholder {
+            Placeholder::Title(x) => self.title = Some(x.to_string()),
+            Placeholder::Date(x) => self.date = Some(*x),
+            Placeholder::Template(x) => self.template = Some(x.to_string()),
+            Placeholder::NoHtml => self.nohtml = true,
+            Placeholder::Other { name, value } => {
+                self.push(name.as_ref(), value.as_ref())
+            }
+        }
+    }
+
+    /// Adds a value to the custom placeholder with the given name, keeping
+    /// any existing values
+    pub fn push(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values
+            .entry(name.into())
+            .or_default()
+            .push(value.into());
+    }
+
+    /// Returns true if a custom placeholder exists with the given name
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Returns the value of the last custom placeholder with the given name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_all(name).last().map(String::as_str)
+    }
+
+    /// Returns the values of every custom placeholder with the given name
+    pub fn get_all(&self, name: &str) -> &[String] {
+        self.values.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the values of every custom placeholder with the given name,
+    /// split by whitespace and commas such that `%tags a, b` and `%tags c`
+    /// produce `a`, `b` and `c`
+    pub fn get_list(&self, name: &str) -> Vec<&str> {
+        self.get_all(name)
+            .iter()
+            .flat_map(|x| x.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|x| !x.is_empty())
+            .collect()
+    }
+
+    /// Returns the value of the last custom placeholder with the given name
+    /// as a boolean, where `true`, `yes`, `on` and `1` are true and `false`,
+    /// `no`, `off` and `0` are false (ignoring case)
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)?.trim().to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some(true),
+            "false" | "no" | "off" | "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the last custom placeholder with the given name
+    /// as a date in the form YYYY-MM-DD
+    pub fn get_date(&self, name: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(self.get(name)?.trim(), "%Y-%m-%d").ok()
+    }
+
+    /// Returns the value of the last custom placeholder with the given name
+    /// parsed into the specified type
+    pub fn get_parsed<T: FromStr>(
+        &self,
+        name: &str,
+    ) -> Option<Result<T, T::Err>> {
+        self.get(name).map(|x| x.trim().parse())
+    }
+
+    /// Iterates over the name and last value of each custom placeholder
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().filter_map(|(name, values)| {
+            values.last().map(|value| (name.as_str(), value.as_str()))
+        })
+    }
+}
+
+impl Page<'_> {
+    /// Returns the metadata declared by the placeholders within the page
+    pub fn metadata(&self) -> PageMetadata {
+        PageMetadata::from_page(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Located;
+
+    fn page(placeholders: Vec<Placeholder<'static>>) -> Page<'static> {
+        Page::new(
+            placeholders
+                .into_iter()
+                .map(|x| Located::from(BlockElement::from(x)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn from_page_should_use_the_last_value_of_standard_placeholders() {
+        let metadata = page(vec![
+            Placeholder::title_from_str("one"),
+            Placeholder::template_from_str("a"),
+            Placeholder::NoHtml,
+            Placeholder::title_from_str("two"),
+            Placeholder::Date(NaiveDate::from_ymd(2021, 6, 17)),
+        ])
+        .metadata();
+
+        assert_eq!(metadata.title.as_deref(), Some("two"));
+        assert_eq!(metadata.template.as_deref(), Some("a"));
+        assert_eq!(metadata.date, Some(NaiveDate::from_ymd(2021, 6, 17)));
+        assert!(metadata.nohtml);
+        assert!(metadata.values.is_empty());
+    }
+
+    #[test]
+    fn from_page_should_keep_every_value_of_custom_placeholders() {
+        let metadata = page(vec![
+            Placeholder::other_from_str("author", "Bob"),
+            Placeholder::other_from_str("tags", "a b"),
+            Placeholder::other_from_str("tags", "c,d"),
+        ])
+        .metadata();
+
+        assert_eq!(metadata.get("author"), Some("Bob"));
+        assert_eq!(metadata.get("tags"), Some("c,d"));
+        assert_eq!(metadata.get_all("tags"), ["a b", "c,d"]);
+        assert_eq!(metadata.get_list("tags"), vec!["a", "b", "c", "d"]);
+        assert_eq!(
+            metadata.iter().collect::<Vec<_>>(),
+            vec![("author", "Bob"), ("tags", "c,d")]
+        );
+        assert!(!metadata.nohtml);
+    }
+
+    #[test]
+    fn typed_accessors_should_parse_values() {
+        let mut metadata = PageMetadata::default();
+        metadata.push("draft", "Yes");
+        metadata.push("public", "No");
+        metadata.push("weight", " 10 ");
+        metadata.push("published", "2021-06-17");
+        metadata.push("other", "maybe");
+
+        assert_eq!(metadata.get_bool("draft"), Some(true));
+        assert_eq!(metadata.get_bool("public"), Some(false));
+        assert_eq!(metadata.get_bool("other"), None);
+        assert_eq!(metadata.get_bool("missing"), None);
+        assert_eq!(metadata.get_parsed::<i32>("weight"), Some(Ok(10)));
+        assert!(matches!(metadata.get_parsed::<i32>("other"), Some(Err(_))));
+        assert_eq!(
+            metadata.get_date("published"),
+            Some(NaiveDate::from_ymd(2021, 6, 17))
+        );
+        assert_eq!(metadata.get_date("other"), None);
+    }
+}
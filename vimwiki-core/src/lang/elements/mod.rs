@@ -7,9 +7,12 @@ use std::iter::FromIterator;
 
 mod blocks;
 pub use blocks::*;
+mod metadata;
+pub use metadata::PageMetadata;
 mod utils;
 pub use utils::{
-    AsChildrenMutSlice, AsChildrenSlice, IntoChildren, Located, Region,
+    AsChildrenMutSlice, AsChildrenSlice, ColumnEncoding, IntoChildren,
+    LineColumn, LineIndex, Located, Region, ShiftRegions,
 };
 
 /// Blanket marker for elements
@@ -19,6 +22,11 @@ impl<'a, T> ElementLike for &'a T where T: ElementLike {}
 impl<'a, T> ElementLike for &'a mut T where T: ElementLike {}
 impl<T> ElementLike for Option<T> where T: ElementLike {}
 
+/// Allows generic elements to be declared in typescript, which substitutes
+/// a placeholder for their type parameters
+#[cfg(feature = "typescript")]
+impl ElementLike for ts_rs::Dummy {}
+
 /// Represents a full page containing different elements
 #[derive(
     Constructor,
@@ -33,6 +41,8 @@ impl<T> ElementLike for Option<T> where T: ElementLike {}
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Page<'a> {
     /// Comprised of the elements within a page
     #[index]
@@ -53,6 +63,73 @@ impl<'a> Page<'a> {
     }
 }
 
+impl Page<'_> {
+    /// Returns the path of elements containing the byte offset, starting
+    /// with the top-level element of the page and ending with the innermost
+    /// element; the path is empty if no element contains the offset
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use vimwiki::{BlockElement, Element, InlineElement, Language, Page};
+    ///
+    /// let page: Page =
+    ///     Language::from_vimwiki_str("= Header =\n- [[Link]]\n").parse().unwrap();
+    ///
+    /// let path = page.path_at(15);
+    /// assert!(matches!(
+    ///     path.first().unwrap().as_inner(),
+    ///     Element::Block(BlockElement::List(_)),
+    /// ));
+    /// assert!(matches!(
+    ///     path.last().unwrap().as_inner(),
+    ///     Element::Inline(InlineElement::Link(_)),
+    /// ));
+    /// ```
+    pub fn path_at(&self, offset: usize) -> Vec<Located<Element<'_>>> {
+        let mut path = Vec::new();
+        let mut children: Vec<Located<Element<'_>>> = self
+            .elements
+            .iter()
+            .filter(|x| x.region().contains(offset))
+            .map(|x| x.as_ref().map(|x| Element::from(x.to_borrowed())))
+            .collect();
+
+        // Descend into the deepest child containing the offset, favoring
+        // the smallest region when multiple children share the same depth
+        while let Some(next) = children
+            .into_iter()
+            .filter(|x| x.region().contains(offset))
+            .min_by_key(|x| {
+                (std::cmp::Reverse(x.region().depth()), x.region().len())
+            })
+        {
+            children = next.clone().into_inner().into_children();
+            path.push(next);
+        }
+
+        path
+    }
+
+    /// Returns the path of elements containing the line and column, where
+    /// the index was built from the text used to parse this page
+    pub fn path_at_line_column(
+        &self,
+        index: &LineIndex,
+        position: LineColumn,
+    ) -> Vec<Located<Element<'_>>> {
+        index
+            .offset(position)
+            .map(|offset| self.path_at(offset))
+            .unwrap_or_default()
+    }
+
+    /// Returns the innermost element containing the byte offset
+    pub fn element_at(&self, offset: usize) -> Option<Located<Element<'_>>> {
+        self.path_at(offset).pop()
+    }
+}
+
 impl Page<'_> {
     pub fn to_borrowed(&self) -> Page {
         let elements = self
@@ -93,6 +170,12 @@ impl<'a> FromIterator<Located<BlockElement<'a>>> for Page<'a> {
     }
 }
 
+impl<'a> ShiftRegions for Page<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        self.elements.shift_regions(delta);
+    }
+}
+
 impl<'a> StrictEq for Page<'a> {
     /// Performs strict_eq on page elements
     fn strict_eq(&self, other: &Self) -> bool {
@@ -109,6 +192,8 @@ impl<'a> StrictEq for Page<'a> {
 /// special inbetween types like `ListItem`
 #[derive(Clone, Debug, From, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Element<'a> {
     Block(BlockElement<'a>),
     Inline(InlineElement<'a>),
@@ -151,6 +236,16 @@ impl<'a> IntoChildren for Element<'a> {
     }
 }
 
+impl<'a> ShiftRegions for Element<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        match self {
+            Self::Block(x) => x.shift_regions(delta),
+            Self::Inline(x) => x.shift_regions(delta),
+            Self::InlineBlock(x) => x.shift_regions(delta),
+        }
+    }
+}
+
 impl<'a> StrictEq for Element<'a> {
     fn strict_eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -210,6 +305,8 @@ impl<'a> Element<'a> {
 /// is not an `InlineElement` such as `ListItem`
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum InlineBlockElement<'a> {
     ListItem(ListItem<'a>),
     Term(Term<'a>),
@@ -266,6 +363,16 @@ impl<'a> IntoChildren for InlineBlockElement<'a> {
     }
 }
 
+impl<'a> ShiftRegions for InlineBlockElement<'a> {
+    fn shift_regions(&mut self, delta: isize) {
+        match self {
+            Self::ListItem(x) => x.shift_regions(delta),
+            Self::Term(x) => x.shift_regions(delta),
+            Self::Definition(x) => x.shift_regions(delta),
+        }
+    }
+}
+
 impl<'a> StrictEq for InlineBlockElement<'a> {
     fn strict_eq(&self, other: &Self) -> bool {
         match (self, other) {
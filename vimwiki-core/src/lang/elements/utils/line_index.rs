@@ -0,0 +1,354 @@
+use super::Region;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Represents the unit used to measure the column of a position within a line
+#[derive(
+    Copy, Clone, Debug, Default, Hash, Eq, PartialEq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnEncoding {
+    /// Columns are measured in bytes of UTF-8
+    #[default]
+    Utf8,
+
+    /// Columns are measured in UTF-16 code units, which is what is used by
+    /// the language server protocol and javascript strings
+    Utf16,
+
+    /// Columns are measured in unicode scalar values (`char`)
+    Char,
+}
+
+/// Represents a zero-based line and column within some text
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+/// Represents a character that takes up more than one byte in UTF-8
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+struct MultiByteChar {
+    /// Byte offset of the character relative to the start of its line
+    offset: usize,
+
+    /// Length of the character in UTF-8 bytes
+    len: usize,
+}
+
+impl MultiByteChar {
+    /// Length of the character in the given encoding
+    fn encoded_len(&self, encoding: ColumnEncoding) -> usize {
+        match encoding {
+            ColumnEncoding::Utf8 => self.len,
+            ColumnEncoding::Utf16 if self.len == 4 => 2,
+            ColumnEncoding::Utf16 | ColumnEncoding::Char => 1,
+        }
+    }
+}
+
+/// Represents an index of the lines within some text, used to convert
+/// between byte offsets (such as those of a [`Region`]) and line/column
+/// positions where columns are measured using a [`ColumnEncoding`].
+///
+/// Lines are terminated by either `\n` or `\r\n`, and the terminator is not
+/// considered part of the line.
+///
+/// ## Examples
+///
+/// ```
+/// use vimwiki::{ColumnEncoding, LineColumn, LineIndex};
+///
+/// let index = LineIndex::new("first line\nsecond 𝄞 line");
+/// assert_eq!(index.line_col(15), Some(LineColumn::new(1, 4)));
+/// assert_eq!(index.offset(LineColumn::new(1, 4)), Some(15));
+///
+/// // The musical symbol takes up 4 bytes, 2 UTF-16 code units and 1 char
+/// let index = index.with_encoding(ColumnEncoding::Utf16);
+/// assert_eq!(index.line_col(22), Some(LineColumn::new(1, 9)));
+/// assert_eq!(index.offset(LineColumn::new(1, 9)), Some(22));
+/// ```
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq)]
+pub struct LineIndex {
+    /// Byte offset of the start of each line
+    starts: Vec<usize>,
+
+    /// Byte offset of the end of each line, excluding the line terminator
+    ends: Vec<usize>,
+
+    /// Characters within each line that are more than a single byte
+    multi_byte_chars: Vec<Vec<MultiByteChar>>,
+
+    /// Encoding used to measure columns
+    encoding: ColumnEncoding,
+}
+
+impl LineIndex {
+    /// Builds an index of the lines within `text`, measuring columns in
+    /// bytes of UTF-8
+    pub fn new(text: &str) -> Self {
+        let mut starts = vec![0];
+        let mut ends = Vec::new();
+        let mut multi_byte_chars = vec![Vec::new()];
+
+        let bytes = text.as_bytes();
+        for (i, c) in text.char_indices() {
+            let line_start = *starts.last().unwrap();
+            if c == '\n' {
+                let end = if i > line_start && bytes[i - 1] == b'\r' {
+                    i - 1
+                } else {
+                    i
+                };
+                ends.push(end);
+                starts.push(i + 1);
+                multi_byte_chars.push(Vec::new());
+            } else if c.len_utf8() > 1 {
+                multi_byte_chars.last_mut().unwrap().push(MultiByteChar {
+                    offset: i - line_start,
+                    len: c.len_utf8(),
+                });
+            }
+        }
+        ends.push(text.len());
+
+        Self {
+            starts,
+            ends,
+            multi_byte_chars,
+            encoding: ColumnEncoding::default(),
+        }
+    }
+
+    /// Changes the encoding used to measure columns
+    pub fn with_encoding(mut self, encoding: ColumnEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the encoding used to measure columns
+    pub fn encoding(&self) -> ColumnEncoding {
+        self.encoding
+    }
+
+    /// Returns the total number of lines, which is always at least one
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns the length in bytes of the indexed text
+    pub fn len(&self) -> usize {
+        self.ends.last().copied().unwrap_or_default()
+    }
+
+    /// Returns true if the indexed text was empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the region of the line, excluding its terminator
+    pub fn line_region(&self, line: usize) -> Option<Region> {
+        let start = *self.starts.get(line)?;
+        let end = *self.ends.get(line)?;
+        Some(Region::new(start, end - start))
+    }
+
+    /// Returns the line containing the byte offset, where an offset at the
+    /// end of the text belongs to the last line
+    pub fn line_of(&self, offset: usize) -> Option<usize> {
+        if offset > self.len() {
+            return None;
+        }
+
+        Some(match self.starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        })
+    }
+
+    /// Converts a byte offset into a line and column, returning None if the
+    /// offset is beyond the end of the text, falls within a line terminator,
+    /// or falls in the middle of a character
+    pub fn line_col(&self, offset: usize) -> Option<LineColumn> {
+        let line = self.line_of(offset)?;
+        if offset > self.ends[line] {
+            return None;
+        }
+
+        let mut column = offset - self.starts[line];
+        for c in self.multi_byte_chars[line].iter() {
+            if c.offset >= column {
+                break;
+            } else if column < c.offset + c.len {
+                return None;
+            }
+            column -= c.len - c.encoded_len(self.encoding);
+        }
+
+        Some(LineColumn::new(line, column))
+    }
+
+    /// Converts a line and column into a byte offset, returning None if the
+    /// line does not exist, the column is beyond the end of the line, or the
+    /// column falls in the middle of a character
+    pub fn offset(&self, position: LineColumn) -> Option<usize> {
+        let start = *self.starts.get(position.line)?;
+        let end = self.ends[position.line];
+
+        // Walk through each wider character, tracking how many more bytes
+        // than encoded units we have passed over
+        let mut extra = 0;
+        for c in self.multi_byte_chars[position.line].iter() {
+            let column = c.offset - extra;
+            let len = c.encoded_len(self.encoding);
+            if position.column <= column {
+                break;
+            } else if position.column < column + len {
+                return None;
+            }
+            extra += c.len - len;
+        }
+
+        let offset = start + position.column + extra;
+        if offset <= end {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    /// Converts a region into a range of line and column positions
+    pub fn line_range(&self, region: Region) -> Option<Range<LineColumn>> {
+        let start = self.line_col(region.offset())?;
+        let end = self.line_col(region.offset() + region.len())?;
+        Some(start..end)
+    }
+
+    /// Converts a range of line and column positions into a region
+    pub fn region(&self, range: Range<LineColumn>) -> Option<Region> {
+        let start = self.offset(range.start)?;
+        let end = self.offset(range.end)?;
+        Some(Region::from(start..end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NOTE: Line 1 contains é (2 bytes, 1 utf16, 1 char) and line 2
+    //       contains 𝄞 (4 bytes, 2 utf16, 1 char)
+    const TEXT: &str = "abc\r\naéb\n𝄞x\n";
+
+    #[test]
+    fn new_should_track_lines_without_terminators() {
+        let index = LineIndex::new(TEXT);
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.len(), TEXT.len());
+        assert_eq!(index.line_region(0), Some(Region::new(0, 3)));
+        assert_eq!(index.line_region(1), Some(Region::new(5, 4)));
+        assert_eq!(index.line_region(2), Some(Region::new(10, 5)));
+        assert_eq!(index.line_region(3), Some(Region::new(16, 0)));
+        assert_eq!(index.line_region(4), None);
+
+        let index = LineIndex::new("");
+        assert!(index.is_empty());
+        assert_eq!(index.line_count(), 1);
+        assert_eq!(index.line_col(0), Some(LineColumn::new(0, 0)));
+    }
+
+    #[test]
+    fn line_of_should_return_line_containing_offset() {
+        let index = LineIndex::new(TEXT);
+        assert_eq!(index.line_of(0), Some(0));
+        assert_eq!(index.line_of(4), Some(0));
+        assert_eq!(index.line_of(5), Some(1));
+        assert_eq!(index.line_of(16), Some(3));
+        assert_eq!(index.line_of(17), None);
+    }
+
+    #[test]
+    fn line_col_should_support_utf8_columns() {
+        let index = LineIndex::new(TEXT);
+        assert_eq!(index.line_col(3), Some(LineColumn::new(0, 3)));
+        assert_eq!(index.line_col(4), None);
+        assert_eq!(index.line_col(8), Some(LineColumn::new(1, 3)));
+        assert_eq!(index.line_col(7), None);
+        assert_eq!(index.line_col(14), Some(LineColumn::new(2, 4)));
+    }
+
+    #[test]
+    fn line_col_should_support_utf16_columns() {
+        let index = LineIndex::new(TEXT).with_encoding(ColumnEncoding::Utf16);
+        assert_eq!(index.line_col(8), Some(LineColumn::new(1, 2)));
+        assert_eq!(index.line_col(9), Some(LineColumn::new(1, 3)));
+        assert_eq!(index.line_col(14), Some(LineColumn::new(2, 2)));
+        assert_eq!(index.line_col(15), Some(LineColumn::new(2, 3)));
+        assert_eq!(index.line_col(12), None);
+    }
+
+    #[test]
+    fn line_col_should_support_char_columns() {
+        let index = LineIndex::new(TEXT).with_encoding(ColumnEncoding::Char);
+        assert_eq!(index.line_col(8), Some(LineColumn::new(1, 2)));
+        assert_eq!(index.line_col(14), Some(LineColumn::new(2, 1)));
+        assert_eq!(index.line_col(15), Some(LineColumn::new(2, 2)));
+    }
+
+    #[test]
+    fn offset_should_be_inverse_of_line_col() {
+        for encoding in [
+            ColumnEncoding::Utf8,
+            ColumnEncoding::Utf16,
+            ColumnEncoding::Char,
+        ] {
+            let index = LineIndex::new(TEXT).with_encoding(encoding);
+            for offset in 0..=TEXT.len() {
+                if let Some(position) = index.line_col(offset) {
+                    assert_eq!(
+                        index.offset(position),
+                        Some(offset),
+                        "{:?} @ {}",
+                        encoding,
+                        offset
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offset_should_reject_positions_outside_of_lines_or_within_chars() {
+        let index = LineIndex::new(TEXT).with_encoding(ColumnEncoding::Utf16);
+        assert_eq!(index.offset(LineColumn::new(0, 4)), None);
+        assert_eq!(index.offset(LineColumn::new(2, 1)), None);
+        assert_eq!(index.offset(LineColumn::new(4, 0)), None);
+    }
+
+    #[test]
+    fn line_range_should_convert_between_regions_and_ranges() {
+        let index = LineIndex::new(TEXT).with_encoding(ColumnEncoding::Utf16);
+        let range = LineColumn::new(1, 1)..LineColumn::new(2, 2);
+        assert_eq!(index.line_range(Region::new(6, 8)), Some(range.clone()));
+        assert_eq!(index.region(range), Some(Region::new(6, 8)));
+    }
+}
@@ -3,6 +3,9 @@ use derive_more::{Constructor, Deref, DerefMut, Display, Index, IndexMut};
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
+mod line_index;
+pub use line_index::{ColumnEncoding, LineColumn, LineIndex};
+
 mod region;
 pub use region::Region;
 
@@ -36,6 +39,23 @@ pub trait IntoChildren {
     fn into_children(self) -> Vec<Self::Child>;
 }
 
+/// Represents a trait that provides the ability to move the regions of an
+/// element and all of its descendants, used when text before the element
+/// has grown or shrunk
+pub trait ShiftRegions {
+    /// Moves all regions by `delta` bytes
+    fn shift_regions(&mut self, delta: isize);
+}
+
+/// Blanket implementation to shift the regions of all elements in a vec
+impl<T: ShiftRegions> ShiftRegions for Vec<T> {
+    fn shift_regions(&mut self, delta: isize) {
+        for x in self.iter_mut() {
+            x.shift_regions(delta);
+        }
+    }
+}
+
 /// Represents an encapsulation of a language element and its location
 /// within some string/file
 #[derive(
@@ -52,6 +72,7 @@ pub trait IntoChildren {
     Serialize,
     Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[display(fmt = "{}", inner)]
 pub struct Located<T>
 where
@@ -129,6 +150,15 @@ where
     pub fn region(&self) -> Region {
         self.region
     }
+
+    /// Returns the range of lines and columns covered by the inner value
+    /// using the index of the text it was parsed from
+    pub fn line_range(
+        &self,
+        index: &LineIndex,
+    ) -> Option<std::ops::Range<LineColumn>> {
+        self.region.line_range(index)
+    }
 }
 
 impl<T> Located<Option<T>>
@@ -191,6 +221,18 @@ where
     }
 }
 
+impl<T> ShiftRegions for Located<T>
+where
+    T: ShiftRegions + ElementLike,
+{
+    /// Moves the region of the located element and any regions within the
+    /// inner element
+    fn shift_regions(&mut self, delta: isize) {
+        self.region = self.region.shift(delta);
+        self.inner.shift_regions(delta);
+    }
+}
+
 impl<T> Hash for Located<T>
 where
     T: Hash + ElementLike,
@@ -200,6 +242,59 @@ where
     }
 }
 
+/// Declares a located element as its element flattened alongside its
+/// region, mirroring how it is serialized
+#[cfg(feature = "typescript")]
+impl<T> ts_rs::TS for Located<T>
+where
+    T: ts_rs::TS + ElementLike,
+{
+    type WithoutGenerics = Located<ts_rs::Dummy>;
+
+    fn ident() -> String {
+        "Located".to_string()
+    }
+
+    fn name() -> String {
+        format!("Located<{}>", T::name())
+    }
+
+    fn decl() -> String {
+        "type Located<T> = T & { region: Region };".to_string()
+    }
+
+    fn decl_concrete() -> String {
+        format!("type Located = {};", Self::inline())
+    }
+
+    fn inline() -> String {
+        format!("{} & {{ region: Region }}", T::inline())
+    }
+
+    fn inline_flattened() -> String {
+        Self::inline()
+    }
+
+    fn visit_dependencies(v: &mut impl ts_rs::TypeVisitor)
+    where
+        Self: 'static,
+    {
+        v.visit::<Region>();
+    }
+
+    fn visit_generics(v: &mut impl ts_rs::TypeVisitor)
+    where
+        Self: 'static,
+    {
+        v.visit::<T>();
+        T::visit_generics(v);
+    }
+
+    fn output_path() -> Option<&'static std::path::Path> {
+        Some(std::path::Path::new("Located.ts"))
+    }
+}
+
 impl<T> From<T> for Located<T>
 where
     T: ElementLike,
@@ -220,6 +315,20 @@ mod tests {
 
     impl ElementLike for Test {}
 
+    impl ShiftRegions for Test {
+        fn shift_regions(&mut self, delta: isize) {
+            self.0 = (self.0 as isize + delta) as usize;
+        }
+    }
+
+    #[test]
+    fn shift_regions_should_move_region_and_inner_value() {
+        let mut le = Located::new(Test(3), Region::new_at_depth(1, 4, 2));
+        le.shift_regions(5);
+        assert_eq!(*le.as_inner(), Test(8));
+        assert_eq!(le.region(), Region::new_at_depth(6, 4, 2));
+    }
+
     #[test]
     fn map_should_transform_inner_value_and_keep_region() {
         let le = Located::new(Test(3), Region::new(1, 4));
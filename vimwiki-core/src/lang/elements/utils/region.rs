@@ -1,3 +1,4 @@
+use super::{LineColumn, LineIndex};
 use crate::lang::parsers::Span;
 use serde::{Deserialize, Serialize};
 use std::ops::{Range, RangeInclusive, RangeTo, RangeToInclusive};
@@ -6,6 +7,8 @@ use std::ops::{Range, RangeInclusive, RangeTo, RangeToInclusive};
 #[derive(
     Copy, Clone, Debug, Default, Hash, Eq, PartialEq, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Region {
     /// Position within some byte array this region begins
     offset: usize,
@@ -40,6 +43,17 @@ impl Region {
         Self::new_at_depth(self.offset, self.len, depth)
     }
 
+    /// Constructs a copy of a region whose offset has been moved by `delta`
+    /// bytes, capping out at an offset of 0
+    pub fn shift(&self, delta: isize) -> Self {
+        let offset = if delta < 0 {
+            self.offset.saturating_sub(delta.unsigned_abs())
+        } else {
+            self.offset + delta as usize
+        };
+        Self::new_at_depth(offset, self.len, self.depth)
+    }
+
     /// Checks if a position is contained within this region
     #[inline]
     pub fn contains(&self, offset: usize) -> bool {
@@ -69,6 +83,13 @@ impl Region {
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
+
+    /// Returns the range of lines and columns covered by the region using
+    /// the index of the text the region refers to
+    #[inline]
+    pub fn line_range(&self, index: &LineIndex) -> Option<Range<LineColumn>> {
+        index.line_range(*self)
+    }
 }
 
 impl<'a> From<Span<'a>> for Region {
@@ -148,6 +169,14 @@ mod tests {
         assert!(!region.contains(7));
     }
 
+    #[test]
+    fn shift_should_move_offset_and_keep_length_and_depth() {
+        let region = Region::new_at_depth(3, 2, 1);
+        assert_eq!(region.shift(4), Region::new_at_depth(7, 2, 1));
+        assert_eq!(region.shift(-2), Region::new_at_depth(1, 2, 1));
+        assert_eq!(region.shift(-5), Region::new_at_depth(0, 2, 1));
+    }
+
     #[test]
     fn from_should_properly_convert_range_to_region() {
         let region = Region::from(0..3);
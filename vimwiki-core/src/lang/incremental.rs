@@ -0,0 +1,283 @@
+use crate::lang::{
+    elements::{BlockElement, Located, Page, Region, ShiftRegions},
+    parsers::{self, vimwiki, Span},
+};
+use std::borrow::Cow;
+
+/// Represents a change to some text where the bytes within `region` are
+/// replaced with `text`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit<'a> {
+    /// Represents the bytes of the original text being replaced
+    pub region: Region,
+
+    /// Represents the text that replaces the bytes within the region
+    pub text: Cow<'a, str>,
+}
+
+impl<'a> TextEdit<'a> {
+    /// Creates a new edit that replaces the bytes within `region` of some
+    /// text with `text`
+    pub fn new(
+        region: impl Into<Region>,
+        text: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        Self {
+            region: region.into(),
+            text: text.into(),
+        }
+    }
+
+    /// Produces a copy of `text` with the edit applied
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the region of the edit falls outside of `text` or does not
+    /// start and end on character boundaries
+    pub fn apply(&self, text: &str) -> String {
+        let start = self.region.offset();
+        let end = start + self.region.len();
+
+        let mut s = String::with_capacity(
+            text.len() - self.region.len() + self.text.len(),
+        );
+        s.push_str(&text[..start]);
+        s.push_str(&self.text);
+        s.push_str(&text[end..]);
+        s
+    }
+
+    /// Returns the change in length of the text after the edit is applied
+    pub fn delta(&self) -> isize {
+        self.text.len() as isize - self.region.len() as isize
+    }
+}
+
+/// Represents the result of reparsing a page after a [`TextEdit`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReparsedPage<'a> {
+    /// Represents the page after the edit
+    pub page: Page<'a>,
+
+    /// Represents the indexes of top-level elements within the new page that
+    /// were reparsed; all other elements were carried over from the old page
+    pub changed: Vec<usize>,
+}
+
+impl<'a> Page<'a> {
+    /// Produces a new page reflecting `edit` being applied to the text the
+    /// page was parsed from, where `text` is the result of applying the edit
+    /// (see [`TextEdit::apply`]). The page is left as-is so that it remains
+    /// available if reparsing fails.
+    ///
+    /// Parsing begins at the top-level element preceding the edit and
+    /// continues until it lines up with an element that followed the edit
+    /// in the old page, at which point the remaining elements are reused with
+    /// their regions shifted to account for the edit.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use vimwiki::{Language, Page, TextEdit};
+    ///
+    /// let old_text = "= Header =\n\nsome paragraph\n\n- list item\n";
+    /// let page: Page = Language::from_vimwiki_str(old_text).parse().unwrap();
+    ///
+    /// let edit = TextEdit::new(12..16, "other");
+    /// let new_text = edit.apply(old_text);
+    /// let reparsed = page.reparse(&new_text, &edit).unwrap();
+    /// assert_eq!(reparsed.changed, vec![0, 1]);
+    ///
+    /// let expected: Page = Language::from_vimwiki_str(&new_text).parse().unwrap();
+    /// assert_eq!(reparsed.page, expected);
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Fails if `text` does not contain the text of the edit at the start of
+    /// its region, or if the affected elements cannot be parsed
+    pub fn reparse<'b>(
+        &self,
+        text: &'b str,
+        edit: &TextEdit<'_>,
+    ) -> Result<ReparsedPage<'b>, parsers::Error<'b>>
+    where
+        'a: 'b,
+    {
+        let old_start = edit.region.offset();
+        let old_end = old_start + edit.region.len();
+        let new_end = old_start + edit.text.len();
+        let delta = edit.delta();
+
+        if text.get(old_start..new_end) != Some(edit.text.as_ref()) {
+            return Err(parsers::Error::from_ctx(
+                &Span::from(text),
+                "TextEdit",
+            ));
+        }
+
+        let mut old: Vec<Located<BlockElement<'b>>> = self.elements.clone();
+
+        // Find the first element touching the edit and back up one more as
+        // removing or adding lines can cause the edit to join the element
+        // before it
+        let first = match old
+            .iter()
+            .position(|x| x.region().offset() + x.region().len() >= old_start)
+        {
+            Some(0) => 0,
+            Some(i) => i - 1,
+            None => old.len().saturating_sub(1),
+        };
+        let start = old
+            .get(first)
+            .map(|x| x.region().offset())
+            .filter(|offset| *offset <= old_start)
+            .unwrap_or(0);
+        let first = if start == 0 { 0 } else { first };
+
+        let rest = old.split_off(first);
+        let mut elements = old;
+        let mut changed = Vec::new();
+        let mut input = Span::from(text).advance_start_by(start);
+
+        loop {
+            let cursor = input.start_offset();
+
+            // Once we are past the edit and at the start of a line, check if
+            // we have lined up with an old element that followed the edit
+            let at_line_start =
+                cursor == 0 || text.as_bytes().get(cursor - 1) == Some(&b'\n');
+            if cursor >= new_end && at_line_start {
+                let old_offset = (cursor as isize - delta) as usize;
+                if old_offset >= old_end {
+                    if let Ok(i) = rest.binary_search_by_key(&old_offset, |x| {
+                        x.region().offset()
+                    }) {
+                        elements.extend(rest.into_iter().skip(i).map(
+                            |mut x| {
+                                x.shift_regions(delta);
+                                x
+                            },
+                        ));
+                        break;
+                    }
+                }
+            }
+
+            if input.is_empty() {
+                break;
+            }
+
+            let (next, element) = vimwiki::maybe_block_element(input)?;
+
+            // Guard against parsers that succeed without consuming input,
+            // which would otherwise loop forever
+            if next.start_offset() == cursor {
+                return Err(parsers::Error::from_ctx(&input, "Page"));
+            }
+
+            if let Some(element) = element {
+                changed.push(elements.len());
+                elements.push(element);
+            }
+
+            input = next;
+        }
+
+        Ok(ReparsedPage {
+            page: Page::new(elements),
+            changed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lang::Language, StrictEq};
+
+    /// Applies the edit to the text, reparses the old page incrementally,
+    /// and verifies it matches a full parse (including regions)
+    fn check(text: &str, edit: TextEdit) -> Vec<usize> {
+        let page: Page = Language::from_vimwiki_str(text).parse().unwrap();
+        let new_text = edit.apply(text);
+        let reparsed = page.reparse(&new_text, &edit).unwrap();
+        let expected: Page =
+            Language::from_vimwiki_str(&new_text).parse().unwrap();
+        assert!(
+            reparsed.page.strict_eq(&expected),
+            "{:#?}\n!=\n{:#?}",
+            reparsed.page,
+            expected
+        );
+        reparsed.changed
+    }
+
+    #[test]
+    fn apply_should_replace_region_with_text() {
+        let edit = TextEdit::new(2..4, "xyz");
+        assert_eq!(edit.apply("abcdef"), "abxyzef");
+        assert_eq!(edit.delta(), 1);
+    }
+
+    #[test]
+    fn reparse_should_fail_if_edit_was_not_applied_to_text() {
+        let text = "one\n\ntwo\n";
+        let page: Page = Language::from_vimwiki_str(text).parse().unwrap();
+
+        let edit = TextEdit::new(5..8, "three");
+        assert!(page.reparse(text, &edit).is_err());
+        assert!(page.reparse("one\n", &edit).is_err());
+        assert!(page.reparse(&edit.apply(text), &edit).is_ok());
+    }
+
+    #[test]
+    fn reparse_should_reuse_elements_before_and_after_edit() {
+        let text = "= one =\n\nparagraph\n\n= two =\n\n= three =\n";
+        let changed = check(text, TextEdit::new(20..24, "list"));
+        assert_eq!(changed, vec![1, 2]);
+    }
+
+    #[test]
+    fn reparse_should_shift_regions_of_elements_after_edit() {
+        let text = "first\n\n- *item*\n  - [ ] sub\n\n|a|b|\n";
+        let changed = check(text, TextEdit::new(0..5, "a much longer line"));
+        assert_eq!(changed, vec![0]);
+
+        let changed = check(text, TextEdit::new(0..5, "x"));
+        assert_eq!(changed, vec![0]);
+    }
+
+    #[test]
+    fn reparse_should_support_edits_that_join_elements() {
+        let text = "one\n\ntwo\n\nthree\n";
+        let changed = check(text, TextEdit::new(3..5, "\n"));
+        assert_eq!(changed, vec![0]);
+    }
+
+    #[test]
+    fn reparse_should_support_edits_that_split_elements() {
+        let text = "one\ntwo\n\nthree\n";
+        let changed = check(text, TextEdit::new(3..4, "\n\n= header =\n"));
+        assert_eq!(changed, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn reparse_should_continue_until_elements_line_up() {
+        let text = "one\n\ntwo\n\nthree\n}}}\n\nfour\n";
+        let changed = check(text, TextEdit::new(0..0, "{{{\n"));
+        assert_eq!(changed, vec![0]);
+    }
+
+    #[test]
+    fn reparse_should_support_edits_at_end_of_text() {
+        let text = "one\n\ntwo";
+        assert_eq!(
+            check(text, TextEdit::new(8..8, "\n= three =")),
+            vec![0, 1, 2]
+        );
+        assert_eq!(check(text, TextEdit::new(3..8, "")), vec![0]);
+        assert_eq!(check("", TextEdit::new(0..0, "text")), vec![0]);
+    }
+}
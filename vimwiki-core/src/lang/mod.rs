@@ -1,7 +1,10 @@
+pub mod cst;
 pub mod elements;
+pub mod incremental;
 pub mod output;
 pub mod parsers;
 
+use cst::Cst;
 use derive_more::Display;
 use elements::*;
 use parsers::{vimwiki, IResult, Span};
@@ -101,6 +104,18 @@ macro_rules! impl_from_language {
     };
 }
 
+// Lossless syntax tree
+impl<'a> FromLanguage<'a> for Cst<'a> {
+    type Error = parsers::Error<'a>;
+
+    fn from_language(l: Language<'a>) -> Result<Self, Self::Error> {
+        match l {
+            Language::Vimwiki(x) => Cst::new(x),
+            _ => Err(parsers::Error::unsupported()),
+        }
+    }
+}
+
 // Top-level types
 impl_from_language!(Page<'a>, vimwiki::page);
 impl_from_language!(
@@ -225,6 +240,12 @@ mod tests {
             let _result: Page = input.parse().expect("Failed to parse");
         }
 
+        #[test]
+        fn parse_to_cst() {
+            let input = Language::from_vimwiki_str("some text");
+            let _result: Cst = input.parse().expect("Failed to parse");
+        }
+
         #[test]
         fn parse_to_located_block_element() {
             let input = Language::from_vimwiki_str("some text");
@@ -0,0 +1,382 @@
+use super::{
+    utils, HtmlAssetConfig, HtmlFormatter, HtmlOutputError, HtmlWikiConfig,
+};
+use crate::lang::elements::*;
+use lazy_static::lazy_static;
+use sha1::{Digest, Sha1};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+use uriparse::{Scheme, URIReference};
+
+lazy_static! {
+    /// Hashes of the content of assets alongside the time they were last
+    /// modified, cached so that an asset is only read again once it changes
+    static ref HASHES: Mutex<HashMap<PathBuf, (SystemTime, String)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Total hex characters of the hash of an asset's content kept in its name
+const HASH_LEN: usize = 16;
+
+/// Represents a local file referenced by the html output that is to be
+/// copied alongside it
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HtmlAsset {
+    /// Path to the file being referenced
+    pub source: PathBuf,
+
+    /// Path within the html output of the wiki where the file is copied,
+    /// which is what links and transclusions now point to
+    pub output: PathBuf,
+}
+
+impl HtmlAsset {
+    /// Returns true if the file being referenced exists
+    pub fn exists(&self) -> bool {
+        self.source.is_file()
+    }
+}
+
+/// Returns the uri of the copy of the asset targeted by the link, recording
+/// the asset, or None if assets are not being collected or the link does
+/// not target an asset
+///
+/// Assets are the targets of transclusions that are local (other than wiki
+/// pages) and of links using the `file:` or `local:` scheme, limited to those
+/// within the root of the wiki unless allowing assets outside of it
+pub(super) fn resolve_asset(
+    f: &mut HtmlFormatter,
+    link: &Link<'_>,
+) -> Result<Option<URIReference<'static>>, HtmlOutputError> {
+    if !f.config().assets.enabled {
+        return Ok(None);
+    }
+
+    let data = match link {
+        Link::Wiki { data } | Link::Raw { data } if is_file_scheme(data) => {
+            data
+        }
+        Link::Transclusion { data }
+            if data.is_local() && !data.is_local_anchor() =>
+        {
+            data
+        }
+        _ => return Ok(None),
+    };
+    if data.is_path_dir() {
+        return Ok(None);
+    }
+
+    let wiki = f.config().to_current_wiki();
+    let source = source_path(f, &wiki, data);
+    if wiki.path_within(&source).is_none() && !allows_outside_wiki(f) {
+        return Ok(None);
+    }
+    let output = output_path(&f.config().assets, &wiki, &source);
+
+    let page_out = wiki
+        .make_output_path(f.config().as_active_page_path_within_wiki(), "html");
+    let mut uri_ref =
+        URIReference::from(utils::make_relative_link(page_out, &output)?);
+    uri_ref.map_fragment(|_| {
+        data.uri_ref.fragment().map(|x| x.clone().into_owned())
+    });
+
+    f.add_asset(HtmlAsset { source, output });
+    Ok(Some(uri_ref))
+}
+
+/// Returns true if assets outside of the wiki can be collected, which is
+/// never the case when sanitizing
+fn allows_outside_wiki(f: &HtmlFormatter) -> bool {
+    f.config().assets.allow_outside_wiki && !f.config().sanitize.enabled
+}
+
+fn is_file_scheme(data: &LinkData<'_>) -> bool {
+    match data.scheme() {
+        Some(Scheme::File) => true,
+        Some(Scheme::Unregistered(x)) => x.as_str() == "local",
+        _ => false,
+    }
+}
+
+/// Produces the path to the file targeted by the link, where a relative
+/// path is relative to the directory of the page containing the link and an
+/// absolute path is relative to the root of the wiki unless the link uses
+/// the `file:` or `local:` scheme
+fn source_path(
+    f: &HtmlFormatter,
+    wiki: &HtmlWikiConfig,
+    data: &LinkData<'_>,
+) -> PathBuf {
+    let path =
+        LinkData::decode_uri(data.to_path_buf().to_string_lossy().as_bytes());
+    let path = PathBuf::from(shellexpand::tilde(&path).to_string());
+
+    let path = if path.is_absolute() {
+        if data.scheme().is_none() {
+            wiki.path.join(utils::make_path_relative(path))
+        } else {
+            path
+        }
+    } else {
+        f.current_page()
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(path)
+    };
+
+    utils::normalize_path(&path)
+}
+
+/// Produces the path where an asset is copied, which mirrors its location
+/// within the wiki unless naming by hash or the asset is outside the wiki
+fn output_path(
+    config: &HtmlAssetConfig,
+    wiki: &HtmlWikiConfig,
+    source: &Path,
+) -> PathBuf {
+    if let Some(path) = wiki.path_within(source).filter(|_| !config.hashed) {
+        return wiki.path_html.join(path);
+    }
+
+    let stem = source
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match hash_file(source) {
+        Some(hash) => {
+            let hash = &hash[..HASH_LEN.min(hash.len())];
+            match source.extension() {
+                Some(ext) => {
+                    format!("{}-{}.{}", stem, hash, ext.to_string_lossy())
+                }
+                None => format!("{}-{}", stem, hash),
+            }
+        }
+
+        // NOTE: A missing asset cannot be hashed, so we keep its name
+        None => source
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or(stem),
+    };
+
+    wiki.path_html.join(&config.hashed_dir).join(name)
+}
+
+/// Returns the hash of the content of the file, or None if it cannot be read
+fn hash_file(path: &Path) -> Option<String> {
+    let modified = fs::metadata(path).and_then(|x| x.modified()).ok()?;
+
+    let mut hashes = HASHES.lock().unwrap_or_else(|x| x.into_inner());
+    if let Some((time, hash)) = hashes.get(path) {
+        if *time == modified {
+            return Some(hash.to_string());
+        }
+    }
+
+    let mut hasher = Sha1::new();
+    io::copy(&mut fs::File::open(path).ok()?, &mut hasher).ok()?;
+    let hash = format!("{:x}", hasher.finalize());
+    hashes.insert(path.to_path_buf(), (modified, hash.to_string()));
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        HtmlConfig, HtmlRuntimeConfig, HtmlSanitizeConfig, Language,
+        ToHtmlString,
+    };
+
+    fn to_html(
+        root: &Path,
+        page: &str,
+        text: &str,
+        hashed: bool,
+    ) -> (String, Vec<HtmlAsset>) {
+        to_html_with_config(
+            root,
+            page,
+            text,
+            HtmlConfig {
+                assets: HtmlAssetConfig {
+                    enabled: true,
+                    hashed,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+    }
+
+    fn to_html_with_config(
+        root: &Path,
+        page: &str,
+        text: &str,
+        config: HtmlConfig,
+    ) -> (String, Vec<HtmlAsset>) {
+        let page_path = root.join(page);
+        let page: Page = Language::from_vimwiki_str(text).parse().unwrap();
+        let (html, report) = page
+            .to_html_string_with_report(HtmlConfig {
+                wikis: vec![HtmlWikiConfig {
+                    path: root.to_path_buf(),
+                    path_html: root.join("html"),
+                    ..Default::default()
+                }],
+                runtime: HtmlRuntimeConfig {
+                    wiki_index: Some(0),
+                    page: page_path,
+                    ..Default::default()
+                },
+                ..config
+            })
+            .unwrap();
+        (html, report.assets)
+    }
+
+    fn write_file(root: &Path, path: &str, text: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn assets_should_mirror_their_location_within_the_wiki() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_file(root, "img/pic.png", "png");
+        write_file(root, "docs/report.pdf", "pdf");
+
+        let (html, assets) = to_html(
+            root,
+            "notes/page.wiki",
+            "{{../img/pic.png}} [[local:../docs/report.pdf#page=2|report]]",
+            false,
+        );
+        assert_eq!(
+            html,
+            "<p><img src=\"../img/pic.png\" /> <a href=\"../docs/report.pdf#page=2\">report</a></p>\n"
+        );
+        assert_eq!(
+            assets,
+            vec![
+                HtmlAsset {
+                    source: root.join("img/pic.png"),
+                    output: root.join("html/img/pic.png"),
+                },
+                HtmlAsset {
+                    source: root.join("docs/report.pdf"),
+                    output: root.join("html/docs/report.pdf"),
+                },
+            ]
+        );
+        assert!(assets.iter().all(HtmlAsset::exists));
+    }
+
+    #[test]
+    fn assets_should_be_named_by_hash_of_content_if_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_file(root, "img/pic.png", "png");
+
+        let (html, assets) =
+            to_html(root, "page.wiki", "{{/img/pic.png}}", true);
+        let name = "pic-9040a7d6cdf7a0d6.png";
+        assert_eq!(html, format!("<p><img src=\"assets/{}\" /></p>\n", name));
+        assert_eq!(assets[0].output, root.join("html/assets").join(name));
+    }
+
+    #[test]
+    fn assets_outside_of_wiki_should_be_placed_in_hashed_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let wiki = dir.path().join("wiki");
+        write_file(dir.path(), "wiki/page.wiki", "");
+        let outside = dir.path().join("music.mp3");
+        fs::write(&outside, "mp3").unwrap();
+
+        let (_, assets) = to_html_with_config(
+            &wiki,
+            "page.wiki",
+            &format!("[[file:{}]]", outside.to_string_lossy()),
+            HtmlConfig {
+                assets: HtmlAssetConfig {
+                    enabled: true,
+                    allow_outside_wiki: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].source, outside);
+        assert!(assets[0].output.starts_with(wiki.join("html/assets")));
+    }
+
+    #[test]
+    fn assets_outside_of_wiki_should_not_be_collected_unless_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let wiki = dir.path().join("wiki");
+        write_file(dir.path(), "wiki/page.wiki", "");
+        write_file(dir.path(), "private/secret.png", "png");
+        let text = format!(
+            "{{{{/../private/secret.png}}}} [[file:{}]]",
+            dir.path().join("private/secret.png").to_string_lossy()
+        );
+
+        let (_, assets) = to_html(&wiki, "page.wiki", &text, false);
+        assert!(assets.is_empty(), "{:?}", assets);
+
+        // Even when allowed, sanitizing never collects them
+        let (_, assets) = to_html_with_config(
+            &wiki,
+            "page.wiki",
+            &text,
+            HtmlConfig {
+                assets: HtmlAssetConfig {
+                    enabled: true,
+                    allow_outside_wiki: true,
+                    ..Default::default()
+                },
+                sanitize: HtmlSanitizeConfig {
+                    enabled: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        assert!(assets.is_empty(), "{:?}", assets);
+    }
+
+    #[test]
+    fn missing_assets_should_still_be_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let (html, assets) =
+            to_html(dir.path(), "page.wiki", "{{missing.png}}", false);
+        assert_eq!(html, "<p><img src=\"missing.png\" /></p>\n");
+        assert_eq!(assets.len(), 1);
+        assert!(!assets[0].exists());
+    }
+
+    #[test]
+    fn links_to_pages_and_remote_uris_should_not_be_assets() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let (_, assets) = to_html(
+            dir.path(),
+            "page.wiki",
+            "[[other]] {{https://example.com/img.png}} https://example.com",
+            false,
+        );
+        assert!(assets.is_empty());
+    }
+}
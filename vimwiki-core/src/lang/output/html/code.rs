@@ -0,0 +1,334 @@
+use super::{HtmlCodeConfig, HtmlOutputError};
+use lazy_static::lazy_static;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use syntect::{
+    highlighting::{Color, Theme, ThemeSet},
+    html::{self, ClassStyle},
+    parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet},
+};
+
+lazy_static! {
+    /// Default syntax set for languages
+    static ref DEFAULT_SYNTAX_SET: Arc<SyntaxSet> =
+        Arc::new(SyntaxSet::load_defaults_nonewlines());
+
+    /// Default theme highlight set for languages
+    static ref DEFAULT_THEME_SET: Arc<ThemeSet> =
+        Arc::new(ThemeSet::load_defaults());
+
+    /// Syntax sets loaded from directories, cached so that each directory is
+    /// only loaded once rather than for every code block
+    static ref SYNTAX_SETS: Mutex<HashMap<PathBuf, Arc<SyntaxSet>>> =
+        Mutex::new(HashMap::new());
+
+    /// Theme sets loaded from directories, cached so that each directory is
+    /// only loaded once rather than for every code block
+    static ref THEME_SETS: Mutex<HashMap<PathBuf, Arc<ThemeSet>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Returns the syntax set loaded from the given directory, or the default
+/// syntax set if no directory is provided
+pub(super) fn load_syntax_set(
+    dir: Option<&Path>,
+) -> Result<Arc<SyntaxSet>, HtmlOutputError> {
+    match dir {
+        Some(dir) => {
+            let mut sets =
+                SYNTAX_SETS.lock().unwrap_or_else(|x| x.into_inner());
+            match sets.get(dir) {
+                Some(ss) => Ok(Arc::clone(ss)),
+                None => {
+                    let ss = Arc::new(SyntaxSet::load_from_folder(dir)?);
+                    sets.insert(dir.to_path_buf(), Arc::clone(&ss));
+                    Ok(ss)
+                }
+            }
+        }
+        None => Ok(Arc::clone(&DEFAULT_SYNTAX_SET)),
+    }
+}
+
+/// Returns the theme set loaded from the given directory, or the default
+/// theme set if no directory is provided
+pub(super) fn load_theme_set(
+    dir: Option<&Path>,
+) -> Result<Arc<ThemeSet>, HtmlOutputError> {
+    match dir {
+        Some(dir) => {
+            let mut sets = THEME_SETS.lock().unwrap_or_else(|x| x.into_inner());
+            match sets.get(dir) {
+                Some(ts) => Ok(Arc::clone(ts)),
+                None => {
+                    let ts = Arc::new(ThemeSet::load_from_folder(dir)?);
+                    sets.insert(dir.to_path_buf(), Arc::clone(&ts));
+                    Ok(ts)
+                }
+            }
+        }
+        None => Ok(Arc::clone(&DEFAULT_THEME_SET)),
+    }
+}
+
+/// Looks up the theme with the given name, reporting an error if missing
+pub(super) fn find_theme<'a>(
+    ts: &'a ThemeSet,
+    name: &str,
+) -> Result<&'a Theme, HtmlOutputError> {
+    ts.themes
+        .get(name)
+        .ok_or_else(|| HtmlOutputError::ThemeMissing(name.to_string()))
+}
+
+/// Produces the stylesheet to accompany code highlighted server-side using
+/// css classes, based on the theme (and theme directory) of the config
+///
+/// Alongside the styles of the theme, this includes styles for line numbers
+/// and highlighted lines
+pub fn highlight_css(
+    config: &HtmlCodeConfig,
+) -> Result<String, HtmlOutputError> {
+    let ts = load_theme_set(config.theme_dir.as_deref())?;
+    let theme = find_theme(&ts, &config.theme)?;
+
+    let mut css =
+        html::css_for_theme_with_class_style(theme, ClassStyle::Spaced);
+    css.push_str(
+        ".code .line-number {\n user-select: none;\n opacity: 0.6;\n margin-right: 1em;\n}\n",
+    );
+    css.push_str(&format!(
+        ".code .line.highlighted {{\n display: inline-block;\n width: 100%;\n background-color: {};\n}}\n",
+        to_css_color(line_highlight_color(theme)),
+    ));
+    Ok(css)
+}
+
+/// Returns the color of highlighted lines for the theme, falling back to a
+/// translucent yellow when the theme does not specify one
+pub(super) fn line_highlight_color(theme: &Theme) -> Color {
+    theme.settings.line_highlight.unwrap_or(Color {
+        r: 0xff,
+        g: 0xee,
+        b: 0x58,
+        a: 0x66,
+    })
+}
+
+pub(super) fn to_css_color(c: Color) -> String {
+    if c.a == 0xff {
+        format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", c.r, c.g, c.b, c.a)
+    }
+}
+
+/// Highlights individual lines of code using css classes, keeping every line
+/// self-contained by reopening the spans of scopes that continue from the
+/// previous line and closing those still open at the end of the line
+pub(super) struct ClassedLineHighlighter<'a> {
+    ss: &'a SyntaxSet,
+    state: ParseState,
+    stack: ScopeStack,
+}
+
+impl<'a> ClassedLineHighlighter<'a> {
+    pub fn new(syntax: &SyntaxReference, ss: &'a SyntaxSet) -> Self {
+        Self {
+            ss,
+            state: ParseState::new(syntax),
+            stack: ScopeStack::new(),
+        }
+    }
+
+    pub fn highlight(&mut self, line: &str) -> String {
+        let mut html = String::new();
+        for scope in self.stack.as_slice() {
+            html.push_str(&format!(
+                r#"<span class="{}">"#,
+                scope.build_string().replace('.', " ")
+            ));
+        }
+
+        let ops = self.state.parse_line(line, self.ss);
+        let (spans, _) = html::line_tokens_to_classed_spans(
+            line,
+            &ops[..],
+            ClassStyle::Spaced,
+            &mut self.stack,
+        );
+        html.push_str(&spans);
+
+        for _ in 0..self.stack.len() {
+            html.push_str("</span>");
+        }
+
+        html
+    }
+}
+
+/// Represents options for the lines of a server-side highlighted code block
+/// taken from the metadata of the block, namely `linenos` to include line
+/// numbers, `linenostart` for the number of the first line, and `hl_lines`
+/// for the lines to highlight such as `1 3-5`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(super) struct CodeLineOptions {
+    pub line_numbers: bool,
+    pub line_number_start: usize,
+    pub highlighted: Vec<RangeInclusive<usize>>,
+}
+
+impl CodeLineOptions {
+    pub fn from_metadata(
+        metadata: &HashMap<Cow<'_, str>, Cow<'_, str>>,
+    ) -> Self {
+        let line_numbers = metadata.get("linenos").is_some_and(|x| {
+            !matches!(
+                x.trim().to_lowercase().as_str(),
+                "false" | "no" | "off" | "0"
+            )
+        });
+
+        let line_number_start = metadata
+            .get("linenostart")
+            .and_then(|x| x.trim().parse().ok())
+            .unwrap_or(1);
+
+        let mut highlighted = Vec::new();
+        if let Some(lines) = metadata.get("hl_lines") {
+            for part in lines
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|x| !x.is_empty())
+            {
+                match part.split_once('-') {
+                    Some((start, end)) => {
+                        if let (Ok(start), Ok(end)) =
+                            (start.parse::<usize>(), end.parse::<usize>())
+                        {
+                            highlighted.push(start..=end);
+                        }
+                    }
+                    None => {
+                        if let Ok(line) = part.parse() {
+                            highlighted.push(line..=line);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            line_numbers,
+            line_number_start,
+            highlighted,
+        }
+    }
+
+    /// Returns true if lines need to be individually wrapped to support line
+    /// numbers or highlighting
+    pub fn is_wrapped(&self) -> bool {
+        self.line_numbers || !self.highlighted.is_empty()
+    }
+
+    /// Returns true if the line at the given index (starting at zero) within
+    /// the code block is highlighted
+    pub fn is_highlighted(&self, idx: usize) -> bool {
+        self.highlighted.iter().any(|x| x.contains(&(idx + 1)))
+    }
+
+    /// Returns the number to display for the line at the given index
+    /// (starting at zero) within the code block, stopping at the largest
+    /// number rather than overflowing
+    pub fn line_number(&self, idx: usize) -> usize {
+        self.line_number_start.saturating_add(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(
+        pairs: &[(&'static str, &'static str)],
+    ) -> HashMap<Cow<'static, str>, Cow<'static, str>> {
+        pairs
+            .iter()
+            .map(|(k, v)| (Cow::from(*k), Cow::from(*v)))
+            .collect()
+    }
+
+    #[test]
+    fn code_line_options_should_default_to_no_wrapping() {
+        let options = CodeLineOptions::from_metadata(&metadata(&[]));
+        assert!(!options.is_wrapped());
+        assert_eq!(options.line_number(0), 1);
+    }
+
+    #[test]
+    fn code_line_options_should_parse_line_numbers_and_highlights() {
+        let options = CodeLineOptions::from_metadata(&metadata(&[
+            ("linenos", "true"),
+            ("linenostart", "10"),
+            ("hl_lines", "1, 3-5 bad 7-x"),
+        ]));
+        assert!(options.is_wrapped());
+        assert!(options.line_numbers);
+        assert_eq!(options.line_number(2), 12);
+        assert!(options.is_highlighted(0));
+        assert!(!options.is_highlighted(1));
+        assert!(options.is_highlighted(2));
+        assert!(options.is_highlighted(4));
+        assert!(!options.is_highlighted(5));
+        assert!(!options.is_highlighted(6));
+
+        let options =
+            CodeLineOptions::from_metadata(&metadata(&[("linenos", "no")]));
+        assert!(!options.line_numbers);
+    }
+
+    #[test]
+    fn code_line_options_should_not_overflow_line_numbers() {
+        let mut metadata = metadata(&[("linenos", "true")]);
+        metadata.insert(
+            Cow::from("linenostart"),
+            Cow::from(usize::MAX.to_string()),
+        );
+        let options = CodeLineOptions::from_metadata(&metadata);
+        assert_eq!(options.line_number(0), usize::MAX);
+        assert_eq!(options.line_number(1), usize::MAX);
+    }
+
+    #[test]
+    fn load_syntax_set_should_cache_sets_by_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = load_syntax_set(Some(dir.path())).unwrap();
+        let b = load_syntax_set(Some(dir.path())).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let a = load_theme_set(Some(dir.path())).unwrap();
+        let b = load_theme_set(Some(dir.path())).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn highlight_css_should_include_theme_and_line_styles() {
+        let css = highlight_css(&HtmlCodeConfig::default()).unwrap();
+        assert!(css.contains(".code {"), "{}", css);
+        assert!(css.contains(".code .line-number {"), "{}", css);
+        assert!(css.contains(".code .line.highlighted {"), "{}", css);
+
+        let err = highlight_css(&HtmlCodeConfig {
+            theme: String::from("missing"),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(
+            matches!(err, HtmlOutputError::ThemeMissing(x) if x == "missing")
+        );
+    }
+}
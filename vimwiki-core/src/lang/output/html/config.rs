@@ -1,7 +1,11 @@
-use super::utils::{deserialize_absolute_path, make_path_relative};
+use super::{
+    utils::{deserialize_absolute_path, make_path_relative},
+    HtmlHooks, HtmlTemplateValue,
+};
 use derive_more::{AsMut, AsRef, Deref, DerefMut};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     convert::TryFrom,
     path::{Component, Path, PathBuf},
 };
@@ -40,6 +44,12 @@ pub struct HtmlConfig {
     #[serde(skip)]
     pub runtime: HtmlRuntimeConfig,
 
+    /// Represents hooks that override the html output of specific elements
+    ///
+    /// [RUNTIME ONLY] Runtime-only config that is not saved/loaded!
+    #[serde(skip)]
+    pub hooks: HtmlHooks,
+
     /// Maps to vimwiki's wiki config and order matters for use in indexed
     /// wiki links
     #[serde(default)]
@@ -57,6 +67,10 @@ pub struct HtmlConfig {
     #[serde(default)]
     pub link: HtmlLinkConfig,
 
+    /// Configuration settings that apply specifically to transclusions
+    #[serde(default)]
+    pub transclusion: HtmlTransclusionConfig,
+
     /// Configuration settings that apply specifically to headers
     #[serde(default)]
     pub header: HtmlHeaderConfig,
@@ -65,6 +79,10 @@ pub struct HtmlConfig {
     #[serde(default)]
     pub code: HtmlCodeConfig,
 
+    /// Configuration settings that apply specifically to math
+    #[serde(default)]
+    pub math: HtmlMathConfig,
+
     /// Configuration settings that apply specifically to comments
     #[serde(default)]
     pub comment: HtmlCommentConfig,
@@ -72,6 +90,14 @@ pub struct HtmlConfig {
     /// Configuration settings that apply specifically to templates
     #[serde(default)]
     pub template: HtmlTemplateConfig,
+
+    /// Configuration settings that apply specifically to sanitizing output
+    #[serde(default)]
+    pub sanitize: HtmlSanitizeConfig,
+
+    /// Configuration settings that apply specifically to assets
+    #[serde(default)]
+    pub assets: HtmlAssetConfig,
 }
 
 impl HtmlConfig {
@@ -270,6 +296,11 @@ pub struct HtmlRuntimeConfig {
 
     /// Path to the page's file that is being processed
     pub page: PathBuf,
+
+    /// Additional variables available to the template of the page being
+    /// processed, such as backlinks computed from the rest of the wiki,
+    /// which take precedence over the variables of the page itself
+    pub variables: HashMap<String, HtmlTemplateValue>,
 }
 
 impl HtmlRuntimeConfig {
@@ -296,6 +327,7 @@ impl Default for HtmlRuntimeConfig {
             //       being included, otherwise trying to map the runtime
             //       page (default) to a tmp wiki (default) will fail
             page: HtmlWikiConfig::default_path().join("index.wiki"),
+            variables: HashMap::new(),
         }
     }
 }
@@ -644,6 +676,12 @@ pub struct HtmlCodeConfig {
     #[serde(default = "HtmlCodeConfig::default_server_side")]
     pub server_side: bool,
 
+    /// If true, server-side rendering will mark code with css classes
+    /// instead of inlining styles, requiring a stylesheet for the theme such
+    /// as the one produced by `vimwiki css`
+    #[serde(default = "HtmlCodeConfig::default_classed")]
+    pub classed: bool,
+
     /// Represents the directory containing `.tmLanguage` syntax files to be used
     /// for language syntax when being performed server-side instead of client-side
     #[serde(default = "HtmlCodeConfig::default_syntax_dir")]
@@ -656,6 +694,7 @@ impl Default for HtmlCodeConfig {
             theme: Self::default_theme(),
             theme_dir: Self::default_theme_dir(),
             server_side: Self::default_server_side(),
+            classed: Self::default_classed(),
             syntax_dir: Self::default_syntax_dir(),
         }
     }
@@ -677,12 +716,332 @@ impl HtmlCodeConfig {
         false
     }
 
+    #[inline]
+    pub fn default_classed() -> bool {
+        false
+    }
+
     #[inline]
     pub fn default_syntax_dir() -> Option<PathBuf> {
         None
     }
 }
 
+/// Represents configuration options related to transclusions
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HtmlTransclusionConfig {
+    /// Maximum number of wiki pages that can be transcluded within one
+    /// another, which protects against deeply-nested transclusions
+    #[serde(default = "HtmlTransclusionConfig::default_max_depth")]
+    pub max_depth: usize,
+
+    /// If true, will include controls for video and audio unless the
+    /// transclusion provides its own `controls` property
+    #[serde(default = "HtmlTransclusionConfig::default_media_controls")]
+    pub media_controls: bool,
+
+    /// Names of attributes that properties can set on any element besides
+    /// images, where a name ending in `*` such as `data-*` allows any
+    /// attribute starting with what comes before it
+    #[serde(default = "HtmlTransclusionConfig::default_global_attributes")]
+    pub global_attributes: Vec<String>,
+
+    /// Transclusions rendered as `<video>`
+    #[serde(default = "HtmlTransclusionConfig::default_video")]
+    pub video: HtmlTransclusionElementConfig,
+
+    /// Transclusions rendered as `<audio>`
+    #[serde(default = "HtmlTransclusionConfig::default_audio")]
+    pub audio: HtmlTransclusionElementConfig,
+
+    /// Transclusions rendered as `<iframe>`
+    #[serde(default = "HtmlTransclusionConfig::default_iframe")]
+    pub iframe: HtmlTransclusionElementConfig,
+
+    /// Transclusions rendered as `<object>`
+    #[serde(default = "HtmlTransclusionConfig::default_object")]
+    pub object: HtmlTransclusionElementConfig,
+}
+
+impl Default for HtmlTransclusionConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: Self::default_max_depth(),
+            media_controls: Self::default_media_controls(),
+            global_attributes: Self::default_global_attributes(),
+            video: Self::default_video(),
+            audio: Self::default_audio(),
+            iframe: Self::default_iframe(),
+            object: Self::default_object(),
+        }
+    }
+}
+
+impl HtmlTransclusionConfig {
+    #[inline]
+    pub fn default_max_depth() -> usize {
+        8
+    }
+
+    #[inline]
+    pub fn default_media_controls() -> bool {
+        true
+    }
+
+    #[inline]
+    pub fn default_global_attributes() -> Vec<String> {
+        to_strings(&[
+            "class", "dir", "hidden", "id", "lang", "style", "tabindex",
+            "title", "aria-*", "data-*",
+        ])
+    }
+
+    #[inline]
+    pub fn default_video() -> HtmlTransclusionElementConfig {
+        HtmlTransclusionElementConfig {
+            extensions: to_strings(&["m4v", "mov", "mp4", "ogv", "webm"]),
+            media_types: to_strings(&["video/*"]),
+            attributes: to_strings(&[
+                "autoplay",
+                "controls",
+                "crossorigin",
+                "height",
+                "loop",
+                "muted",
+                "playsinline",
+                "poster",
+                "preload",
+                "width",
+            ]),
+        }
+    }
+
+    #[inline]
+    pub fn default_audio() -> HtmlTransclusionElementConfig {
+        HtmlTransclusionElementConfig {
+            extensions: to_strings(&[
+                "aac", "flac", "m4a", "mp3", "oga", "ogg", "opus", "wav",
+            ]),
+            media_types: to_strings(&["audio/*"]),
+            attributes: to_strings(&[
+                "autoplay",
+                "controls",
+                "crossorigin",
+                "loop",
+                "muted",
+                "preload",
+            ]),
+        }
+    }
+
+    #[inline]
+    pub fn default_iframe() -> HtmlTransclusionElementConfig {
+        HtmlTransclusionElementConfig {
+            extensions: to_strings(&["htm", "html", "pdf"]),
+            media_types: to_strings(&["application/pdf"]),
+            attributes: to_strings(&[
+                "allow",
+                "allowfullscreen",
+                "height",
+                "loading",
+                "name",
+                "referrerpolicy",
+                "sandbox",
+                "width",
+            ]),
+        }
+    }
+
+    #[inline]
+    pub fn default_object() -> HtmlTransclusionElementConfig {
+        HtmlTransclusionElementConfig {
+            extensions: to_strings(&["csv", "json", "txt", "xml"]),
+            media_types: to_strings(&[
+                "application/json",
+                "application/xml",
+                "text/csv",
+                "text/plain",
+                "text/xml",
+            ]),
+            attributes: to_strings(&["height", "name", "type", "width"]),
+        }
+    }
+}
+
+/// Represents configuration options for transclusions rendered as a specific
+/// element, where those that match none of the elements are images
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HtmlTransclusionElementConfig {
+    /// Extensions (without the leading `.`) of the paths of transclusions to
+    /// render as the element, compared ignoring case
+    #[serde(default)]
+    pub extensions: Vec<String>,
+
+    /// Media types of `data:` uris of transclusions to render as the
+    /// element, where a media type ending in `*` such as `video/*` matches
+    /// any media type starting with what comes before it
+    #[serde(default)]
+    pub media_types: Vec<String>,
+
+    /// Names of attributes specific to the element that properties of
+    /// transclusions can set, alongside the global attributes; properties
+    /// with any other name are dropped
+    #[serde(default)]
+    pub attributes: Vec<String>,
+}
+
+fn to_strings(x: &[&str]) -> Vec<String> {
+    x.iter().map(ToString::to_string).collect()
+}
+
+/// Represents configuration options related to assets, which are the local
+/// files (such as images and attachments) referenced by transclusions and by
+/// links using the `file:` or `local:` scheme
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HtmlAssetConfig {
+    /// If true, will collect assets to be copied into the html output of the
+    /// wiki, mirroring their location within the wiki, and point links and
+    /// transclusions to the copies
+    #[serde(default = "HtmlAssetConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// If true, will name copies of assets by the hash of their content
+    /// within `hashed_dir` rather than mirroring their location
+    #[serde(default = "HtmlAssetConfig::default_hashed")]
+    pub hashed: bool,
+
+    /// Directory relative to the html output of the wiki that contains
+    /// copies of assets named by the hash of their content, which is also
+    /// used for assets outside of the wiki
+    #[serde(default = "HtmlAssetConfig::default_hashed_dir")]
+    pub hashed_dir: PathBuf,
+
+    /// If true, will also collect assets outside of the root of the wiki
+    /// (such as `[[file:/etc/hosts]]` or `{{~/pic.png}}`) rather than only
+    /// those within it, which is never the case when sanitizing
+    #[serde(default = "HtmlAssetConfig::default_allow_outside_wiki")]
+    pub allow_outside_wiki: bool,
+}
+
+impl Default for HtmlAssetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            hashed: Self::default_hashed(),
+            hashed_dir: Self::default_hashed_dir(),
+            allow_outside_wiki: Self::default_allow_outside_wiki(),
+        }
+    }
+}
+
+impl HtmlAssetConfig {
+    #[inline]
+    pub fn default_enabled() -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn default_hashed() -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn default_hashed_dir() -> PathBuf {
+        PathBuf::from("assets")
+    }
+
+    #[inline]
+    pub fn default_allow_outside_wiki() -> bool {
+        false
+    }
+}
+
+/// Represents configuration options related to math
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HtmlMathConfig {
+    /// If true, will render math server-side as MathML instead of leaving the
+    /// TeX within delimiters for MathJax to render client-side; any math
+    /// that cannot be converted is still written within delimiters
+    #[serde(default = "HtmlMathConfig::default_server_side")]
+    pub server_side: bool,
+}
+
+impl Default for HtmlMathConfig {
+    fn default() -> Self {
+        Self {
+            server_side: Self::default_server_side(),
+        }
+    }
+}
+
+impl HtmlMathConfig {
+    #[inline]
+    pub fn default_server_side() -> bool {
+        false
+    }
+}
+
+/// Represents configuration options related to sanitizing output, which is
+/// useful for wikis that accept contributions from many people
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HtmlSanitizeConfig {
+    /// If true, will only write attributes whose names are allowed and will
+    /// not write links or transclusions whose uri uses a blocked scheme,
+    /// reporting anything that is stripped
+    #[serde(default = "HtmlSanitizeConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// Names of attributes that can be written for code blocks and links
+    /// when sanitizing, where a name ending in `*` such as `data-*` allows
+    /// any attribute starting with what comes before it
+    #[serde(default = "HtmlSanitizeConfig::default_allowed_attributes")]
+    pub allowed_attributes: Vec<String>,
+
+    /// Schemes of uris such as `javascript` that are not written for links
+    /// and transclusions when sanitizing
+    #[serde(default = "HtmlSanitizeConfig::default_blocked_uri_schemes")]
+    pub blocked_uri_schemes: Vec<String>,
+}
+
+impl Default for HtmlSanitizeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            allowed_attributes: Self::default_allowed_attributes(),
+            blocked_uri_schemes: Self::default_blocked_uri_schemes(),
+        }
+    }
+}
+
+impl HtmlSanitizeConfig {
+    #[inline]
+    pub fn default_enabled() -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn default_allowed_attributes() -> Vec<String> {
+        vec![
+            String::from("alt"),
+            String::from("autoplay"),
+            String::from("class"),
+            String::from("controls"),
+            String::from("height"),
+            String::from("id"),
+            String::from("lang"),
+            String::from("muted"),
+            String::from("title"),
+            String::from("width"),
+            String::from("data-*"),
+        ]
+    }
+
+    #[inline]
+    pub fn default_blocked_uri_schemes() -> Vec<String> {
+        vec![String::from("javascript"), String::from("vbscript")]
+    }
+}
+
 /// Represents configuration options related to comments
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HtmlCommentConfig {
@@ -726,6 +1085,13 @@ pub struct HtmlTemplateConfig {
     /// is specified
     #[serde(default = "HtmlTemplateConfig::default_text")]
     pub text: String,
+
+    /// If true, templates are written like those of vimwiki where only
+    /// `%name%` placeholders are replaced with the unescaped value of the
+    /// variable `name` and all other text is kept as-is; otherwise, templates
+    /// use the `{{ name }}` and `{% tag %}` syntax of `HtmlTemplateRenderer`
+    #[serde(default = "HtmlTemplateConfig::default_legacy_placeholders")]
+    pub legacy_placeholders: bool,
 }
 
 impl Default for HtmlTemplateConfig {
@@ -735,6 +1101,7 @@ impl Default for HtmlTemplateConfig {
             ext: Self::default_ext(),
             dir: Self::default_dir(),
             text: Self::default_text(),
+            legacy_placeholders: Self::default_legacy_placeholders(),
         }
     }
 }
@@ -786,4 +1153,9 @@ impl HtmlTemplateConfig {
 
         DEFAULT_TEMPLATE_STR.to_string()
     }
+
+    #[inline]
+    pub const fn default_legacy_placeholders() -> bool {
+        true
+    }
 }
@@ -1,5 +1,33 @@
-use super::{utils, HtmlConfig, HtmlFormatter, HtmlOutputError, Output};
+use super::{
+    utils, HtmlAsset, HtmlConfig, HtmlFormatter, HtmlOutputError, HtmlStripped,
+    HtmlTemplateConfig, HtmlTemplateRenderer, HtmlTemplateValue, Output,
+};
 use chrono::Local;
+use std::{cell::RefCell, collections::BTreeMap, path::PathBuf};
+
+/// Represents what was found while converting to html besides the output
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HtmlReport {
+    /// Anything that was stripped from the output when sanitizing
+    pub stripped: Vec<HtmlStripped>,
+
+    /// Local files referenced by the output that are to be copied alongside
+    /// it, which are only collected when assets are enabled
+    pub assets: Vec<HtmlAsset>,
+
+    /// Paths of the template files that were loaded to produce an html page
+    pub templates: Vec<PathBuf>,
+}
+
+impl HtmlReport {
+    fn from_formatter(formatter: &mut HtmlFormatter) -> Self {
+        Self {
+            stripped: formatter.take_stripped(),
+            assets: formatter.take_assets(),
+            templates: Vec::new(),
+        }
+    }
+}
 
 pub trait ToHtmlString {
     /// Converts to individual HTML dom string
@@ -7,6 +35,14 @@ pub trait ToHtmlString {
         &self,
         config: HtmlConfig,
     ) -> Result<String, HtmlOutputError>;
+
+    /// Converts to individual HTML dom string, also returning a report of
+    /// anything that was stripped from the output when sanitizing and the
+    /// assets referenced by the output
+    fn to_html_string_with_report(
+        &self,
+        config: HtmlConfig,
+    ) -> Result<(String, HtmlReport), HtmlOutputError>;
 }
 
 impl<T: Output<HtmlFormatter>> ToHtmlString for T {
@@ -14,9 +50,18 @@ impl<T: Output<HtmlFormatter>> ToHtmlString for T {
         &self,
         config: HtmlConfig,
     ) -> Result<String, HtmlOutputError> {
+        self.to_html_string_with_report(config)
+            .map(|(content, _)| content)
+    }
+
+    fn to_html_string_with_report(
+        &self,
+        config: HtmlConfig,
+    ) -> Result<(String, HtmlReport), HtmlOutputError> {
         let mut formatter = HtmlFormatter::new(config);
         self.fmt(&mut formatter)?;
-        Ok(formatter.into_content())
+        let report = HtmlReport::from_formatter(&mut formatter);
+        Ok((formatter.into_content(), report))
     }
 }
 
@@ -26,6 +71,14 @@ pub trait ToHtmlPage {
         &self,
         config: HtmlConfig,
     ) -> Result<String, HtmlOutputError>;
+
+    /// Converts to an HTML page string, also returning a report of anything
+    /// that was stripped from the output when sanitizing and the assets
+    /// referenced by the output
+    fn to_html_page_with_report(
+        &self,
+        config: HtmlConfig,
+    ) -> Result<(String, HtmlReport), HtmlOutputError>;
 }
 
 impl<T: Output<HtmlFormatter>> ToHtmlPage for T {
@@ -33,6 +86,13 @@ impl<T: Output<HtmlFormatter>> ToHtmlPage for T {
         &self,
         config: HtmlConfig,
     ) -> Result<String, HtmlOutputError> {
+        self.to_html_page_with_report(config).map(|(page, _)| page)
+    }
+
+    fn to_html_page_with_report(
+        &self,
+        config: HtmlConfig,
+    ) -> Result<(String, HtmlReport), HtmlOutputError> {
         // Build an HTML formatter using the provided config and funnel our
         // output through it
         let mut formatter = HtmlFormatter::new(config);
@@ -55,62 +115,144 @@ impl<T: Output<HtmlFormatter>> ToHtmlPage for T {
             .unwrap_or_else(|| Local::now().naive_local().date());
 
         // Attempts to find and load template in {TEMPLATE_DIR}/{TEMPLATE},
-        // defaulting to vimwiki's standard template
+        // defaulting to vimwiki's standard template, and keeps track of
+        // every template file that is loaded for the report
+        let template_config = formatter.config().template.clone();
+        let templates = RefCell::new(Vec::new());
+        let load = |name: &str| {
+            let (path, text) = load_template(&template_config, name)?;
+            let mut templates = templates.borrow_mut();
+            if !templates.contains(&path) {
+                templates.push(path);
+            }
+            Ok(text)
+        };
         let template = formatter
             .take_template()
-            .map(|p| formatter.config().template.dir.join(p))
-            .map(std::fs::read_to_string)
-            .transpose()
-            .map_err(|source| HtmlOutputError::TemplateNotLoaded { source })?
-            .unwrap_or_else(|| formatter.config().template.text.to_string());
-
-        // Fill in template variables
-        // NOTE: Content is filled in last so we don't replace parts of content
-        //       with template variable contents as template variables only
-        //       apply to the template and not the content itself
-        let template = template
-            .replace("%title%", &title)
-            .replace("%date%", &date.to_string())
-            .replace(
-                "%root_path%",
-                {
-                    let path_str = utils::path_to_uri_string(
-                        formatter
-                            .config()
-                            .to_active_page_path_to_wiki_root()
-                            .as_path(),
-                    );
-
-                    if path_str.is_empty() {
-                        String::new()
-                    } else {
-                        format!("{}/", path_str)
-                    }
-                }
-                .as_str(),
-            )
-            .replace(
-                "%wiki_path%",
-                &utils::path_to_uri_string(
-                    formatter.config().as_active_page_path_within_wiki(),
-                ),
-            )
-            .replace(
-                "%css%",
-                formatter.config().to_current_wiki().css_name.as_str(),
+            .map(|p| load(&p.to_string_lossy()))
+            .transpose()?
+            .unwrap_or_else(|| template_config.text.to_string());
+
+        // Build up the variables available to the template, where the custom
+        // variables of the page are overridden by the standard ones, which
+        // are themselves overridden by those provided at runtime
+        let metadata = formatter.take_metadata();
+        let mut variables: BTreeMap<String, HtmlTemplateValue> = metadata
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.into()))
+            .collect();
+        variables.insert(
+            "metadata".to_string(),
+            HtmlTemplateValue::map(vec![
+                ("title", HtmlTemplateValue::from(metadata.title)),
+                ("date", metadata.date.into()),
+                ("template", metadata.template.into()),
+                ("nohtml", metadata.nohtml.into()),
+                ("values", metadata.values.into()),
+            ]),
+        );
+        variables.insert("title".to_string(), title.into());
+        variables.insert("date".to_string(), date.into());
+        variables.insert("root_path".to_string(), {
+            let path_str = utils::path_to_uri_string(
+                formatter
+                    .config()
+                    .to_active_page_path_to_wiki_root()
+                    .as_path(),
+            );
+
+            if path_str.is_empty() {
+                String::new().into()
+            } else {
+                format!("{}/", path_str).into()
+            }
+        });
+        variables.insert(
+            "wiki_path".to_string(),
+            utils::path_to_uri_string(
+                formatter.config().as_active_page_path_within_wiki(),
             )
-            .replace("%encoding%", "utf-8")
-            .replace("%content%", formatter.get_content());
+            .into(),
+        );
+        variables.insert(
+            "css".to_string(),
+            formatter
+                .config()
+                .to_current_wiki()
+                .css_name
+                .as_str()
+                .into(),
+        );
+        variables.insert("encoding".to_string(), "utf-8".into());
+        variables.insert("tags".to_string(), formatter.take_tags().into());
+        variables.insert(
+            "toc".to_string(),
+            HtmlTemplateValue::List(
+                formatter
+                    .take_toc()
+                    .into_iter()
+                    .map(|entry| {
+                        HtmlTemplateValue::map(vec![
+                            ("level", HtmlTemplateValue::from(entry.level)),
+                            ("text", entry.text.into()),
+                            ("id", entry.id.into()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        );
+        variables.extend(
+            formatter
+                .config()
+                .runtime
+                .variables
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone())),
+        );
+
+        // NOTE: Content is inserted as html so it is neither escaped nor
+        //       scanned for placeholders as template variables only apply to
+        //       the template and not the content itself
+        variables.insert(
+            "content".to_string(),
+            HtmlTemplateValue::Html(formatter.get_content().to_string()),
+        );
 
-        Ok(template)
+        let renderer = HtmlTemplateRenderer::new(load)
+            .with_legacy_placeholders(template_config.legacy_placeholders);
+        let page = renderer.render(&template, &variables)?;
+
+        let mut report = HtmlReport::from_formatter(&mut formatter);
+        report.templates = templates.take();
+        Ok((page, report))
     }
 }
 
+/// Loads the text of a template within the template directory, first trying
+/// the name as-is and then with the template extension added, returning the
+/// path of the template alongside its text
+fn load_template(
+    config: &HtmlTemplateConfig,
+    name: &str,
+) -> Result<(PathBuf, String), HtmlOutputError> {
+    let path = config.dir.join(name);
+    let path = if path.is_file() || config.ext.is_empty() {
+        path
+    } else {
+        config.dir.join(format!("{}.{}", name, config.ext))
+    };
+
+    std::fs::read_to_string(&path)
+        .map(|text| (path, text))
+        .map_err(|source| HtmlOutputError::TemplateNotLoaded { source })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        HtmlOutputResult, HtmlRuntimeConfig, HtmlTemplateConfig, HtmlWikiConfig,
+        HtmlOutputResult, HtmlRuntimeConfig, HtmlTemplateConfig,
+        HtmlWikiConfig, Placeholder,
     };
     use chrono::NaiveDate;
     use std::path::PathBuf;
@@ -136,6 +278,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn to_html_with_report_should_return_anything_stripped_and_assets() {
+        let stripped = HtmlStripped::Uri {
+            element: String::from("a"),
+            uri: String::from("javascript:alert(1)"),
+        };
+        let asset = HtmlAsset {
+            source: PathBuf::from("wiki/img.png"),
+            output: PathBuf::from("html/img.png"),
+        };
+        let output = TestOutput(|f: &mut HtmlFormatter| {
+            f.add_stripped(HtmlStripped::Uri {
+                element: String::from("a"),
+                uri: String::from("javascript:alert(1)"),
+            });
+            f.add_asset(HtmlAsset {
+                source: PathBuf::from("wiki/img.png"),
+                output: PathBuf::from("html/img.png"),
+            });
+            Ok(())
+        });
+
+        let (content, report) = output
+            .to_html_string_with_report(HtmlConfig::default())
+            .unwrap();
+        assert_eq!(content, "");
+        assert_eq!(report.stripped, vec![stripped.clone()]);
+        assert_eq!(report.assets, vec![asset.clone()]);
+
+        let (_, report) = output
+            .to_html_page_with_report(HtmlConfig::default())
+            .unwrap();
+        assert_eq!(report.stripped, vec![stripped]);
+        assert_eq!(report.assets, vec![asset]);
+    }
+
     #[test]
     fn to_html_string_should_produce_a_string_representing_only_the_html_of_the_output(
     ) {
@@ -241,6 +419,7 @@ mod tests {
             runtime: HtmlRuntimeConfig {
                 wiki_index: Some(0),
                 page: ["some", "path", "to", "a", "file.wiki"].iter().collect(),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -257,6 +436,7 @@ mod tests {
             runtime: HtmlRuntimeConfig {
                 wiki_index: Some(0),
                 page: ["some", "path", "to", "file.wiki"].iter().collect(),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -273,6 +453,7 @@ mod tests {
             runtime: HtmlRuntimeConfig {
                 wiki_index: Some(0),
                 page: ["some", "path", "file.wiki"].iter().collect(),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -294,6 +475,7 @@ mod tests {
             runtime: HtmlRuntimeConfig {
                 wiki_index: Some(0),
                 page: ["some", "path", "to", "a", "file.wiki"].iter().collect(),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -365,4 +547,147 @@ mod tests {
         let result = output.to_html_page(config).unwrap();
         assert_eq!(result, "<html>some output content</html>");
     }
+
+    #[test]
+    fn to_html_page_should_support_template_syntax_with_escaped_variables() {
+        let output = TestOutput(|f| {
+            f.set_title("<Title>");
+            f.metadata_mut().push("author", "Me & You");
+            f.add_tag("a");
+            f.add_tag("b");
+            Ok(())
+        });
+        let template = HtmlTemplateConfig {
+            legacy_placeholders: false,
+            ..HtmlTemplateConfig::from_text(
+                "{{ title }}|{{ author }}|{% for tag in tags %}\
+                 {{ tag }}{% if not loop.last %},{% endif %}{% endfor %}",
+            )
+        };
+        let config = HtmlConfig {
+            template,
+            ..Default::default()
+        };
+
+        let result = output.to_html_page(config).unwrap();
+        assert_eq!(result, "&lt;Title&gt;|Me &amp; You|a,b");
+    }
+
+    #[test]
+    fn to_html_page_should_keep_template_syntax_as_is_in_legacy_mode() {
+        let output = TestOutput(_text("content"));
+        let config = HtmlConfig {
+            template: HtmlTemplateConfig::from_text(
+                "<script>var a = {{}}; {% b %}</script>%content%",
+            ),
+            ..Default::default()
+        };
+
+        let result = output.to_html_page(config).unwrap();
+        assert_eq!(result, "<script>var a = {{}}; {% b %}</script>content");
+    }
+
+    #[test]
+    fn to_html_page_should_not_replace_placeholders_if_legacy_mode_disabled() {
+        let output = TestOutput(_text("content"));
+        let template = HtmlTemplateConfig {
+            legacy_placeholders: false,
+            ..HtmlTemplateConfig::from_text("%content% {{ content }}")
+        };
+        let config = HtmlConfig {
+            template,
+            ..Default::default()
+        };
+
+        let result = output.to_html_page(config).unwrap();
+        assert_eq!(result, "%content% content");
+    }
+
+    #[test]
+    fn to_html_page_should_prefer_runtime_variables_over_page_variables() {
+        let output = TestOutput(|f| {
+            f.metadata_mut().push("backlinks", "page");
+            Ok(())
+        });
+        let template = HtmlTemplateConfig {
+            legacy_placeholders: false,
+            ..HtmlTemplateConfig::from_text(
+                "{% for x in backlinks %}[{{ x }}]{% endfor %}",
+            )
+        };
+        let mut config = HtmlConfig {
+            template,
+            ..Default::default()
+        };
+        config
+            .runtime
+            .variables
+            .insert("backlinks".to_string(), vec!["a", "b"].into());
+
+        let result = output.to_html_page(config).unwrap();
+        assert_eq!(result, "[a][b]");
+    }
+
+    #[test]
+    fn to_html_page_should_load_templates_from_template_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("base.tpl"),
+            "<title>{% block title %}{% endblock %}</title>{{ content }}",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("custom.tpl"),
+            "{% extends 'base' %}{% block title %}{{ title }}{% endblock %}",
+        )
+        .unwrap();
+
+        let output = TestOutput(|f| {
+            use std::fmt::Write;
+            f.set_title("Title");
+            f.set_template("custom");
+            write!(f, "<p>content</p>")?;
+            Ok(())
+        });
+        let config = HtmlConfig {
+            template: HtmlTemplateConfig {
+                dir: dir.path().to_path_buf(),
+                legacy_placeholders: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (result, report) = output.to_html_page_with_report(config).unwrap();
+        assert_eq!(result, "<title>Title</title><p>content</p>");
+        assert_eq!(
+            report.templates,
+            vec![dir.path().join("custom.tpl"), dir.path().join("base.tpl")]
+        );
+    }
+
+    #[test]
+    fn to_html_page_should_expose_page_metadata_to_templates() {
+        let output = TestOutput(|f| {
+            f.metadata_mut().insert(&Placeholder::NoHtml);
+            f.metadata_mut().push("keywords", "a b");
+            f.metadata_mut().push("keywords", "c");
+            Ok(())
+        });
+        let template = HtmlTemplateConfig {
+            legacy_placeholders: false,
+            ..HtmlTemplateConfig::from_text(
+                "{{ metadata.nohtml }}|{{ keywords }}|\
+                 {% for x in metadata.values.keywords %}[{{ x }}]{% endfor %}|\
+                 {{ metadata.values.keywords | join(' ') | split | join(',') }}",
+            )
+        };
+        let config = HtmlConfig {
+            template,
+            ..Default::default()
+        };
+
+        let result = output.to_html_page(config).unwrap();
+        assert_eq!(result, "true|c|[a b][c]|a,b,c");
+    }
 }
@@ -1,5 +1,6 @@
-use super::LinkResolutionError;
+use super::{HtmlTemplateError, LinkResolutionError};
 use derive_more::{Display, Error};
+use std::path::PathBuf;
 use uriparse::{PathError, RelativeReferenceError, URIReferenceError};
 
 pub type HtmlOutputResult = Result<(), HtmlOutputError>;
@@ -31,17 +32,55 @@ pub enum HtmlOutputError {
         source: LinkResolutionError,
     },
 
+    #[display(fmt = "Theme {} is missing", _0)]
     ThemeMissing(#[error(not(source))] String),
 
     MissingWikiAtIndex(#[error(not(source))] usize),
 
     MissingWikiWithName(#[error(not(source))] String),
 
+    #[display(fmt = "Transclusion of {:?} forms a cycle", _0)]
+    TransclusionCycle(#[error(not(source))] PathBuf),
+
+    #[display(
+        fmt = "Transclusion of {:?} exceeds max depth of {}",
+        path,
+        max_depth
+    )]
+    TransclusionTooDeep {
+        path: PathBuf,
+        max_depth: usize,
+    },
+
+    #[display(fmt = "Transclusion of {:?} has no section {}", path, anchor)]
+    TransclusionSectionMissing {
+        path: PathBuf,
+        anchor: String,
+    },
+
+    #[display(fmt = "Transclusion of {:?} failed to load", path)]
+    TransclusionNotLoaded {
+        path: PathBuf,
+        #[error(source)]
+        source: std::io::Error,
+    },
+
+    #[display(fmt = "Transclusion of {:?} failed to parse: {}", path, message)]
+    TransclusionNotParsed {
+        path: PathBuf,
+        message: String,
+    },
+
     TemplateNotLoaded {
         #[error(source)]
         source: std::io::Error,
     },
 
+    TemplateFailed {
+        #[error(source)]
+        source: HtmlTemplateError,
+    },
+
     Fmt {
         #[error(source)]
         source: std::fmt::Error,
@@ -83,3 +122,9 @@ impl From<syntect::LoadingError> for HtmlOutputError {
         Self::SyntaxOrThemeNotLoaded { source }
     }
 }
+
+impl From<HtmlTemplateError> for HtmlOutputError {
+    fn from(source: HtmlTemplateError) -> Self {
+        Self::TemplateFailed { source }
+    }
+}
@@ -1,4 +1,8 @@
-use super::{HtmlConfig, HtmlOutputError, OutputFormatter};
+use super::{
+    HtmlAsset, HtmlConfig, HtmlHookElement, HtmlHookOutcome, HtmlOutputError,
+    HtmlOutputResult, HtmlStripped, Output, OutputFormatter,
+};
+use crate::PageMetadata;
 use chrono::NaiveDate;
 use std::{
     borrow::Cow,
@@ -29,8 +33,46 @@ pub struct HtmlFormatter {
     /// Contains the template to be used for the page
     template: Option<PathBuf>,
 
+    /// Contains the metadata declared by the placeholders of the page
+    metadata: PageMetadata,
+
+    /// Contains the tags found within the page, in order, without duplicates
+    tags: Vec<String>,
+
+    /// Contains the headers found within the page for a table of contents
+    toc: Vec<HtmlTocEntry>,
+
+    /// Contains anything removed from the output when sanitizing, in order
+    stripped: Vec<HtmlStripped>,
+
+    /// Contains the assets referenced by the page, in order, without
+    /// duplicates
+    assets: Vec<HtmlAsset>,
+
+    /// Contains the paths of the wiki pages being transcluded, from the
+    /// outermost to the innermost, to detect cycles
+    transclusions: Vec<PathBuf>,
+
     /// Contains the content to be injected into a template
     content: String,
+
+    /// If true, the next element to run its hook will skip it and use its
+    /// default output instead
+    skip_next_hook: bool,
+}
+
+/// Represents a header written to the page that can be used to build a
+/// table of contents within a template
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtmlTocEntry {
+    /// Level of the header, starting at 1
+    pub level: usize,
+
+    /// Text of the header without any decorations
+    pub text: String,
+
+    /// Id of the element that can be linked to in order to reach the header
+    pub id: String,
 }
 
 impl OutputFormatter for HtmlFormatter {
@@ -52,10 +94,49 @@ impl HtmlFormatter {
             title: None,
             date: None,
             template: None,
+            metadata: PageMetadata::default(),
+            tags: Vec::new(),
+            toc: Vec::new(),
+            stripped: Vec::new(),
+            assets: Vec::new(),
+            transclusions: Vec::new(),
             content: String::new(),
+            skip_next_hook: false,
         }
     }
 
+    /// Runs the hook registered for the element, if any, returning true if
+    /// the hook rendered the element such that the default output should be
+    /// skipped
+    pub fn run_hook(
+        &mut self,
+        element: HtmlHookElement<'_>,
+    ) -> Result<bool, HtmlOutputError> {
+        if std::mem::take(&mut self.skip_next_hook)
+            || self.config.hooks.is_empty()
+        {
+            return Ok(false);
+        }
+
+        match self.config.hooks.get(&element) {
+            Some(hook) => Ok(hook(element, self)? == HtmlHookOutcome::Rendered),
+            None => Ok(false),
+        }
+    }
+
+    /// Writes the default output of the element, bypassing any hook
+    /// registered for it while still running hooks of its children, which
+    /// allows a hook to wrap the default output
+    pub fn render_default(
+        &mut self,
+        element: HtmlHookElement<'_>,
+    ) -> HtmlOutputResult {
+        self.skip_next_hook = true;
+        let result = element.fmt(self);
+        self.skip_next_hook = false;
+        result
+    }
+
     /// Represents the config contained within the formatter
     #[inline]
     pub fn config(&self) -> &HtmlConfig {
@@ -144,6 +225,100 @@ impl HtmlFormatter {
         self.template.take()
     }
 
+    pub fn metadata(&self) -> &PageMetadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut PageMetadata {
+        &mut self.metadata
+    }
+
+    pub fn take_metadata(&mut self) -> PageMetadata {
+        std::mem::take(&mut self.metadata)
+    }
+
+    /// Adds a tag to those found within the page, ignoring it if it has
+    /// already been added
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.tags.iter().any(|x| x == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn take_tags(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.tags)
+    }
+
+    pub fn add_toc_entry(&mut self, entry: HtmlTocEntry) {
+        self.toc.push(entry);
+    }
+
+    pub fn get_toc(&self) -> &[HtmlTocEntry] {
+        &self.toc
+    }
+
+    pub fn take_toc(&mut self) -> Vec<HtmlTocEntry> {
+        std::mem::take(&mut self.toc)
+    }
+
+    /// Records something that was removed from the output when sanitizing
+    pub fn add_stripped(&mut self, stripped: HtmlStripped) {
+        self.stripped.push(stripped);
+    }
+
+    pub fn get_stripped(&self) -> &[HtmlStripped] {
+        &self.stripped
+    }
+
+    pub fn take_stripped(&mut self) -> Vec<HtmlStripped> {
+        std::mem::take(&mut self.stripped)
+    }
+
+    /// Records an asset referenced by the page, skipping assets that are
+    /// already recorded
+    pub fn add_asset(&mut self, asset: HtmlAsset) {
+        if !self.assets.contains(&asset) {
+            self.assets.push(asset);
+        }
+    }
+
+    pub fn get_assets(&self) -> &[HtmlAsset] {
+        &self.assets
+    }
+
+    pub fn take_assets(&mut self) -> Vec<HtmlAsset> {
+        std::mem::take(&mut self.assets)
+    }
+
+    /// Marks the start of transcluding the wiki page at the given path
+    pub fn push_transclusion(&mut self, path: impl Into<PathBuf>) {
+        self.transclusions.push(path.into());
+    }
+
+    /// Marks the end of transcluding the innermost wiki page
+    pub fn pop_transclusion(&mut self) -> Option<PathBuf> {
+        self.transclusions.pop()
+    }
+
+    /// Returns the paths of the wiki pages being transcluded, from the
+    /// outermost to the innermost
+    pub fn get_transclusions(&self) -> &[PathBuf] {
+        &self.transclusions
+    }
+
+    /// Returns the path of the page whose content is being written, which is
+    /// the innermost page being transcluded or otherwise the active page
+    pub fn current_page(&self) -> &Path {
+        self.transclusions
+            .last()
+            .map(PathBuf::as_path)
+            .unwrap_or_else(|| self.config.active_page())
+    }
+
     pub fn get_content(&self) -> &str {
         self.content.as_str()
     }
@@ -0,0 +1,354 @@
+use super::{HtmlFormatter, HtmlOutputError, HtmlOutputResult};
+use crate::lang::{elements::*, output::Output};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// Represents a function that renders an element in place of the default
+/// html output, returning whether it rendered the element or the default
+/// output should be used instead
+pub type HtmlHook = dyn Fn(
+        HtmlHookElement<'_>,
+        &mut HtmlFormatter,
+    ) -> Result<HtmlHookOutcome, HtmlOutputError>
+    + Send
+    + Sync;
+
+/// Represents the result of running a hook
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HtmlHookOutcome {
+    /// Hook has rendered the element itself
+    Rendered,
+
+    /// Hook has not rendered the element, so the default output is used
+    Default,
+}
+
+/// Represents the kinds of elements that can have hooks
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HtmlHookKind {
+    Blockquote,
+    CodeBlock,
+    DefinitionList,
+    Divider,
+    Header,
+    List,
+    ListItem,
+    MathBlock,
+    Paragraph,
+    Table,
+    Text,
+    DecoratedText,
+    Keyword,
+    Link,
+    Tags,
+    CodeInline,
+    MathInline,
+    Comment,
+}
+
+/// Represents an element being rendered that is provided to a hook
+#[derive(Copy, Clone, Debug)]
+pub enum HtmlHookElement<'a> {
+    Blockquote(&'a Blockquote<'a>),
+    CodeBlock(&'a CodeBlock<'a>),
+    DefinitionList(&'a DefinitionList<'a>),
+    Divider(&'a Divider),
+    Header(&'a Header<'a>),
+    List(&'a List<'a>),
+    ListItem(&'a ListItem<'a>),
+    MathBlock(&'a MathBlock<'a>),
+    Paragraph(&'a Paragraph<'a>),
+    Table(&'a Table<'a>),
+    Text(&'a Text<'a>),
+    DecoratedText(&'a DecoratedText<'a>),
+    Keyword(&'a Keyword),
+    Link(&'a Link<'a>),
+    Tags(&'a Tags<'a>),
+    CodeInline(&'a CodeInline<'a>),
+    MathInline(&'a MathInline<'a>),
+    Comment(&'a Comment<'a>),
+}
+
+impl<'a> HtmlHookElement<'a> {
+    /// Returns the kind of the element
+    pub fn kind(&self) -> HtmlHookKind {
+        match self {
+            Self::Blockquote(_) => HtmlHookKind::Blockquote,
+            Self::CodeBlock(_) => HtmlHookKind::CodeBlock,
+            Self::DefinitionList(_) => HtmlHookKind::DefinitionList,
+            Self::Divider(_) => HtmlHookKind::Divider,
+            Self::Header(_) => HtmlHookKind::Header,
+            Self::List(_) => HtmlHookKind::List,
+            Self::ListItem(_) => HtmlHookKind::ListItem,
+            Self::MathBlock(_) => HtmlHookKind::MathBlock,
+            Self::Paragraph(_) => HtmlHookKind::Paragraph,
+            Self::Table(_) => HtmlHookKind::Table,
+            Self::Text(_) => HtmlHookKind::Text,
+            Self::DecoratedText(_) => HtmlHookKind::DecoratedText,
+            Self::Keyword(_) => HtmlHookKind::Keyword,
+            Self::Link(_) => HtmlHookKind::Link,
+            Self::Tags(_) => HtmlHookKind::Tags,
+            Self::CodeInline(_) => HtmlHookKind::CodeInline,
+            Self::MathInline(_) => HtmlHookKind::MathInline,
+            Self::Comment(_) => HtmlHookKind::Comment,
+        }
+    }
+}
+
+impl<'a> Output<HtmlFormatter> for HtmlHookElement<'a> {
+    fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        match self {
+            Self::Blockquote(x) => x.fmt(f),
+            Self::CodeBlock(x) => x.fmt(f),
+            Self::DefinitionList(x) => x.fmt(f),
+            Self::Divider(x) => x.fmt(f),
+            Self::Header(x) => x.fmt(f),
+            Self::List(x) => x.fmt(f),
+            Self::ListItem(x) => x.fmt(f),
+            Self::MathBlock(x) => x.fmt(f),
+            Self::Paragraph(x) => x.fmt(f),
+            Self::Table(x) => x.fmt(f),
+            Self::Text(x) => x.fmt(f),
+            Self::DecoratedText(x) => x.fmt(f),
+            Self::Keyword(x) => x.fmt(f),
+            Self::Link(x) => x.fmt(f),
+            Self::Tags(x) => x.fmt(f),
+            Self::CodeInline(x) => x.fmt(f),
+            Self::MathInline(x) => x.fmt(f),
+            Self::Comment(x) => x.fmt(f),
+        }
+    }
+}
+
+/// Represents a registry of hooks that override the html output of elements,
+/// either by kind or by the language of code blocks
+///
+/// A hook can render the element fully, return [`HtmlHookOutcome::Default`]
+/// to use the default output instead, or wrap the default output by calling
+/// [`HtmlFormatter::render_default`]
+///
+/// ## Examples
+///
+/// ```
+/// use std::fmt::Write;
+/// use vimwiki::*;
+///
+/// let mut config = HtmlConfig::default();
+/// config
+///     .hooks
+///     .register(HtmlHookKind::Divider, |_, f| {
+///         f.write_str("<hr class=\"divider\" />")?;
+///         Ok(HtmlHookOutcome::Rendered)
+///     })
+///     .register_code_language("mermaid", |code, f| {
+///         f.write_str("<div class=\"mermaid\">")?;
+///         f.write_str(&code.lines.join("\n"))?;
+///         f.write_str("</div>")?;
+///         Ok(HtmlHookOutcome::Rendered)
+///     });
+///
+/// let page: Page = Language::from_vimwiki_str("----\n{{{mermaid\na --> b\n}}}\n")
+///     .parse()
+///     .unwrap();
+/// assert_eq!(
+///     page.to_html_string(config).unwrap(),
+///     "<hr class=\"divider\" />\n<div class=\"mermaid\">a --> b</div>\n",
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct HtmlHooks {
+    by_kind: HashMap<HtmlHookKind, Arc<HtmlHook>>,
+    by_code_language: HashMap<String, Arc<HtmlHook>>,
+}
+
+impl HtmlHooks {
+    /// Returns true if no hooks have been registered
+    pub fn is_empty(&self) -> bool {
+        self.by_kind.is_empty() && self.by_code_language.is_empty()
+    }
+
+    /// Registers a hook for all elements of the given kind, replacing any
+    /// existing hook for that kind
+    pub fn register<F>(&mut self, kind: HtmlHookKind, hook: F) -> &mut Self
+    where
+        F: Fn(
+                HtmlHookElement<'_>,
+                &mut HtmlFormatter,
+            ) -> Result<HtmlHookOutcome, HtmlOutputError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.by_kind.insert(kind, Arc::new(hook));
+        self
+    }
+
+    /// Registers a hook for code blocks with the given language, replacing
+    /// any existing hook for that language; these take precedence over a
+    /// hook registered for all code blocks
+    pub fn register_code_language<F>(
+        &mut self,
+        language: impl Into<String>,
+        hook: F,
+    ) -> &mut Self
+    where
+        F: Fn(
+                &CodeBlock<'_>,
+                &mut HtmlFormatter,
+            ) -> Result<HtmlHookOutcome, HtmlOutputError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.by_code_language.insert(
+            language.into(),
+            Arc::new(move |element, f| match element {
+                HtmlHookElement::CodeBlock(x) => hook(x, f),
+                _ => Ok(HtmlHookOutcome::Default),
+            }),
+        );
+        self
+    }
+
+    /// Removes the hook registered for the given kind, if any
+    pub fn unregister(&mut self, kind: HtmlHookKind) -> &mut Self {
+        self.by_kind.remove(&kind);
+        self
+    }
+
+    /// Removes the hook registered for code blocks with the given language,
+    /// if any
+    pub fn unregister_code_language(&mut self, language: &str) -> &mut Self {
+        self.by_code_language.remove(language);
+        self
+    }
+
+    /// Returns the hook to use for the element, if any
+    pub fn get(&self, element: &HtmlHookElement<'_>) -> Option<Arc<HtmlHook>> {
+        let hook = match element {
+            HtmlHookElement::CodeBlock(x) => x
+                .language
+                .as_deref()
+                .and_then(|lang| self.by_code_language.get(lang)),
+            _ => None,
+        };
+
+        hook.or_else(|| self.by_kind.get(&element.kind()))
+            .map(Arc::clone)
+    }
+}
+
+impl fmt::Debug for HtmlHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HtmlHooks")
+            .field("by_kind", &self.by_kind.keys().collect::<Vec<_>>())
+            .field(
+                "by_code_language",
+                &self.by_code_language.keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HtmlConfig, Language, ToHtmlString};
+    use std::fmt::Write;
+
+    fn to_html(text: &str, hooks: HtmlHooks) -> String {
+        let page: Page = Language::from_vimwiki_str(text).parse().unwrap();
+        page.to_html_string(HtmlConfig {
+            hooks,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn hook_should_replace_default_output_when_rendered() {
+        let mut hooks = HtmlHooks::default();
+        hooks.register(HtmlHookKind::CodeInline, |element, f| {
+            if let HtmlHookElement::CodeInline(x) = element {
+                write!(f, "<kbd>{}</kbd>", x.as_str())?;
+            }
+            Ok(HtmlHookOutcome::Rendered)
+        });
+
+        assert_eq!(
+            to_html("some `code`", hooks),
+            "<p>some <kbd>code</kbd></p>\n"
+        );
+    }
+
+    #[test]
+    fn hook_should_fall_back_to_default_output() {
+        let mut hooks = HtmlHooks::default();
+        hooks.register(HtmlHookKind::CodeInline, |element, f| match element {
+            HtmlHookElement::CodeInline(x) if x.as_str() == "key" => {
+                write!(f, "<kbd>key</kbd>")?;
+                Ok(HtmlHookOutcome::Rendered)
+            }
+            _ => Ok(HtmlHookOutcome::Default),
+        });
+
+        assert_eq!(
+            to_html("`key` and `code`", hooks),
+            "<p><kbd>key</kbd> and <code>code</code></p>\n"
+        );
+    }
+
+    #[test]
+    fn hook_should_be_able_to_wrap_default_output_with_hooks_of_children() {
+        let mut hooks = HtmlHooks::default();
+        hooks
+            .register(HtmlHookKind::Paragraph, |element, f| {
+                write!(f, "<div class=\"wrapper\">")?;
+                f.render_default(element)?;
+                write!(f, "</div>")?;
+                Ok(HtmlHookOutcome::Rendered)
+            })
+            .register(HtmlHookKind::Text, |element, f| {
+                if let HtmlHookElement::Text(x) = element {
+                    write!(f, "{}", x.as_str().to_uppercase())?;
+                }
+                Ok(HtmlHookOutcome::Rendered)
+            });
+
+        assert_eq!(
+            to_html("some text", hooks),
+            "<div class=\"wrapper\"><p>SOME TEXT</p></div>\n"
+        );
+    }
+
+    #[test]
+    fn code_language_hook_should_take_precedence_over_kind_hook() {
+        let mut hooks = HtmlHooks::default();
+        hooks
+            .register(HtmlHookKind::CodeBlock, |_, f| {
+                write!(f, "<pre>any</pre>")?;
+                Ok(HtmlHookOutcome::Rendered)
+            })
+            .register_code_language("mermaid", |code, f| {
+                write!(
+                    f,
+                    "<div class=\"mermaid\">{}</div>",
+                    code.lines.join("\n")
+                )?;
+                Ok(HtmlHookOutcome::Rendered)
+            });
+
+        assert_eq!(
+            to_html(
+                "{{{mermaid\na --> b\n}}}\n{{{rust\nfn\n}}}\n",
+                hooks.clone()
+            ),
+            "<div class=\"mermaid\">a --> b</div>\n<pre>any</pre>\n"
+        );
+
+        hooks.unregister(HtmlHookKind::CodeBlock);
+        assert_eq!(
+            to_html("{{{rust\nfn\n}}}\n", hooks),
+            "<pre><code class=\"rust\">fn</code></pre>\n"
+        );
+    }
+}
@@ -0,0 +1,976 @@
+use derive_more::{Display, Error};
+use voca_rs::escape;
+
+/// Represents an expression that could not be converted into MathML, either
+/// because it is malformed or uses TeX that is not supported
+#[derive(Clone, Debug, Display, Error, PartialEq, Eq)]
+#[display(fmt = "Unsupported math: {}", message)]
+pub struct MathConversionError {
+    #[error(not(source))]
+    pub message: String,
+}
+
+impl MathConversionError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+type MathResult<T> = Result<T, MathConversionError>;
+
+/// Maximum depth of groups, commands and environments nested within one
+/// another, guarding against overflowing the stack
+const MAX_DEPTH: usize = 100;
+
+/// Converts TeX into MathML, supporting the commonly-used subset of TeX such
+/// as fractions, roots, scripts, greek letters, operators, fonts, accents,
+/// `\left` and `\right` delimiters, and environments like `align`, `cases`
+/// and the various matrices
+///
+/// When an environment is provided, the TeX is treated as the body of that
+/// environment as if surrounded by `\begin{environment}` and
+/// `\end{environment}`
+pub fn tex_to_mathml(
+    tex: &str,
+    environment: Option<&str>,
+    display: bool,
+) -> MathResult<String> {
+    let mut parser = MathParser {
+        chars: tex.chars().collect(),
+        pos: 0,
+        depth: 0,
+        display,
+    };
+
+    let content = match environment {
+        Some(env) => parser.environment(env)?,
+        None => parser.row(&[])?,
+    };
+
+    match parser.stop() {
+        None => {}
+        Some(stop) => {
+            return Err(MathConversionError::new(format!(
+                "Unexpected {}",
+                stop
+            )))
+        }
+    }
+
+    Ok(format!(
+        r#"<math xmlns="http://www.w3.org/1998/Math/MathML"{}>{}</math>"#,
+        if display { r#" display="block""# } else { "" },
+        content,
+    ))
+}
+
+/// Represents something that ends a sequence of math
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq)]
+enum Stop {
+    #[display(fmt = "}}")]
+    CloseGroup,
+    #[display(fmt = "&")]
+    Column,
+    #[display(fmt = r"\\")]
+    Row,
+    #[display(fmt = r"\right")]
+    Right,
+    #[display(fmt = r"\end")]
+    End,
+}
+
+struct MathParser {
+    chars: Vec<char>,
+    pos: usize,
+
+    /// Total atoms currently being parsed, each nested within the last
+    depth: usize,
+
+    display: bool,
+}
+
+impl MathParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek_command(&self) -> Option<String> {
+        if self.peek() != Some('\\') {
+            return None;
+        }
+
+        let rest = &self.chars[self.pos + 1..];
+        let len = rest.iter().take_while(|c| c.is_ascii_alphabetic()).count();
+        match len {
+            0 => rest.first().map(|c| c.to_string()),
+            _ => Some(rest[..len].iter().collect()),
+        }
+    }
+
+    fn command(&mut self) -> Option<String> {
+        let name = self.peek_command()?;
+        self.pos += 1 + name.chars().count();
+        Some(name)
+    }
+
+    /// Returns what would end the current sequence of math, if anything
+    fn stop(&mut self) -> Option<Stop> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '}' => Some(Stop::CloseGroup),
+            '&' => Some(Stop::Column),
+            '\\' => match self.peek_command()?.as_str() {
+                "\\" => Some(Stop::Row),
+                "right" => Some(Stop::Right),
+                "end" => Some(Stop::End),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> MathResult<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(MathConversionError::new(format!("Expected {}", c)))
+        }
+    }
+
+    /// Parses a sequence of math until reaching the end of input or a stop,
+    /// also stopping at any of the given characters
+    fn row(&mut self, until: &[char]) -> MathResult<String> {
+        let mut nodes = Vec::new();
+
+        loop {
+            if self.stop().is_some() {
+                break;
+            }
+            match self.peek() {
+                None => break,
+                Some(c) if until.contains(&c) => break,
+                _ => {}
+            }
+
+            let node = self.scripted()?;
+            nodes.push(node);
+        }
+
+        Ok(match nodes.len() {
+            1 => nodes.remove(0),
+            _ => format!("<mrow>{}</mrow>", nodes.concat()),
+        })
+    }
+
+    /// Parses an atom along with any subscript and superscript attached
+    fn scripted(&mut self) -> MathResult<String> {
+        let (base, has_limits) = match self.peek() {
+            Some('^') | Some('_') => ("<mrow></mrow>".to_string(), false),
+            _ => self.atom()?,
+        };
+
+        let mut sub = None;
+        let mut sup = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.pos += 1;
+                    sub = Some(self.argument()?);
+                }
+                Some('^') if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some(self.argument()?);
+                }
+                Some('\'') if sup.is_none() => {
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        self.pos += 1;
+                        primes.push('′');
+                    }
+                    sup = Some(format!("<mo>{}</mo>", primes));
+                }
+                Some('_') | Some('^') => {
+                    return Err(MathConversionError::new("Double script"))
+                }
+                _ => break,
+            }
+        }
+
+        // Limits go above and below operators like sums when displayed as a
+        // block, otherwise they are placed to the side like other scripts
+        let (under, over, underover) = if has_limits && self.display {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+
+        Ok(match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", under, base, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", over, base, sup),
+            (Some(sub), Some(sup)) => {
+                format!("<{0}>{1}{2}{3}</{0}>", underover, base, sub, sup)
+            }
+        })
+    }
+
+    /// Parses a single argument such as `{x+1}`, `x` or `\alpha`
+    fn argument(&mut self) -> MathResult<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(MathConversionError::new("Missing argument")),
+            Some('{') => Ok(self.atom()?.0),
+            Some(c) if c.is_ascii_digit() => {
+                self.pos += 1;
+                Ok(format!("<mn>{}</mn>", c))
+            }
+            _ => Ok(self.atom()?.0),
+        }
+    }
+
+    /// Parses the text within braces without interpreting it as math
+    fn text_argument(&mut self) -> MathResult<String> {
+        self.expect('{')?;
+        let mut depth = 0;
+        let mut text = String::new();
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| MathConversionError::new("Expected }"))?;
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        Ok(text)
+    }
+
+    /// Parses an atom, returning it alongside whether or not it is an
+    /// operator whose scripts are limits such as `\sum`, failing if nested
+    /// too deeply within other atoms
+    fn atom(&mut self) -> MathResult<(String, bool)> {
+        if self.depth >= MAX_DEPTH {
+            return Err(MathConversionError::new(format!(
+                "Nested deeper than {}",
+                MAX_DEPTH
+            )));
+        }
+
+        self.depth += 1;
+        let result = self.atom_body();
+        self.depth -= 1;
+        result
+    }
+
+    fn atom_body(&mut self) -> MathResult<(String, bool)> {
+        self.skip_whitespace();
+        let c = self
+            .peek()
+            .ok_or_else(|| MathConversionError::new("Unexpected end"))?;
+
+        if c == '\\' {
+            return self.command_atom();
+        }
+
+        self.pos += 1;
+        let node = match c {
+            '{' => {
+                let row = self.row(&[])?;
+                self.expect('}')?;
+                row
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = c.to_string();
+                while let Some(c) = self.peek() {
+                    let is_decimal = c == '.'
+                        && matches!(
+                            self.chars.get(self.pos + 1),
+                            Some(x) if x.is_ascii_digit()
+                        );
+                    if c.is_ascii_digit() || is_decimal {
+                        number.push(c);
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+
+                if number == "." {
+                    "<mo>.</mo>".to_string()
+                } else {
+                    format!("<mn>{}</mn>", number)
+                }
+            }
+            c if c.is_alphabetic() => format!("<mi>{}</mi>", c),
+            '-' => "<mo>\u{2212}</mo>".to_string(),
+            '*' => "<mo>\u{2217}</mo>".to_string(),
+            '~' => r#"<mspace width="0.333em"></mspace>"#.to_string(),
+            '(' | ')' | '[' | ']' | '|' => {
+                format!(r#"<mo stretchy="false">{}</mo>"#, c)
+            }
+            '+' | '=' | '<' | '>' | ',' | ';' | ':' | '!' | '/' | '?' | '@' => {
+                format!("<mo>{}</mo>", escape::escape_html(&c.to_string()))
+            }
+            '#' | '$' | '%' | '^' | '_' | '&' | '}' => {
+                return Err(MathConversionError::new(format!(
+                    "Unexpected {}",
+                    c
+                )))
+            }
+            c => format!("<mo>{}</mo>", escape::escape_html(&c.to_string())),
+        };
+
+        Ok((node, false))
+    }
+
+    fn command_atom(&mut self) -> MathResult<(String, bool)> {
+        let name = self
+            .command()
+            .ok_or_else(|| MathConversionError::new("Missing command"))?;
+
+        if let Some(c) = symbol_identifier(&name) {
+            return Ok((format!("<mi>{}</mi>", c), false));
+        }
+        if let Some(c) = symbol_operator(&name) {
+            return Ok((format!("<mo>{}</mo>", escape::escape_html(c)), false));
+        }
+        if let Some(c) = large_operator(&name) {
+            let has_limits =
+                !matches!(name.as_str(), "int" | "iint" | "iiint" | "oint");
+            return Ok((
+                format!(r#"<mo largeop="true">{}</mo>"#, c),
+                has_limits,
+            ));
+        }
+        if FUNCTIONS.contains(&name.as_str()) {
+            return Ok((format!("<mi>{}</mi>", name), false));
+        }
+        if LIMIT_FUNCTIONS.contains(&name.as_str()) {
+            return Ok((format!("<mo>{}</mo>", name), true));
+        }
+        if let Some(width) = space_width(&name) {
+            return Ok((
+                format!(r#"<mspace width="{}"></mspace>"#, width),
+                false,
+            ));
+        }
+
+        let node = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let num = self.argument()?;
+                let den = self.argument()?;
+                format!("<mfrac>{}{}</mfrac>", num, den)
+            }
+            "binom" | "dbinom" | "tbinom" => {
+                let n = self.argument()?;
+                let k = self.argument()?;
+                format!(
+                    r#"<mrow><mo>(</mo><mfrac linethickness="0">{}{}</mfrac><mo>)</mo></mrow>"#,
+                    n, k
+                )
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                if self.peek() == Some('[') {
+                    self.pos += 1;
+                    let index = self.row(&[']'])?;
+                    self.expect(']')?;
+                    let base = self.argument()?;
+                    format!("<mroot>{}{}</mroot>", base, index)
+                } else {
+                    format!("<msqrt>{}</msqrt>", self.argument()?)
+                }
+            }
+            "text" | "textrm" | "textnormal" | "mbox" | "textit" | "textbf" => {
+                let text = self.text_argument()?;
+                let variant = match name.as_str() {
+                    "textit" => r#" mathvariant="italic""#,
+                    "textbf" => r#" mathvariant="bold""#,
+                    _ => "",
+                };
+                format!(
+                    "<mtext{}>{}</mtext>",
+                    variant,
+                    escape::escape_html(&text)
+                )
+            }
+            "operatorname" => {
+                let text = self.text_argument()?;
+                format!("<mi>{}</mi>", escape::escape_html(text.trim()))
+            }
+            "mathrm" | "mathbf" | "mathit" | "mathbb" | "mathcal"
+            | "mathfrak" | "mathsf" | "mathtt" | "boldsymbol" => {
+                let variant = match name.as_str() {
+                    "mathrm" => "normal",
+                    "mathbf" => "bold",
+                    "mathit" => "italic",
+                    "mathbb" => "double-struck",
+                    "mathcal" => "script",
+                    "mathfrak" => "fraktur",
+                    "mathsf" => "sans-serif",
+                    "mathtt" => "monospace",
+                    _ => "bold-italic",
+                };
+                format!(
+                    r#"<mstyle mathvariant="{}">{}</mstyle>"#,
+                    variant,
+                    self.argument()?
+                )
+            }
+            "hat" | "widehat" | "bar" | "overline" | "vec" | "dot" | "ddot"
+            | "tilde" | "widetilde" | "check" | "breve" | "acute" | "grave"
+            | "overrightarrow" => {
+                let accent = match name.as_str() {
+                    "hat" | "widehat" => "^",
+                    "bar" | "overline" => "\u{AF}",
+                    "vec" | "overrightarrow" => "\u{2192}",
+                    "dot" => "\u{2D9}",
+                    "ddot" => "\u{A8}",
+                    "tilde" | "widetilde" => "~",
+                    "check" => "\u{2C7}",
+                    "breve" => "\u{2D8}",
+                    "acute" => "\u{B4}",
+                    _ => "`",
+                };
+                format!(
+                    r#"<mover accent="true">{}<mo>{}</mo></mover>"#,
+                    self.argument()?,
+                    accent
+                )
+            }
+            "underline" => format!(
+                r#"<munder accentunder="true">{}<mo>_</mo></munder>"#,
+                self.argument()?
+            ),
+            "overset" | "stackrel" => {
+                let over = self.argument()?;
+                let base = self.argument()?;
+                format!("<mover>{}{}</mover>", base, over)
+            }
+            "underset" => {
+                let under = self.argument()?;
+                let base = self.argument()?;
+                format!("<munder>{}{}</munder>", base, under)
+            }
+            "left" => {
+                let open = self.delimiter()?;
+                let body = self.row(&[])?;
+                match self.command().as_deref() {
+                    Some("right") => {}
+                    _ => {
+                        return Err(MathConversionError::new(r"Missing \right"))
+                    }
+                }
+                let close = self.delimiter()?;
+                format!(
+                    r#"<mrow><mo fence="true" stretchy="true">{}</mo>{}<mo fence="true" stretchy="true">{}</mo></mrow>"#,
+                    open, body, close
+                )
+            }
+            "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl"
+            | "Bigr" | "biggl" | "biggr" | "Biggl" | "Biggr" => {
+                format!(r#"<mo fence="true">{}</mo>"#, self.delimiter()?)
+            }
+            "begin" => {
+                let env = self.text_argument()?;
+                let table = self.environment(env.trim())?;
+                match self.command().as_deref() {
+                    Some("end") => {}
+                    _ => return Err(MathConversionError::new(r"Missing \end")),
+                }
+                let end = self.text_argument()?;
+                if end.trim() != env.trim() {
+                    return Err(MathConversionError::new(format!(
+                        r"Expected \end{{{}}}",
+                        env
+                    )));
+                }
+                table
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => {
+                "<mrow></mrow>".to_string()
+            }
+            _ => {
+                return Err(MathConversionError::new(format!(
+                    r"Unknown command \{}",
+                    name
+                )))
+            }
+        };
+
+        Ok((node, false))
+    }
+
+    /// Parses a delimiter following `\left`, `\right` or `\big`
+    fn delimiter(&mut self) -> MathResult<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('\\') => {
+                let name = self.command().unwrap_or_default();
+                let c = match name.as_str() {
+                    "{" | "lbrace" => "{",
+                    "}" | "rbrace" => "}",
+                    "|" | "Vert" => "\u{2016}",
+                    "langle" => "\u{27E8}",
+                    "rangle" => "\u{27E9}",
+                    "lfloor" => "\u{230A}",
+                    "rfloor" => "\u{230B}",
+                    "lceil" => "\u{2308}",
+                    "rceil" => "\u{2309}",
+                    "vert" => "|",
+                    _ => {
+                        return Err(MathConversionError::new(format!(
+                            r"Unknown delimiter \{}",
+                            name
+                        )))
+                    }
+                };
+                Ok(c.to_string())
+            }
+            Some('.') => {
+                self.pos += 1;
+                Ok(String::new())
+            }
+            Some(c) if "()[]|/".contains(c) => {
+                self.pos += 1;
+                Ok(c.to_string())
+            }
+            _ => Err(MathConversionError::new("Missing delimiter")),
+        }
+    }
+
+    /// Parses the body of an environment up to its `\end`, producing a table
+    /// surrounded by the delimiters of the environment
+    fn environment(&mut self, env: &str) -> MathResult<String> {
+        let (open, close, align) = match env {
+            "matrix" | "smallmatrix" => ("", "", None),
+            "pmatrix" => ("(", ")", None),
+            "bmatrix" => ("[", "]", None),
+            "Bmatrix" => ("{", "}", None),
+            "vmatrix" => ("|", "|", None),
+            "Vmatrix" => ("\u{2016}", "\u{2016}", None),
+            "cases" => ("{", "", Some("left left")),
+            "align" | "align*" | "aligned" | "split" | "alignat"
+            | "alignat*" | "eqnarray" | "eqnarray*" => {
+                ("", "", Some("right left"))
+            }
+            "gather" | "gather*" | "gathered" | "equation" | "equation*"
+            | "multline" | "multline*" => ("", "", None),
+            "array" => {
+                let spec = self.text_argument()?;
+                let align = spec
+                    .chars()
+                    .filter_map(|c| match c {
+                        'l' => Some("left"),
+                        'c' => Some("center"),
+                        'r' => Some("right"),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                return self.table("", "", Some(&align));
+            }
+            _ => {
+                return Err(MathConversionError::new(format!(
+                    "Unknown environment {}",
+                    env
+                )))
+            }
+        };
+
+        self.table(open, close, align)
+    }
+
+    fn table(
+        &mut self,
+        open: &str,
+        close: &str,
+        align: Option<&str>,
+    ) -> MathResult<String> {
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+
+        loop {
+            cells.push(format!("<mtd>{}</mtd>", self.row(&[])?));
+            match self.stop() {
+                Some(Stop::Column) => self.pos += 1,
+                Some(Stop::Row) => {
+                    self.pos += 2;
+                    rows.push(std::mem::take(&mut cells));
+                }
+                Some(Stop::End) | None => {
+                    rows.push(std::mem::take(&mut cells));
+                    break;
+                }
+                Some(stop) => {
+                    return Err(MathConversionError::new(format!(
+                        "Unexpected {}",
+                        stop
+                    )))
+                }
+            }
+        }
+
+        // A trailing \\ produces an empty row that should not be displayed
+        if rows.len() > 1
+            && rows
+                .last()
+                .is_some_and(|x| x == &["<mtd><mrow></mrow></mtd>"])
+        {
+            rows.pop();
+        }
+
+        let table = format!(
+            "<mtable{}>{}</mtable>",
+            align
+                .map(|x| format!(r#" columnalign="{}""#, x))
+                .unwrap_or_default(),
+            rows.into_iter()
+                .map(|cells| format!("<mtr>{}</mtr>", cells.concat()))
+                .collect::<String>(),
+        );
+
+        Ok(if open.is_empty() && close.is_empty() {
+            table
+        } else {
+            format!(
+                r#"<mrow><mo fence="true">{}</mo>{}<mo fence="true">{}</mo></mrow>"#,
+                open, table, close
+            )
+        })
+    }
+}
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan",
+    "sinh", "cosh", "tanh", "coth", "log", "ln", "lg", "exp", "deg", "dim",
+    "ker", "hom", "arg", "gcd", "Pr",
+];
+
+const LIMIT_FUNCTIONS: &[&str] =
+    &["lim", "liminf", "limsup", "max", "min", "sup", "inf", "det"];
+
+fn symbol_identifier(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "ell" => "ℓ",
+        "hbar" => "ℏ",
+        "emptyset" | "varnothing" => "∅",
+        "aleph" => "ℵ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "wp" => "℘",
+        "%" => "%",
+        "$" => "$",
+        "#" => "#",
+        "_" => "_",
+        _ => return None,
+    })
+}
+
+fn symbol_operator(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "⋅",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "supset" => "⊃",
+        "subseteq" => "⊆",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "angle" => "∠",
+        "triangle" => "△",
+        "prime" => "′",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "{" | "lbrace" => "{",
+        "}" | "rbrace" => "}",
+        "|" | "Vert" => "‖",
+        "vert" => "|",
+        "&" => "&",
+        "colon" => ":",
+        _ => return None,
+    })
+}
+
+fn large_operator(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        "bigoplus" => "⨁",
+        "bigotimes" => "⨂",
+        "bigvee" => "⋁",
+        "bigwedge" => "⋀",
+        _ => return None,
+    })
+}
+
+fn space_width(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "," | "thinspace" => "0.167em",
+        ":" | ">" | "medspace" => "0.222em",
+        ";" | "thickspace" => "0.278em",
+        " " => "0.333em",
+        "!" | "negthinspace" => "-0.167em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(tex: &str) -> String {
+        let math = tex_to_mathml(tex, None, false).unwrap();
+        math.trim_start_matches(
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML">"#,
+        )
+        .trim_end_matches("</math>")
+        .to_string()
+    }
+
+    #[test]
+    fn tex_to_mathml_should_convert_identifiers_numbers_and_operators() {
+        assert_eq!(
+            inline("x + 2.5 = y - 1"),
+            "<mrow><mi>x</mi><mo>+</mo><mn>2.5</mn><mo>=</mo><mi>y</mi>\
+             <mo>\u{2212}</mo><mn>1</mn></mrow>"
+        );
+        assert_eq!(
+            inline(r"a < b"),
+            "<mrow><mi>a</mi><mo>&lt;</mo><mi>b</mi></mrow>"
+        );
+        assert_eq!(
+            inline(r"\alpha \leq \infty"),
+            "<mrow><mi>α</mi><mo>≤</mo><mi>∞</mi></mrow>"
+        );
+    }
+
+    #[test]
+    fn tex_to_mathml_should_convert_scripts_fractions_and_roots() {
+        assert_eq!(
+            inline("x^2_i"),
+            "<msubsup><mi>x</mi><mi>i</mi><mn>2</mn></msubsup>"
+        );
+        assert_eq!(
+            inline("e^{-x}"),
+            "<msup><mi>e</mi><mrow><mo>\u{2212}</mo><mi>x</mi></mrow></msup>"
+        );
+        assert_eq!(
+            inline("x^23"),
+            "<mrow><msup><mi>x</mi><mn>2</mn></msup><mn>3</mn></mrow>"
+        );
+        assert_eq!(
+            inline(r"\frac{a}{b}"),
+            "<mfrac><mi>a</mi><mi>b</mi></mfrac>"
+        );
+        assert_eq!(inline(r"\sqrt{x}"), "<msqrt><mi>x</mi></msqrt>");
+        assert_eq!(
+            inline(r"\sqrt[3]{x}"),
+            "<mroot><mi>x</mi><mn>3</mn></mroot>"
+        );
+        assert_eq!(inline("f'"), "<msup><mi>f</mi><mo>′</mo></msup>");
+    }
+
+    #[test]
+    fn tex_to_mathml_should_place_limits_under_operators_when_displayed() {
+        assert_eq!(
+            inline(r"\sum_{i=0}^n i"),
+            r#"<mrow><msubsup><mo largeop="true">∑</mo><mrow><mi>i</mi><mo>=</mo><mn>0</mn></mrow><mi>n</mi></msubsup><mi>i</mi></mrow>"#
+        );
+
+        let block = tex_to_mathml(r"\lim_{x \to 0} x", None, true).unwrap();
+        assert!(block.contains(r#"display="block""#), "{}", block);
+        assert!(block.contains("<munder><mo>lim</mo>"), "{}", block);
+    }
+
+    #[test]
+    fn tex_to_mathml_should_support_text_fonts_accents_and_delimiters() {
+        assert_eq!(
+            inline(r"\text{if } x"),
+            "<mrow><mtext>if </mtext><mi>x</mi></mrow>"
+        );
+        assert_eq!(
+            inline(r"\mathbb{R}"),
+            r#"<mstyle mathvariant="double-struck"><mi>R</mi></mstyle>"#
+        );
+        assert_eq!(
+            inline(r"\hat{x}"),
+            r#"<mover accent="true"><mi>x</mi><mo>^</mo></mover>"#
+        );
+        assert_eq!(
+            inline(r"\left( x \right]"),
+            r#"<mrow><mo fence="true" stretchy="true">(</mo><mi>x</mi><mo fence="true" stretchy="true">]</mo></mrow>"#
+        );
+        assert_eq!(inline(r"\sin x"), "<mrow><mi>sin</mi><mi>x</mi></mrow>");
+    }
+
+    #[test]
+    fn tex_to_mathml_should_support_environments() {
+        assert_eq!(
+            inline(r"\begin{pmatrix} a & b \\ c & d \end{pmatrix}"),
+            r#"<mrow><mo fence="true">(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr><mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo fence="true">)</mo></mrow>"#
+        );
+
+        let align =
+            tex_to_mathml("x &= 1 \\\\\ny &= 2 \\\\", Some("align"), true)
+                .unwrap();
+        assert_eq!(
+            align,
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block"><mtable columnalign="right left"><mtr><mtd><mi>x</mi></mtd><mtd><mrow><mo>=</mo><mn>1</mn></mrow></mtd></mtr><mtr><mtd><mi>y</mi></mtd><mtd><mrow><mo>=</mo><mn>2</mn></mrow></mtd></mtr></mtable></math>"#
+        );
+    }
+
+    #[test]
+    fn tex_to_mathml_should_fail_for_unsupported_or_malformed_tex() {
+        assert!(tex_to_mathml(r"\unknowncommand x", None, false).is_err());
+        assert!(tex_to_mathml(r"\frac{a}", None, false).is_err());
+        assert!(tex_to_mathml(r"{x", None, false).is_err());
+        assert!(tex_to_mathml(r"x}", None, false).is_err());
+        assert!(tex_to_mathml(r"x^2^3", None, false).is_err());
+        assert!(tex_to_mathml(r"a \\ b", None, false).is_err());
+        assert!(tex_to_mathml("x", Some("tikzpicture"), true).is_err());
+        assert!(
+            tex_to_mathml(r"\begin{matrix} a \end{pmatrix}", None, false)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn tex_to_mathml_should_fail_if_nested_too_deeply() {
+        let nested = |open: &str, n: usize| {
+            format!("{}x{}", open.repeat(n), "}".repeat(n))
+        };
+
+        assert!(tex_to_mathml(&nested("{", MAX_DEPTH - 1), None, false).is_ok());
+        assert!(tex_to_mathml(
+            &nested(r"\frac{1}{", MAX_DEPTH / 3),
+            None,
+            false
+        )
+        .is_ok());
+        assert_eq!(
+            tex_to_mathml(&nested("{", 20000), None, false),
+            Err(MathConversionError::new("Nested deeper than 100"))
+        );
+        assert!(tex_to_mathml(&nested(r"\sqrt{", 20000), None, false).is_err());
+        assert!(tex_to_mathml(&nested("x^{", 20000), None, false).is_err());
+        assert!(tex_to_mathml(&nested(r"\left( ", 20000), None, false).is_err());
+    }
+}
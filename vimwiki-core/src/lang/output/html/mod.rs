@@ -2,14 +2,40 @@ mod config;
 pub use config::*;
 
 mod formatter;
-pub use formatter::HtmlFormatter;
+pub use formatter::{HtmlFormatter, HtmlTocEntry};
 
 mod convert;
-pub use convert::{ToHtmlPage, ToHtmlString};
+pub use convert::{HtmlReport, ToHtmlPage, ToHtmlString};
 
 mod error;
 pub use error::{HtmlOutputError, HtmlOutputResult};
 
+mod assets;
+pub use assets::HtmlAsset;
+
+mod code;
+pub use code::highlight_css;
+use code::{ClassedLineHighlighter, CodeLineOptions};
+
+mod hooks;
+pub use hooks::{
+    HtmlHook, HtmlHookElement, HtmlHookKind, HtmlHookOutcome, HtmlHooks,
+};
+
+mod math;
+pub use math::{tex_to_mathml, MathConversionError};
+
+mod sanitize;
+pub use sanitize::HtmlStripped;
+
+mod template;
+pub use template::{
+    HtmlTemplateError, HtmlTemplateRenderer, HtmlTemplateValue,
+};
+
+mod transclusion;
+pub use transclusion::HtmlTransclusionElement;
+
 mod utils;
 pub use utils::LinkResolutionError;
 
@@ -17,26 +43,14 @@ use crate::lang::{
     elements::*,
     output::{Output, OutputFormatter},
 };
-use lazy_static::lazy_static;
 use std::{borrow::Cow, collections::HashMap, fmt::Write};
 use syntect::{
     easy::HighlightLines,
-    highlighting::ThemeSet,
     html::{self, IncludeBackground},
-    parsing::SyntaxSet,
 };
 use uriparse::URIReference;
 use voca_rs::escape;
 
-lazy_static! {
-    /// Default syntax set for languages
-    static ref DEFAULT_SYNTAX_SET: SyntaxSet =
-        SyntaxSet::load_defaults_nonewlines();
-
-    /// Default theme highlight set for languages
-    static ref DEFAULT_THEME_SET: ThemeSet = ThemeSet::load_defaults();
-}
-
 impl<'a> Output<HtmlFormatter> for Page<'a> {
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
         for element in self.elements.iter() {
@@ -97,6 +111,10 @@ impl<'a> Output<HtmlFormatter> for Blockquote<'a> {
     /// </blockquote>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Blockquote(self))? {
+            return Ok(());
+        }
+
         writeln!(f, "<blockquote>")?;
 
         // If we have more than one group of lines, then we want a paragraph
@@ -145,6 +163,10 @@ impl<'a> Output<HtmlFormatter> for DefinitionList<'a> {
     /// </dl>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::DefinitionList(self))? {
+            return Ok(());
+        }
+
         writeln!(f, "<dl>")?;
         for bundle in self {
             // Write our term in the form <dt>{term}</dt>
@@ -178,6 +200,10 @@ impl Output<HtmlFormatter> for Divider {
     /// <hr />
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Divider(self))? {
+            return Ok(());
+        }
+
         write!(f, "<hr />")?;
         Ok(())
     }
@@ -208,6 +234,10 @@ impl<'a> Output<HtmlFormatter> for Header<'a> {
     /// </div>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Header(self))? {
+            return Ok(());
+        }
+
         let raw_content = self.content.to_string();
         let header_id = utils::normalize_id(&raw_content);
         let unique_header_id = f.ensure_unique_id(&header_id);
@@ -259,6 +289,13 @@ impl<'a> Output<HtmlFormatter> for Header<'a> {
             if has_different_complete_id {
                 write!(f, "</div>")?;
             }
+
+            let entry = HtmlTocEntry {
+                level: self.level,
+                text: raw_content.trim().to_string(),
+                id: unique_complete_header_id.to_string(),
+            };
+            f.add_toc_entry(entry);
         }
 
         Ok(())
@@ -286,6 +323,10 @@ impl<'a> Output<HtmlFormatter> for List<'a> {
     /// </ol>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::List(self))? {
+            return Ok(());
+        }
+
         // TODO: This should be used for list items... how?
         let _ignore_newlines = f.config().list.ignore_newline;
 
@@ -349,6 +390,10 @@ impl<'a> Output<HtmlFormatter> for ListItem<'a> {
     /// <li class="rejected">...</li>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::ListItem(self))? {
+            return Ok(());
+        }
+
         // TODO: This should be used for list items... how?
         let _ignore_newlines = f.config().list.ignore_newline;
 
@@ -412,7 +457,32 @@ impl<'a> Output<HtmlFormatter> for MathBlock<'a> {
     /// some math enclosed in block notation
     /// \end{environment}
     /// ```
+    ///
+    /// ### With server-side rendering
+    ///
+    /// When enabled, the math is instead converted into MathML, falling back
+    /// to the above notation if the math is not supported
+    ///
+    /// ```html
+    /// <math xmlns="http://www.w3.org/1998/Math/MathML" display="block">
+    ///     ...
+    /// </math>
+    /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::MathBlock(self))? {
+            return Ok(());
+        }
+
+        if f.config().math.server_side {
+            let tex = self.lines.join("\n");
+            if let Ok(mathml) =
+                tex_to_mathml(&tex, self.environment.as_deref(), true)
+            {
+                write!(f, "{}", mathml)?;
+                return Ok(());
+            }
+        }
+
         if let Some(env) = self.environment.as_deref() {
             writeln!(f, r"\begin{{{}}}", env)?;
             for line in self {
@@ -440,8 +510,7 @@ impl<'a> Output<HtmlFormatter> for Placeholder<'a> {
     ///
     /// Note that this doesn't actually do any writing, but instead updates
     /// settings in the formatter with specific details such as a title, date,
-    /// or alternative template to use
-    /// ```
+    /// or alternative template to use, along with the metadata of the page
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
         match self {
             Self::Title(x) => f.set_title(x),
@@ -450,6 +519,8 @@ impl<'a> Output<HtmlFormatter> for Placeholder<'a> {
             _ => {}
         }
 
+        f.metadata_mut().insert(self);
+
         Ok(())
     }
 }
@@ -494,34 +565,30 @@ impl<'a> Output<HtmlFormatter> for CodeBlock<'a> {
     ///     ...
     /// </pre>
     /// ```
+    ///
+    /// When the metadata of the block includes `linenos="true"` or
+    /// `hl_lines="1 3-5"`, each line is wrapped to hold its number and
+    /// whether it is highlighted, with `linenostart="10"` changing the
+    /// number of the first line:
+    ///
+    /// ```html
+    /// <span class="line highlighted"><span class="line-number">1</span>...</span>
+    /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::CodeBlock(self))? {
+            return Ok(());
+        }
+
         // If we are told to perform a server-side render of styles, we
         // build out the <pre> tag and then inject a variety of <span> wrapping
         // individual text elements with associated stylings
         if f.config().code.server_side {
-            // Load and use the syntax set from the specified directory if
-            // given, otherwise use the default syntax set
-            let custom_ss = f
-                .config()
-                .code
-                .syntax_dir
-                .as_ref()
-                .map(SyntaxSet::load_from_folder)
-                .transpose()
-                .map_err(HtmlOutputError::from)?;
-            let ss = custom_ss.as_ref().unwrap_or(&DEFAULT_SYNTAX_SET);
-
-            // Load and use the theme set from the specified directory if
-            // given, otherwise use the default theme set
-            let custom_ts = f
-                .config()
-                .code
-                .theme_dir
-                .as_ref()
-                .map(ThemeSet::load_from_folder)
-                .transpose()
-                .map_err(HtmlOutputError::from)?;
-            let ts = custom_ts.as_ref().unwrap_or(&DEFAULT_THEME_SET);
+            // Load the syntax and theme sets from the specified directories
+            // if given, otherwise use the defaults, both of which are cached
+            let ss =
+                code::load_syntax_set(f.config().code.syntax_dir.as_deref())?;
+            let ts =
+                code::load_theme_set(f.config().code.theme_dir.as_deref())?;
 
             // Get syntax using language specifier, otherwise use plain text
             let syntax = if let Some(lang) = self.language.as_ref() {
@@ -532,17 +599,8 @@ impl<'a> Output<HtmlFormatter> for CodeBlock<'a> {
             };
 
             // Load the specified theme, reporting an error if missing
-            let theme =
-                ts.themes.get(&f.config().code.theme).ok_or_else(|| {
-                    HtmlOutputError::ThemeMissing(
-                        f.config().code.theme.to_string(),
-                    )
-                })?;
-            let mut h = HighlightLines::new(syntax, theme);
-
-            // NOTE: The function to create the <pre> tag includes a newline
-            //       at the end, which is why we use write! instead of writeln!
-            write!(f, "{}", html::start_highlighted_html_snippet(theme).0)?;
+            let theme = code::find_theme(&ts, &f.config().code.theme)?;
+            let options = CodeLineOptions::from_metadata(&self.metadata);
 
             // TODO: The preferred way is to iterate with line endings
             //       included, which we don't have. Want to avoid allocating
@@ -550,16 +608,31 @@ impl<'a> Output<HtmlFormatter> for CodeBlock<'a> {
             //       may need to be retooled to be just the entire text
             //       including line endings while supporting an iterator over
             //       the lines
-            for line in self {
-                let regions = h.highlight(line, ss);
-                writeln!(
-                    f,
-                    "{}",
-                    html::styled_line_to_highlighted_html(
+            if f.config().code.classed {
+                writeln!(f, r#"<pre class="code">"#)?;
+
+                let mut h = ClassedLineHighlighter::new(syntax, &ss);
+                for (idx, line) in self.lines.iter().enumerate() {
+                    let line = h.highlight(line);
+                    write_code_line(f, &options, idx, &line, None)?;
+                }
+            } else {
+                // NOTE: The function to create the <pre> tag includes a
+                //       newline at the end, which is why we use write!
+                //       instead of writeln!
+                write!(f, "{}", html::start_highlighted_html_snippet(theme).0)?;
+
+                let highlight =
+                    code::to_css_color(code::line_highlight_color(theme));
+                let mut h = HighlightLines::new(syntax, theme);
+                for (idx, line) in self.lines.iter().enumerate() {
+                    let regions = h.highlight(line, &ss);
+                    let line = html::styled_line_to_highlighted_html(
                         &regions[..],
                         IncludeBackground::No,
-                    )
-                )?;
+                    );
+                    write_code_line(f, &options, idx, &line, Some(&highlight))?;
+                }
             }
 
             write!(f, "</pre>")?;
@@ -575,13 +648,17 @@ impl<'a> Output<HtmlFormatter> for CodeBlock<'a> {
 
                 // If provided with a language, fill it in as the class
                 if let Some(lang) = self.language.as_ref() {
-                    write!(f, r#" class="{}""#, lang)?;
+                    write!(f, r#" class="{}""#, escape::escape_html(lang))?;
                 }
 
                 // For each metadata assignment, treat it as an HTML attribute
-                for (attr, value) in self.metadata.iter() {
-                    write!(f, r#" {}="{}""#, attr, value)?;
-                }
+                sanitize::write_attributes(
+                    f,
+                    "code",
+                    self.metadata
+                        .iter()
+                        .map(|(attr, value)| (attr.as_ref(), value.as_ref())),
+                )?;
 
                 // NOTE: We do NOT include a newline here because it results
                 //       in the output having a newline at the beginning of
@@ -627,6 +704,32 @@ impl<'a> Output<HtmlFormatter> for Paragraph<'a> {
     /// <p>Some paragraph text<br />on multiple lines</p>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Paragraph(self))? {
+            return Ok(());
+        }
+
+        // A paragraph comprised solely of the transclusion of a wiki page is
+        // replaced by the content of that page rather than nesting it within
+        // the paragraph
+        let mut elements = self.lines.iter().flat_map(|line| line.iter());
+        if let (Some(element), None) = (elements.next(), elements.next()) {
+            if let InlineElement::Link(link @ Link::Transclusion { data }) =
+                element.as_inner()
+            {
+                if let Some(path) = transclusion::find_page(f, data) {
+                    if f.run_hook(HtmlHookElement::Link(link))? {
+                        return Ok(());
+                    }
+
+                    return transclusion::write_page(
+                        f,
+                        path,
+                        data.to_anchor().as_ref(),
+                    );
+                }
+            }
+        }
+
         let ignore_newlines = f.config().paragraph.ignore_newline;
         let is_blank = self.is_blank();
 
@@ -748,6 +851,10 @@ impl<'a> Output<HtmlFormatter> for Table<'a> {
     /// ```
     ///
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Table(self))? {
+            return Ok(());
+        }
+
         if self.centered {
             writeln!(f, "<table class=\"center\">")?;
         } else {
@@ -856,6 +963,10 @@ impl<'a> Output<HtmlFormatter> for InlineElement<'a> {
 impl<'a> Output<HtmlFormatter> for Text<'a> {
     /// Writes text in HTML, escaping any HTML-specific characters
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Text(self))? {
+            return Ok(());
+        }
+
         write!(f, "{}", escape::escape_html(self.as_str()))?;
         Ok(())
     }
@@ -864,6 +975,10 @@ impl<'a> Output<HtmlFormatter> for Text<'a> {
 impl<'a> Output<HtmlFormatter> for DecoratedText<'a> {
     /// Writes decorated text in HTML
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::DecoratedText(self))? {
+            return Ok(());
+        }
+
         // First, we figure out the type of decoration to apply with bold
         // having the most unique situation as it can also act as an anchor
         match self {
@@ -948,6 +1063,10 @@ impl Output<HtmlFormatter> for Keyword {
     /// Unable to be implemented via Output<HtmlFormatter> trait as generic associated types
     /// would be required.
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Keyword(self))? {
+            return Ok(());
+        }
+
         // For all keywords other than todo, they are treated as plain output
         // for HTML. For todo, it is wrapped in a span with a todo class
         match self {
@@ -1051,7 +1170,42 @@ impl<'a> Output<HtmlFormatter> for Link<'a> {
     /// <img src="path/to/img.png" alt="descr" />
     /// <img src="path/to/img.png" alt="descr" style="A" />
     /// ```
+    ///
+    /// Transclusions of video, audio, documents such as PDFs and other files
+    /// are detected by the extension of their path (or the media type of a
+    /// `data:` uri) and written as `<video>`, `<audio>`, `<iframe>` and
+    /// `<object>` instead, keeping only properties that are valid attributes
+    /// of the element:
+    ///
+    /// ```html
+    /// <video src="path/to/clip.mp4" controls>descr</video>
+    /// <audio src="path/to/song.mp3" controls>descr</audio>
+    /// <iframe src="path/to/doc.pdf" title="descr"></iframe>
+    /// <object data="path/to/notes.txt">descr</object>
+    /// ```
+    ///
+    /// ### Transclusion of Wiki Page
+    ///
+    /// For `{{path/to/page}}` or `{{path/to/page.wiki#Header}}` where the
+    /// page exists and the transclusion is the only content of a paragraph,
+    /// the content of the page (or only the section under the header) is
+    /// inlined in place of the paragraph:
+    ///
+    /// ```html
+    /// <div class="transclusion"> ... </div>
+    /// ```
+    ///
+    /// Anywhere else, such as alongside text, the page cannot be nested and
+    /// is written as a link to it instead:
+    ///
+    /// ```html
+    /// <a href="path/to/page.html">path/to/page</a>
+    /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Link(self))? {
+            return Ok(());
+        }
+
         // Produces a link tag of <a href=".." ...>link/description</a>
         // based on the link data and a given base url representing the root
         // of the wiki if needed
@@ -1062,32 +1216,61 @@ impl<'a> Output<HtmlFormatter> for Link<'a> {
             properties: Option<&HashMap<Cow<'_, str>, Cow<'_, str>>>,
             use_img_tag: bool,
         ) -> HtmlOutputResult {
-            if use_img_tag {
-                write!(f, "<img src=\"{}\"", href)?;
-
-                if let Some(desc) = description {
-                    write!(
+            let transclusion = if use_img_tag {
+                Some(f.config().transclusion.element_for(href))
+            } else {
+                None
+            };
+            let element = transclusion.map_or("a", |x| x.as_tag());
+            if !f.config().sanitize.is_uri_allowed_on(element, href) {
+                f.add_stripped(HtmlStripped::Uri {
+                    element: element.to_string(),
+                    uri: href.to_string(),
+                });
+
+                // Without the link, we still want to keep its description
+                // (or the alternative text of an image) as text, falling back
+                // to the text of the uri for links like the default output
+                match description {
+                    Some(desc) => write!(
                         f,
-                        " alt=\"{}\"",
+                        "{}",
                         escape::escape_html(desc.to_string().as_str())
-                    )?;
+                    )?,
+                    None if !use_img_tag => write!(
+                        f,
+                        "{}",
+                        escape::escape_html(href.to_string().as_str())
+                    )?,
+                    None => {}
                 }
+                return Ok(());
+            }
 
-                if let Some(properties) = properties {
-                    for (k, v) in properties.iter() {
-                        write!(f, " {}=\"{}\"", k, escape::escape_html(v))?;
-                    }
-                }
+            // NOTE: Uris cannot contain quotes or angle brackets, so they are
+            //       only escaped in full when sanitizing
+            let href_str = if f.config().sanitize.enabled {
+                escape::escape_html(href.to_string().as_str())
+            } else {
+                href.to_string()
+            };
+            let properties = properties
+                .into_iter()
+                .flatten()
+                .map(|(k, v)| (k.as_ref(), v.as_ref()));
 
-                write!(f, " />")?;
+            if let Some(transclusion) = transclusion {
+                transclusion::write_element(
+                    f,
+                    transclusion,
+                    &href_str,
+                    description,
+                    properties,
+                )?;
             } else {
-                write!(f, "<a href=\"{}\"", href)?;
+                write!(f, "<a href=\"{}\"", href_str)?;
 
-                if let Some(properties) = properties {
-                    for (k, v) in properties.iter() {
-                        write!(f, " {}=\"{}\"", k, escape::escape_html(v))?;
-                    }
-                }
+                sanitize::write_attributes(f, element, properties)?;
 
                 write!(f, ">")?;
 
@@ -1105,7 +1288,7 @@ impl<'a> Output<HtmlFormatter> for Link<'a> {
                         }
                         .fmt(f)?
                     }
-                    None => write!(f, "{}", href)?,
+                    None => write!(f, "{}", href_str)?,
                 }
 
                 write!(f, "</a>")?;
@@ -1114,20 +1297,37 @@ impl<'a> Output<HtmlFormatter> for Link<'a> {
             Ok(())
         }
 
-        let uri_ref = utils::resolve_link(
-            f.config(),
-            &f.config().to_current_wiki(),
-            f.config().as_active_page_path_within_wiki(),
-            self,
-        )
-        .map_err(HtmlOutputError::from)?;
+        // Transclusions of wiki pages outside of a paragraph of their own
+        // cannot be replaced by the content of the page, so they link to it
+        let page_link;
+        let link = match self {
+            Self::Transclusion { data }
+                if transclusion::find_page(f, data).is_some() =>
+            {
+                page_link = Self::Wiki { data: data.clone() };
+                &page_link
+            }
+            _ => self,
+        };
+
+        // Links to assets point to where the assets are copied, if enabled
+        let uri_ref = match assets::resolve_asset(f, link)? {
+            Some(uri_ref) => uri_ref,
+            None => utils::resolve_link(
+                f.config(),
+                &f.config().to_current_wiki(),
+                f.config().as_active_page_path_within_wiki(),
+                link,
+            )
+            .map_err(HtmlOutputError::from)?,
+        };
 
         write_link(
             f,
             &uri_ref,
-            self.to_description_or_fallback().as_ref(),
-            self.properties(),
-            matches!(self, Self::Transclusion { .. }),
+            link.to_description_or_fallback().as_ref(),
+            link.properties(),
+            matches!(link, Self::Transclusion { .. }),
         )
     }
 }
@@ -1144,7 +1344,12 @@ impl<'a> Output<HtmlFormatter> for Tags<'a> {
     /// <span id="Header 1-tag1"></span><span class="tag" id="tag1">tag1</span>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Tags(self))? {
+            return Ok(());
+        }
+
         for tag in self {
+            f.add_tag(tag.as_str());
             let id = utils::normalize_id(tag.as_str());
             let unique_id = f.ensure_unique_id(&id);
 
@@ -1180,6 +1385,10 @@ impl<'a> Output<HtmlFormatter> for CodeInline<'a> {
     /// <code>some code</code>
     /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::CodeInline(self))? {
+            return Ok(());
+        }
+
         write!(f, "<code>{}</code>", escape::escape_html(self.as_str()))?;
         Ok(())
     }
@@ -1193,7 +1402,27 @@ impl<'a> Output<HtmlFormatter> for MathInline<'a> {
     /// ```html
     /// \(some math\)
     /// ```
+    ///
+    /// ### With server-side rendering
+    ///
+    /// When enabled, the math is instead converted into MathML, falling back
+    /// to the above notation if the math is not supported
+    ///
+    /// ```html
+    /// <math xmlns="http://www.w3.org/1998/Math/MathML">...</math>
+    /// ```
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::MathInline(self))? {
+            return Ok(());
+        }
+
+        if f.config().math.server_side {
+            if let Ok(mathml) = tex_to_mathml(self.as_str(), None, false) {
+                write!(f, "{}", mathml)?;
+                return Ok(());
+            }
+        }
+
         write!(f, r"\({}\)", escape::escape_html(self.as_str()))?;
         Ok(())
     }
@@ -1202,6 +1431,10 @@ impl<'a> Output<HtmlFormatter> for MathInline<'a> {
 impl<'a> Output<HtmlFormatter> for Comment<'a> {
     /// Writes a comment in HTML
     fn fmt(&self, f: &mut HtmlFormatter) -> HtmlOutputResult {
+        if f.run_hook(HtmlHookElement::Comment(self))? {
+            return Ok(());
+        }
+
         match self {
             Self::Line(x) => x.fmt(f),
             Self::MultiLine(x) => x.fmt(f),
@@ -1273,6 +1506,47 @@ fn build_complete_id(
     Ok(complete_id)
 }
 
+/// Writes a line of server-side highlighted code, wrapping it with a span
+/// holding its line number and highlighting when either is requested, where
+/// the highlight color is inlined if provided
+fn write_code_line(
+    f: &mut HtmlFormatter,
+    options: &CodeLineOptions,
+    idx: usize,
+    line: &str,
+    highlight_color: Option<&str>,
+) -> HtmlOutputResult {
+    if !options.is_wrapped() {
+        writeln!(f, "{}", line)?;
+        return Ok(());
+    }
+
+    if options.is_highlighted(idx) {
+        write!(f, r#"<span class="line highlighted""#)?;
+        if let Some(color) = highlight_color {
+            write!(
+                f,
+                r#" style="display:inline-block;width:100%;background-color:{};""#,
+                color
+            )?;
+        }
+        write!(f, ">")?;
+    } else {
+        write!(f, r#"<span class="line">"#)?;
+    }
+
+    if options.line_numbers {
+        write!(
+            f,
+            r#"<span class="line-number">{}</span>"#,
+            options.line_number(idx)
+        )?;
+    }
+
+    writeln!(f, "{}</span>", line)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1308,6 +1582,7 @@ mod tests {
                     wiki.as_ref(),
                     page.as_ref(),
                 ]),
+                ..Default::default()
             },
             ..Default::default()
         }
@@ -1936,6 +2211,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn math_block_should_support_serverside_render() {
+        let math = MathBlock::new(
+            vec![Cow::from(r"x &= \frac{1}{2} \\"), Cow::from("y &= 3")],
+            Some(Cow::from("align")),
+        );
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            math: HtmlMathConfig { server_side: true },
+            ..Default::default()
+        });
+        math.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            concat!(
+                r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">"#,
+                r#"<mtable columnalign="right left">"#,
+                r#"<mtr><mtd><mi>x</mi></mtd><mtd><mrow><mo>=</mo><mfrac><mn>1</mn><mn>2</mn></mfrac></mrow></mtd></mtr>"#,
+                r#"<mtr><mtd><mi>y</mi></mtd><mtd><mrow><mo>=</mo><mn>3</mn></mrow></mtd></mtr>"#,
+                r#"</mtable></math>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn math_block_should_fall_back_to_mathjax_notation_if_unsupported() {
+        let math = MathBlock::from_lines(vec![r"\unsupported{x}"]);
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            math: HtmlMathConfig { server_side: true },
+            ..Default::default()
+        });
+        math.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            indoc! {r"
+                \[
+                \unsupported{x}
+                \]
+            "}
+            .trim(),
+        );
+    }
+
     #[test]
     fn placeholder_should_set_title_if_specified() {
         let placeholder = Placeholder::title_from_str("test title");
@@ -1991,6 +2310,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn code_block_should_escape_metadata_values_clientside() {
+        let mut metadata = HashMap::new();
+        metadata.insert(Cow::from("title"), Cow::from(r#""><script>"#));
+        let code = CodeBlock::new(
+            Some(Cow::from("rust")),
+            metadata,
+            vec![Cow::from("code")],
+        );
+        let mut f = HtmlFormatter::default();
+        code.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            r#"<pre><code class="rust" title="&quot;&gt;&lt;script&gt;">code</code></pre>"#,
+        );
+    }
+
+    #[test]
+    fn code_block_should_strip_disallowed_metadata_when_sanitizing() {
+        let mut metadata = HashMap::new();
+        metadata.insert(Cow::from("onclick"), Cow::from("alert(1)"));
+        let code = CodeBlock::new(None, metadata, vec![Cow::from("code")]);
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            sanitize: HtmlSanitizeConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        code.fmt(&mut f).unwrap();
+
+        assert_str_eq!(f.get_content(), r"<pre><code>code</code></pre>");
+        assert_eq!(
+            f.get_stripped(),
+            [HtmlStripped::Attribute {
+                element: String::from("code"),
+                name: String::from("onclick"),
+                value: String::from("alert(1)"),
+            }]
+        );
+    }
+
     #[test]
     fn code_block_should_support_serverside_render() {
         let code = CodeBlock::new(
@@ -2047,6 +2409,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn code_block_should_support_serverside_render_with_css_classes() {
+        let code = CodeBlock::new(
+            Some(Cow::from("rust")),
+            HashMap::new(),
+            vec![Cow::from("/* a"), Cow::from("b */ x")],
+        );
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            code: HtmlCodeConfig {
+                server_side: true,
+                classed: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        code.fmt(&mut f).unwrap();
+
+        // Each line is self-contained, so the comment spanning both lines
+        // is closed at the end of the first line and reopened on the second
+        assert_str_eq!(
+            f.get_content(),
+            indoc! {r#"
+                <pre class="code">
+                <span class="source rust"><span class="comment block rust"><span class="punctuation definition comment rust">/*</span> a</span></span>
+                <span class="source rust"><span class="comment block rust">b <span class="punctuation definition comment rust">*/</span></span> x</span>
+                </pre>
+            "#}
+            .trim()
+        );
+    }
+
+    #[test]
+    fn code_block_should_support_serverside_render_with_line_numbers_and_highlights(
+    ) {
+        let mut metadata = HashMap::new();
+        metadata.insert(Cow::from("linenos"), Cow::from("true"));
+        metadata.insert(Cow::from("linenostart"), Cow::from("5"));
+        metadata.insert(Cow::from("hl_lines"), Cow::from("2"));
+        let code = CodeBlock::new(
+            None,
+            metadata,
+            vec![Cow::from("some lines"), Cow::from("of code")],
+        );
+
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            code: HtmlCodeConfig {
+                server_side: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        code.fmt(&mut f).unwrap();
+        assert_str_eq!(
+            f.get_content(),
+            indoc! {r#"
+                <pre style="background-color:#ffffff;">
+                <span class="line"><span class="line-number">5</span><span style="color:#323232;">some lines</span></span>
+                <span class="line highlighted" style="display:inline-block;width:100%;background-color:#f5f5f5;"><span class="line-number">6</span><span style="color:#323232;">of code</span></span>
+                </pre>
+            "#}
+            .trim()
+        );
+
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            code: HtmlCodeConfig {
+                server_side: true,
+                classed: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        code.fmt(&mut f).unwrap();
+        assert_str_eq!(
+            f.get_content(),
+            indoc! {r#"
+                <pre class="code">
+                <span class="line"><span class="line-number">5</span><span class="text plain">some lines</span></span>
+                <span class="line highlighted"><span class="line-number">6</span><span class="text plain">of code</span></span>
+                </pre>
+            "#}
+            .trim()
+        );
+    }
+
+    #[test]
+    fn code_block_should_fail_serverside_render_with_missing_theme() {
+        let code = CodeBlock::from_lines(vec!["some code"]);
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            code: HtmlCodeConfig {
+                server_side: true,
+                theme: String::from("missing"),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            code.fmt(&mut f),
+            Err(HtmlOutputError::ThemeMissing(x)) if x == "missing"
+        ));
+    }
+
     #[test]
     #[ignore]
     fn code_block_should_support_serverside_render_with_custom_syntax_dir() {
@@ -2885,6 +3349,226 @@ mod tests {
         );
     }
 
+    #[test]
+    fn transclusion_link_should_strip_disallowed_properties_when_sanitizing() {
+        let mut properties: HashMap<Cow<str>, Cow<str>> = HashMap::new();
+        properties.insert(Cow::from("onerror"), Cow::from("alert(1)"));
+        properties.insert(Cow::from("class"), Cow::from("wide"));
+
+        let link = Link::new_transclusion_link(
+            URIReference::try_from("https://example.com/img.jpg?a=b&c=d")
+                .unwrap(),
+            None,
+            properties,
+        );
+
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            sanitize: HtmlSanitizeConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        link.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            r#"<img src="https://example.com/img.jpg?a=b&amp;c=d" class="wide" />"#
+        );
+        assert_eq!(
+            f.get_stripped(),
+            [HtmlStripped::Attribute {
+                element: String::from("img"),
+                name: String::from("onerror"),
+                value: String::from("alert(1)"),
+            }]
+        );
+    }
+
+    #[test]
+    fn transclusion_link_should_output_media_elements_based_on_extension() {
+        let to_html = |uri: &str, description: Option<&str>| {
+            let link = Link::new_transclusion_link(
+                URIReference::try_from(uri).unwrap(),
+                description.map(Description::from),
+                None,
+            );
+            let mut f = HtmlFormatter::default();
+            link.fmt(&mut f).unwrap();
+            f.into_content()
+        };
+
+        assert_str_eq!(
+            to_html("https://example.com/clip.MP4", Some("a <clip>")),
+            r#"<video src="https://example.com/clip.MP4" controls>a &lt;clip&gt;</video>"#
+        );
+        assert_str_eq!(
+            to_html("https://example.com/song.mp3", None),
+            r#"<audio src="https://example.com/song.mp3" controls></audio>"#
+        );
+        assert_str_eq!(
+            to_html("https://example.com/doc.pdf", Some("doc")),
+            r#"<iframe src="https://example.com/doc.pdf" title="doc"></iframe>"#
+        );
+        assert_str_eq!(
+            to_html("https://example.com/notes.txt", Some("notes")),
+            r#"<object data="https://example.com/notes.txt">notes</object>"#
+        );
+        assert_str_eq!(
+            to_html("data:video/webm;base64,AAAA", None),
+            r#"<video src="data:video/webm;base64,AAAA" controls></video>"#
+        );
+    }
+
+    #[test]
+    fn transclusion_link_should_only_keep_properties_valid_for_media_element() {
+        let mut properties: HashMap<Cow<str>, Cow<str>> = HashMap::new();
+        properties.insert(Cow::from("autoplay"), Cow::from("true"));
+        properties.insert(Cow::from("controls"), Cow::from("false"));
+        properties.insert(Cow::from("alt"), Cow::from("ignored"));
+
+        let link = Link::new_transclusion_link(
+            URIReference::try_from("clip.mp4").unwrap(),
+            None,
+            properties,
+        );
+
+        let mut f = HtmlFormatter::default();
+        link.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            r#"<video src="clip.mp4" autoplay></video>"#
+        );
+        assert_eq!(
+            f.get_stripped(),
+            [HtmlStripped::Attribute {
+                element: String::from("video"),
+                name: String::from("alt"),
+                value: String::from("ignored"),
+            }]
+        );
+    }
+
+    #[test]
+    fn transclusion_link_should_keep_allowed_media_attributes_when_sanitizing()
+    {
+        let mut properties: HashMap<Cow<str>, Cow<str>> = HashMap::new();
+        properties.insert(Cow::from("controls"), Cow::from("0"));
+        properties.insert(Cow::from("autoplay"), Cow::from("1"));
+        properties.insert(Cow::from("loop"), Cow::from("true"));
+
+        let link = Link::new_transclusion_link(
+            URIReference::try_from("clip.mp4").unwrap(),
+            Some(Description::from("d")),
+            properties,
+        );
+
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            sanitize: HtmlSanitizeConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        link.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            r#"<video src="clip.mp4" autoplay>d</video>"#
+        );
+        assert_eq!(
+            f.get_stripped(),
+            [HtmlStripped::Attribute {
+                element: String::from("video"),
+                name: String::from("loop"),
+                value: String::from("true"),
+            }]
+        );
+    }
+
+    #[test]
+    fn transclusion_link_should_not_embed_data_uri_documents_when_sanitizing() {
+        let html = Link::new_transclusion_link(
+            URIReference::try_from("data:text/html;base64,PHNjcmlwdD4=")
+                .unwrap(),
+            None,
+            HashMap::new(),
+        );
+        let xml = Link::new_transclusion_link(
+            URIReference::try_from("data:text/xml;base64,PGEvPg==").unwrap(),
+            Some(Description::from("xml")),
+            HashMap::new(),
+        );
+
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            sanitize: HtmlSanitizeConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        html.fmt(&mut f).unwrap();
+        xml.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            r#"<img src="data:text/html;base64,PHNjcmlwdD4=" />xml"#
+        );
+        assert_eq!(
+            f.take_stripped(),
+            vec![HtmlStripped::Uri {
+                element: String::from("object"),
+                uri: String::from("data:text/xml;base64,PGEvPg=="),
+            }]
+        );
+    }
+
+    #[test]
+    fn link_should_strip_blocked_uris_when_sanitizing() {
+        let link = Link::new_raw_link(
+            URIReference::try_from("javascript:alert(1)").unwrap(),
+        );
+        let transclusion = Link::new_transclusion_link(
+            URIReference::try_from("javascript:alert(2)").unwrap(),
+            Some(Description::from("<an image>")),
+            HashMap::new(),
+        );
+
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            sanitize: HtmlSanitizeConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        link.fmt(&mut f).unwrap();
+        transclusion.fmt(&mut f).unwrap();
+
+        assert_str_eq!(f.get_content(), "javascript:alert(1)&lt;an image&gt;");
+        assert_eq!(
+            f.take_stripped(),
+            vec![
+                HtmlStripped::Uri {
+                    element: String::from("a"),
+                    uri: String::from("javascript:alert(1)"),
+                },
+                HtmlStripped::Uri {
+                    element: String::from("img"),
+                    uri: String::from("javascript:alert(2)"),
+                },
+            ]
+        );
+
+        // Without sanitizing, the uri is written as-is
+        let mut f = HtmlFormatter::default();
+        link.fmt(&mut f).unwrap();
+        assert_str_eq!(
+            f.get_content(),
+            r#"<a href="javascript:alert(1)">javascript:alert(1)</a>"#
+        );
+    }
+
     #[test]
     fn tags_should_output_span_per_tag() {
         let tags: Tags = vec!["one", "two"].into_iter().collect();
@@ -3025,6 +3709,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn math_inline_should_support_serverside_render() {
+        let math_inline = MathInline::from(r"a < \sqrt{b}");
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            math: HtmlMathConfig { server_side: true },
+            ..Default::default()
+        });
+        math_inline.fmt(&mut f).unwrap();
+
+        assert_str_eq!(
+            f.get_content(),
+            concat!(
+                r#"<math xmlns="http://www.w3.org/1998/Math/MathML">"#,
+                r#"<mrow><mi>a</mi><mo>&lt;</mo><msqrt><mi>b</mi></msqrt></mrow>"#,
+                r#"</math>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn math_inline_should_fall_back_to_mathjax_notation_if_unsupported() {
+        let math_inline = MathInline::from(r"<\unsupported>");
+        let mut f = HtmlFormatter::new(HtmlConfig {
+            math: HtmlMathConfig { server_side: true },
+            ..Default::default()
+        });
+        math_inline.fmt(&mut f).unwrap();
+
+        assert_str_eq!(f.get_content(), r"\(&lt;\unsupported&gt;\)");
+    }
+
     #[test]
     fn comment_should_output_tag_based_on_inner_element() {
         let comment = Comment::from(LineComment::from("some comment"));
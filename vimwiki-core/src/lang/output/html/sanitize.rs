@@ -0,0 +1,164 @@
+use super::{HtmlFormatter, HtmlOutputResult, HtmlSanitizeConfig};
+use derive_more::Display;
+use std::fmt::Write;
+use uriparse::URIReference;
+use voca_rs::escape;
+
+/// Represents something removed from the html output when sanitizing, or
+/// because it is not a valid attribute of the element of a transclusion
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum HtmlStripped {
+    /// Attribute whose name is not allowed or not valid on an element
+    #[display(fmt = "attribute {}=\"{}\" on <{}>", name, value, element)]
+    Attribute {
+        element: String,
+        name: String,
+        value: String,
+    },
+
+    /// Link or transclusion whose uri uses a blocked scheme
+    #[display(fmt = "uri {} on <{}>", uri, element)]
+    Uri { element: String, uri: String },
+}
+
+impl HtmlSanitizeConfig {
+    /// Returns true if an attribute with the given name can be written,
+    /// which is always the case when sanitizing is disabled
+    ///
+    /// Names are compared ignoring case and can be matched by an allowed
+    /// name ending in `*` such as `data-*`, but must consist solely of
+    /// alphanumeric characters and `-`, `_`, `.` or `:`
+    pub fn is_attribute_allowed(&self, name: &str) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let is_valid = !name.is_empty()
+            && name.chars().all(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':')
+            });
+        let name = name.to_lowercase();
+
+        is_valid
+            && self.allowed_attributes.iter().any(|allowed| {
+                let allowed = allowed.to_lowercase();
+                match allowed.strip_suffix('*') {
+                    Some(prefix) => name.starts_with(prefix),
+                    None => name == allowed,
+                }
+            })
+    }
+
+    /// Returns true if the uri can be written, which is always the case
+    /// when sanitizing is disabled
+    pub fn is_uri_allowed(&self, uri: &URIReference<'_>) -> bool {
+        !self.enabled
+            || uri.scheme().is_none_or(|scheme| {
+                let scheme = scheme.as_str().to_lowercase();
+                !self
+                    .blocked_uri_schemes
+                    .iter()
+                    .any(|x| x.to_lowercase() == scheme)
+            })
+    }
+
+    /// Returns true if the uri can be written on the element, which is the
+    /// case for any uri allowed by [`Self::is_uri_allowed`] except for
+    /// `data:` uris on `<iframe>` and `<object>` when sanitizing, as their
+    /// content would be loaded as a document that can run scripts
+    pub fn is_uri_allowed_on(
+        &self,
+        element: &str,
+        uri: &URIReference<'_>,
+    ) -> bool {
+        let is_embedded_data = matches!(element, "iframe" | "object")
+            && uri
+                .scheme()
+                .is_some_and(|x| x.as_str().eq_ignore_ascii_case("data"));
+
+        self.is_uri_allowed(uri) && !(self.enabled && is_embedded_data)
+    }
+}
+
+/// Writes attributes of an element, escaping their values and, when
+/// sanitizing, skipping those that are not allowed while recording them
+pub(super) fn write_attributes<'a>(
+    f: &mut HtmlFormatter,
+    element: &str,
+    attributes: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> HtmlOutputResult {
+    for (name, value) in attributes {
+        if f.config().sanitize.is_attribute_allowed(name) {
+            write!(f, r#" {}="{}""#, name, escape::escape_html(value))?;
+        } else {
+            f.add_stripped(HtmlStripped::Attribute {
+                element: element.to_string(),
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn enabled() -> HtmlSanitizeConfig {
+        HtmlSanitizeConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn is_attribute_allowed_should_allow_everything_if_disabled() {
+        let config = HtmlSanitizeConfig::default();
+        assert!(config.is_attribute_allowed("onclick"));
+        assert!(config.is_attribute_allowed("style"));
+    }
+
+    #[test]
+    fn is_attribute_allowed_should_use_allow_list_if_enabled() {
+        let config = enabled();
+        assert!(config.is_attribute_allowed("class"));
+        assert!(config.is_attribute_allowed("ALT"));
+        assert!(config.is_attribute_allowed("data-line"));
+        assert!(config.is_attribute_allowed("controls"));
+        assert!(config.is_attribute_allowed("autoplay"));
+        assert!(config.is_attribute_allowed("muted"));
+        assert!(!config.is_attribute_allowed("onclick"));
+        assert!(!config.is_attribute_allowed("style"));
+        assert!(!config.is_attribute_allowed("data-x\"><script"));
+        assert!(!config.is_attribute_allowed(""));
+    }
+
+    #[test]
+    fn is_uri_allowed_should_reject_blocked_schemes_if_enabled() {
+        let uri = URIReference::try_from("JavaScript:alert(1)").unwrap();
+        assert!(HtmlSanitizeConfig::default().is_uri_allowed(&uri));
+        assert!(!enabled().is_uri_allowed(&uri));
+
+        let uri = URIReference::try_from("https://example.com").unwrap();
+        assert!(enabled().is_uri_allowed(&uri));
+
+        let uri = URIReference::try_from("some/page.html").unwrap();
+        assert!(enabled().is_uri_allowed(&uri));
+    }
+
+    #[test]
+    fn is_uri_allowed_on_should_reject_data_uris_of_documents_if_enabled() {
+        let uri =
+            URIReference::try_from("data:text/xml;base64,PGEvPg==").unwrap();
+        assert!(HtmlSanitizeConfig::default().is_uri_allowed_on("object", &uri));
+        assert!(!enabled().is_uri_allowed_on("object", &uri));
+        assert!(!enabled().is_uri_allowed_on("iframe", &uri));
+        assert!(enabled().is_uri_allowed_on("img", &uri));
+
+        let uri = URIReference::try_from("notes.txt").unwrap();
+        assert!(enabled().is_uri_allowed_on("object", &uri));
+    }
+}
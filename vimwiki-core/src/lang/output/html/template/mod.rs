@@ -0,0 +1,730 @@
+use super::HtmlOutputError;
+use derive_more::{Display, Error};
+use std::collections::{BTreeMap, HashMap};
+use voca_rs::escape;
+
+mod parser;
+use parser::{Expr, Node, Template};
+
+mod value;
+pub use value::HtmlTemplateValue;
+
+/// Maximum depth of templates including or extending other templates,
+/// guarding against templates that refer to each other indirectly
+const MAX_DEPTH: usize = 32;
+
+type Loader<'a> = dyn Fn(&str) -> Result<String, HtmlOutputError> + 'a;
+
+/// Represents an error encountered when parsing or rendering a template
+#[derive(Clone, Debug, Display, Error, PartialEq, Eq)]
+#[display(
+    fmt = "{}line {}: {}",
+    "name.as_ref().map(|x| format!(\"{}, \", x)).unwrap_or_default()",
+    line,
+    message
+)]
+pub struct HtmlTemplateError {
+    /// Name of the template loaded from the template directory, or none
+    /// if the error is within the initial template
+    #[error(not(source))]
+    pub name: Option<String>,
+
+    /// One-based line within the template where the error occurred
+    pub line: usize,
+
+    pub message: String,
+}
+
+impl HtmlTemplateError {
+    fn new(line: usize, message: String) -> Self {
+        Self {
+            name: None,
+            line,
+            message,
+        }
+    }
+}
+
+/// Renders templates with a set of variables, supporting:
+///
+/// * `{{ expression }}` to write a value, escaping it unless it is html or
+///   passed through the `safe` filter
+/// * `{% if expression %}`, `{% elif expression %}`, `{% else %}` and
+///   `{% endif %}` for conditionals
+/// * `{% for name in expression %}`, `{% else %}` and `{% endfor %}` for
+///   loops, where `loop.index`, `loop.index0`, `loop.first`, `loop.last`
+///   and `loop.length` describe the current iteration
+/// * `{% include "name" %}` to render another template in place
+/// * `{% extends "name" %}` alongside `{% block name %}` and
+///   `{% endblock %}` to render another template, replacing its blocks
+/// * `{% raw %}` and `{% endraw %}` to write text as-is
+/// * `{# comments #}`, and `-` within any delimiter such as `{{-` or `-%}`
+///   to trim whitespace before or after it
+///
+/// Expressions are made up of variables such as `page.title`, quoted
+/// strings, numbers, `true`, `false`, `none`, the operators `==`, `!=`,
+/// `not`, `and` and `or`, and filters such as `value | join(", ")`, where
+/// the available filters are `safe`, `escape` (or `e`), `upper`, `lower`,
+/// `trim`, `length`, `first`, `last`, `join`, `split` and `default`
+///
+/// When legacy placeholders are enabled, the template is instead treated as a
+/// vimwiki template where each `%name%` is replaced with the unescaped value
+/// of the variable `name` if it exists and all other text (including `{{`
+/// and `{%`) is written as-is
+pub struct HtmlTemplateRenderer<'a> {
+    loader: Box<Loader<'a>>,
+    legacy_placeholders: bool,
+}
+
+impl<'a> HtmlTemplateRenderer<'a> {
+    /// Creates a renderer that uses the loader to retrieve the text of
+    /// templates that are included or extended by name
+    pub fn new(
+        loader: impl Fn(&str) -> Result<String, HtmlOutputError> + 'a,
+    ) -> Self {
+        Self {
+            loader: Box::new(loader),
+            legacy_placeholders: false,
+        }
+    }
+
+    /// Sets whether or not templates are vimwiki templates where only
+    /// `%name%` is replaced rather than using the template syntax
+    pub fn with_legacy_placeholders(mut self, enabled: bool) -> Self {
+        self.legacy_placeholders = enabled;
+        self
+    }
+
+    /// Renders the template text using the given variables
+    pub fn render(
+        &self,
+        text: &str,
+        variables: &BTreeMap<String, HtmlTemplateValue>,
+    ) -> Result<String, HtmlOutputError> {
+        if self.legacy_placeholders {
+            return Ok(replace_placeholders(text, |name| variables.get(name)));
+        }
+
+        let template = Template::parse(text)?;
+        let mut state = RenderState {
+            scopes: vec![variables.clone()],
+            blocks: HashMap::new(),
+            names: Vec::new(),
+            out: String::new(),
+        };
+        self.render_template(&template, &mut state)?;
+        Ok(state.out)
+    }
+
+    fn load(
+        &self,
+        name: &str,
+        line: usize,
+        state: &RenderState,
+    ) -> Result<Template, HtmlOutputError> {
+        if state.names.iter().any(|x| x == name)
+            || state.names.len() >= MAX_DEPTH
+        {
+            return Err(state
+                .error(line, format!("Template {} refers to itself", name))
+                .into());
+        }
+
+        let text = (self.loader)(name)?;
+        Template::parse(&text).map_err(|mut x| {
+            x.name = Some(name.to_string());
+            x.into()
+        })
+    }
+
+    fn render_template(
+        &self,
+        template: &Template,
+        state: &mut RenderState,
+    ) -> Result<(), HtmlOutputError> {
+        match template.extends.as_ref() {
+            // When extending, the blocks of this template replace those of
+            // the parent unless a template extending this one already has
+            Some(parent) => {
+                collect_blocks(&template.nodes, &mut state.blocks);
+                let parent_template = self.load(parent, 1, state)?;
+                state.names.push(parent.to_string());
+                self.render_template(&parent_template, state)?;
+                state.names.pop();
+                Ok(())
+            }
+            None => self.render_nodes(&template.nodes, state),
+        }
+    }
+
+    fn render_nodes(
+        &self,
+        nodes: &[Node],
+        state: &mut RenderState,
+    ) -> Result<(), HtmlOutputError> {
+        for node in nodes {
+            match node {
+                Node::Text(x) => state.out.push_str(x),
+                Node::Output { expr, line } => {
+                    let value = state.eval(expr, *line)?;
+                    state.write(&value);
+                }
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let mut body = otherwise;
+                    for (cond, nodes) in branches {
+                        if state.eval(cond, 0)?.is_truthy() {
+                            body = nodes;
+                            break;
+                        }
+                    }
+                    self.render_nodes(body, state)?;
+                }
+                Node::For {
+                    name,
+                    iter,
+                    body,
+                    otherwise,
+                } => {
+                    let items = match state.eval(iter, 0)? {
+                        HtmlTemplateValue::List(x) => x,
+                        HtmlTemplateValue::Map(x) => x
+                            .into_iter()
+                            .map(|(key, value)| {
+                                HtmlTemplateValue::map(vec![
+                                    ("key", HtmlTemplateValue::from(key)),
+                                    ("value", value),
+                                ])
+                            })
+                            .collect(),
+                        HtmlTemplateValue::Null => Vec::new(),
+                        x => vec![x],
+                    };
+
+                    if items.is_empty() {
+                        self.render_nodes(otherwise, state)?;
+                    }
+
+                    let length = items.len();
+                    for (i, item) in items.into_iter().enumerate() {
+                        let mut scope = BTreeMap::new();
+                        scope.insert(name.to_string(), item);
+                        scope.insert(
+                            "loop".to_string(),
+                            HtmlTemplateValue::map(vec![
+                                ("index", HtmlTemplateValue::from(i + 1)),
+                                ("index0", HtmlTemplateValue::from(i)),
+                                ("first", HtmlTemplateValue::from(i == 0)),
+                                (
+                                    "last",
+                                    HtmlTemplateValue::from(i + 1 == length),
+                                ),
+                                ("length", HtmlTemplateValue::from(length)),
+                            ]),
+                        );
+
+                        state.scopes.push(scope);
+                        let result = self.render_nodes(body, state);
+                        state.scopes.pop();
+                        result?;
+                    }
+                }
+                Node::Include { name, line } => {
+                    let template = self.load(name, *line, state)?;
+
+                    // Blocks being replaced only apply to the templates
+                    // being extended, not to those included
+                    let blocks = std::mem::take(&mut state.blocks);
+                    state.names.push(name.to_string());
+                    let result = self.render_template(&template, state);
+                    state.names.pop();
+                    state.blocks = blocks;
+                    result?;
+                }
+                Node::Block { name, body } => {
+                    match state.blocks.get(name).cloned() {
+                        Some(body) => self.render_nodes(&body, state)?,
+                        None => self.render_nodes(body, state)?,
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Collects the blocks found within the nodes, keeping any existing block
+/// of the same name
+fn collect_blocks(nodes: &[Node], blocks: &mut HashMap<String, Vec<Node>>) {
+    for node in nodes {
+        if let Node::Block { name, body } = node {
+            blocks
+                .entry(name.to_string())
+                .or_insert_with(|| body.clone());
+            collect_blocks(body, blocks);
+        }
+    }
+}
+
+/// Replaces each `%name%` within the text where `name` is a variable
+fn replace_placeholders<'a>(
+    text: &str,
+    lookup: impl Fn(&str) -> Option<&'a HtmlTemplateValue>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        let value = after.find('%').and_then(|end| {
+            let name = &after[..end];
+            let is_name = !name.is_empty()
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if is_name {
+                lookup(name).map(|value| (end, value))
+            } else {
+                None
+            }
+        });
+
+        match value {
+            Some((end, value)) => {
+                out.push_str(&rest[..start]);
+                out.push_str(&value.to_string());
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[..=start]);
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+struct RenderState {
+    /// Variables available, where later scopes shadow earlier ones
+    scopes: Vec<BTreeMap<String, HtmlTemplateValue>>,
+
+    /// Blocks replacing those of the same name in extended templates
+    blocks: HashMap<String, Vec<Node>>,
+
+    /// Names of the templates being rendered, used to detect cycles
+    names: Vec<String>,
+
+    out: String,
+}
+
+impl RenderState {
+    fn error(&self, line: usize, message: String) -> HtmlTemplateError {
+        HtmlTemplateError {
+            name: self.names.last().cloned(),
+            line,
+            message,
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&HtmlTemplateValue> {
+        self.scopes.iter().rev().find_map(|x| x.get(name))
+    }
+
+    fn write(&mut self, value: &HtmlTemplateValue) {
+        match value {
+            HtmlTemplateValue::Html(x) => self.out.push_str(x),
+            x => self.out.push_str(&escape::escape_html(&x.to_string())),
+        }
+    }
+
+    fn eval(
+        &self,
+        expr: &Expr,
+        line: usize,
+    ) -> Result<HtmlTemplateValue, HtmlTemplateError> {
+        Ok(match expr {
+            Expr::Literal(x) => x.clone(),
+            Expr::Path(path) => {
+                let mut value = self.lookup(&path[0]);
+                for key in path[1..].iter() {
+                    value = value.and_then(|x| x.get(key));
+                }
+                value.cloned().unwrap_or_default()
+            }
+            Expr::Not(x) => (!self.eval(x, line)?.is_truthy()).into(),
+            Expr::And(a, b) => (self.eval(a, line)?.is_truthy()
+                && self.eval(b, line)?.is_truthy())
+            .into(),
+            Expr::Or(a, b) => {
+                let a = self.eval(a, line)?;
+                if a.is_truthy() {
+                    a
+                } else {
+                    self.eval(b, line)?
+                }
+            }
+            Expr::Eq(a, b) => {
+                values_eq(&self.eval(a, line)?, &self.eval(b, line)?).into()
+            }
+            Expr::Ne(a, b) => {
+                (!values_eq(&self.eval(a, line)?, &self.eval(b, line)?)).into()
+            }
+            Expr::Filter { expr, name, args } => {
+                let value = self.eval(expr, line)?;
+                let args = args
+                    .iter()
+                    .map(|x| self.eval(x, line))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.filter(value, name, &args, line)?
+            }
+        })
+    }
+
+    fn filter(
+        &self,
+        value: HtmlTemplateValue,
+        name: &str,
+        args: &[HtmlTemplateValue],
+        line: usize,
+    ) -> Result<HtmlTemplateValue, HtmlTemplateError> {
+        let arg = |i: usize| args.get(i).cloned().unwrap_or_default();
+        Ok(match name {
+            "safe" => match value {
+                HtmlTemplateValue::Html(_) => value,
+                x => HtmlTemplateValue::Html(x.to_string()),
+            },
+            "escape" | "e" => match value {
+                HtmlTemplateValue::Html(_) => value,
+                x => {
+                    HtmlTemplateValue::Html(escape::escape_html(&x.to_string()))
+                }
+            },
+            "upper" => {
+                HtmlTemplateValue::String(value.to_string().to_uppercase())
+            }
+            "lower" => {
+                HtmlTemplateValue::String(value.to_string().to_lowercase())
+            }
+            "trim" => {
+                HtmlTemplateValue::String(value.to_string().trim().to_string())
+            }
+            "length" => value.len().into(),
+            "first" => match value {
+                HtmlTemplateValue::List(x) => {
+                    x.into_iter().next().unwrap_or_default()
+                }
+                _ => HtmlTemplateValue::Null,
+            },
+            "last" => match value {
+                HtmlTemplateValue::List(x) => {
+                    x.into_iter().last().unwrap_or_default()
+                }
+                _ => HtmlTemplateValue::Null,
+            },
+            "join" => match value {
+                HtmlTemplateValue::List(x) => HtmlTemplateValue::String(
+                    x.iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<String>>()
+                        .join(&arg(0).to_string()),
+                ),
+                x => x,
+            },
+            "split" => {
+                let text = value.to_string();
+                let items: Vec<&str> = match arg(0) {
+                    HtmlTemplateValue::Null => {
+                        text.split_whitespace().collect()
+                    }
+                    sep => text
+                        .split(sep.to_string().as_str())
+                        .map(str::trim)
+                        .filter(|x| !x.is_empty())
+                        .collect(),
+                };
+                items.into()
+            }
+            "default" => {
+                if value.is_truthy() {
+                    value
+                } else {
+                    arg(0)
+                }
+            }
+            _ => {
+                return Err(self.error(line, format!("Unknown filter {}", name)))
+            }
+        })
+    }
+}
+
+/// Compares values, treating text and html with the same contents as equal
+fn values_eq(a: &HtmlTemplateValue, b: &HtmlTemplateValue) -> bool {
+    use HtmlTemplateValue::{Html, String};
+    match (a, b) {
+        (String(a), Html(b)) | (Html(a), String(b)) => a == b,
+        (a, b) => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_with(
+        text: &str,
+        templates: &[(&str, &str)],
+        variables: Vec<(&str, HtmlTemplateValue)>,
+    ) -> Result<String, HtmlOutputError> {
+        render_in_mode(text, templates, variables, false)
+    }
+
+    fn render_in_mode(
+        text: &str,
+        templates: &[(&str, &str)],
+        variables: Vec<(&str, HtmlTemplateValue)>,
+        legacy_placeholders: bool,
+    ) -> Result<String, HtmlOutputError> {
+        let templates: HashMap<String, String> = templates
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let variables = variables
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+
+        HtmlTemplateRenderer::new(move |name| {
+            templates.get(name).cloned().ok_or_else(|| {
+                HtmlOutputError::TemplateNotLoaded {
+                    source: std::io::Error::from(std::io::ErrorKind::NotFound),
+                }
+            })
+        })
+        .with_legacy_placeholders(legacy_placeholders)
+        .render(text, &variables)
+    }
+
+    fn render(text: &str, variables: Vec<(&str, HtmlTemplateValue)>) -> String {
+        render_with(text, &[], variables).unwrap()
+    }
+
+    fn template_error(err: HtmlOutputError) -> HtmlTemplateError {
+        match err {
+            HtmlOutputError::TemplateFailed { source } => source,
+            x => panic!("Unexpected error: {}", x),
+        }
+    }
+
+    #[test]
+    fn render_should_escape_values_unless_html_or_safe() {
+        let variables = || {
+            vec![
+                ("text", "<b>&</b>".into()),
+                ("html", HtmlTemplateValue::Html("<b>&</b>".to_string())),
+            ]
+        };
+
+        assert_eq!(
+            render("{{ text }}", variables()),
+            "&lt;b&gt;&amp;&lt;/b&gt;"
+        );
+        assert_eq!(render("{{ text | safe }}", variables()), "<b>&</b>");
+        assert_eq!(render("{{ html }}", variables()), "<b>&</b>");
+        assert_eq!(
+            render("{{ text | e | e }}", variables()),
+            "&lt;b&gt;&amp;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn render_should_replace_legacy_placeholders_without_escaping() {
+        let variables = vec![
+            ("title", "<Title>".into()),
+            ("content", HtmlTemplateValue::Html("%title%".to_string())),
+        ];
+
+        assert_eq!(
+            render_in_mode(
+                "%title% 100% %missing% %content%",
+                &[],
+                variables,
+                true
+            )
+            .unwrap(),
+            "<Title> 100% %missing% %title%"
+        );
+    }
+
+    #[test]
+    fn render_should_only_replace_legacy_placeholders_in_legacy_mode() {
+        let render = |text: &str| {
+            render_in_mode(text, &[], vec![("content", "x".into())], true)
+                .unwrap()
+        };
+
+        assert_eq!(
+            render("<script>var a = {{}};</script>%content%"),
+            "<script>var a = {{}};</script>x"
+        );
+        assert_eq!(
+            render("{{a:1}} {% x %} {# %content%"),
+            "{{a:1}} {% x %} {# x"
+        );
+    }
+
+    #[test]
+    fn render_should_describe_unexpected_tokens_in_errors() {
+        let message = |text: &str| {
+            template_error(render_with(text, &[], Vec::new()).unwrap_err())
+                .message
+        };
+
+        assert_eq!(message("{{}}"), "Unexpected end of expression");
+        assert_eq!(message("{{ a b }}"), "Unexpected `b`");
+        assert_eq!(
+            message("{{ a | }}"),
+            "Expected name, but got end of expression"
+        );
+        assert_eq!(message("{{ a:1 }}"), "Unexpected `:` in expression");
+    }
+
+    #[test]
+    fn render_should_support_conditionals() {
+        let template = "{% if a and not b %}1{% elif a == 'x' or c %}2\
+                        {% else %}3{% endif %}";
+        let variables = |a: &str, b: bool, c: bool| {
+            vec![("a", a.into()), ("b", b.into()), ("c", c.into())]
+        };
+
+        assert_eq!(render(template, variables("x", false, false)), "1");
+        assert_eq!(render(template, variables("x", true, false)), "2");
+        assert_eq!(render(template, variables("", true, true)), "2");
+        assert_eq!(render(template, variables("", true, false)), "3");
+    }
+
+    #[test]
+    fn render_should_support_loops_over_lists_and_maps() {
+        let variables = || {
+            vec![
+                ("tags", vec!["a", "b", "c"].into()),
+                ("empty", HtmlTemplateValue::List(Vec::new())),
+                (
+                    "meta",
+                    HtmlTemplateValue::map(vec![("k1", "v1"), ("k2", "v2")]),
+                ),
+            ]
+        };
+
+        assert_eq!(
+            render(
+                "{% for t in tags %}{{ loop.index }}{{ t }}\
+                 {% if not loop.last %},{% endif %}{% endfor %}",
+                variables()
+            ),
+            "1a,2b,3c"
+        );
+        assert_eq!(
+            render(
+                "{% for t in empty %}{{ t }}{% else %}none{% endfor %}",
+                variables()
+            ),
+            "none"
+        );
+        assert_eq!(
+            render(
+                "{% for x in meta %}{{ x.key }}={{ x.value }};{% endfor %}",
+                variables()
+            ),
+            "k1=v1;k2=v2;"
+        );
+    }
+
+    #[test]
+    fn render_should_support_filters() {
+        let variables =
+            || vec![("tags", vec!["a", "b"].into()), ("name", " Name ".into())];
+
+        assert_eq!(
+            render("{{ tags | join(', ') | upper }}", variables()),
+            "A, B"
+        );
+        assert_eq!(render("{{ tags | length }}", variables()), "2");
+        assert_eq!(render("{{ tags | last }}", variables()), "b");
+        assert_eq!(
+            render("[{{ name | trim | lower }}]", variables()),
+            "[name]"
+        );
+        assert_eq!(render("{{ missing | default('x') }}", variables()), "x");
+        assert_eq!(
+            render(
+                "{{ name | split | length }}{{ 'a, b,' | split(',') | last }}",
+                variables()
+            ),
+            "1b"
+        );
+
+        let err =
+            render_with("\n{{ name | nope }}", &[], variables()).unwrap_err();
+        assert_eq!(template_error(err).line, 2);
+    }
+
+    #[test]
+    fn render_should_support_includes_and_inheritance() {
+        let templates = [
+            (
+                "base",
+                "<title>{% block title %}Default{% endblock %}</title>\
+                 {% include 'nav' %}\
+                 <main>{% block main %}{% endblock %}</main>",
+            ),
+            ("nav", "<nav>{{ title }}</nav>"),
+            (
+                "page",
+                "{% extends 'base' %}{% block main %}page{% endblock %}",
+            ),
+        ];
+
+        assert_eq!(
+            render_with(
+                "{% extends 'page' %}\
+                 {% block title %}{{ title }}{% endblock %}\
+                 ignored",
+                &templates,
+                vec![("title", "T".into())],
+            )
+            .unwrap(),
+            "<title>T</title><nav>T</nav><main>page</main>"
+        );
+    }
+
+    #[test]
+    fn render_should_fail_if_templates_refer_to_themselves() {
+        let templates =
+            [("a", "{% include 'b' %}"), ("b", "\n{% include 'a' %}")];
+        let err = template_error(
+            render_with("{% include 'a' %}", &templates, Vec::new())
+                .unwrap_err(),
+        );
+        assert_eq!(err.name.as_deref(), Some("b"));
+        assert_eq!(err.line, 2);
+
+        let templates = [("a", "{% extends 'a' %}")];
+        assert!(
+            render_with("{% extends 'a' %}", &templates, Vec::new()).is_err()
+        );
+    }
+
+    #[test]
+    fn render_should_report_the_template_with_a_parse_error() {
+        let templates = [("a", "{{ x")];
+        let err = template_error(
+            render_with("{% include 'a' %}", &templates, Vec::new())
+                .unwrap_err(),
+        );
+        assert_eq!(err.name.as_deref(), Some("a"));
+    }
+}
@@ -0,0 +1,751 @@
+use super::{HtmlTemplateError, HtmlTemplateValue};
+use std::fmt;
+
+/// Represents a template parsed into nodes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    /// Name of the template that this one extends, if any
+    pub extends: Option<String>,
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Text(String),
+    Output {
+        expr: Expr,
+        line: usize,
+    },
+    If {
+        branches: Vec<(Expr, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        name: String,
+        iter: Expr,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Include {
+        name: String,
+        line: usize,
+    },
+    Block {
+        name: String,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Literal(HtmlTemplateValue),
+    Path(Vec<String>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Eq(Box<Expr>, Box<Expr>),
+    Ne(Box<Expr>, Box<Expr>),
+    Filter {
+        expr: Box<Expr>,
+        name: String,
+        args: Vec<Expr>,
+    },
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Self, HtmlTemplateError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens: tokens.into_iter().peekable(),
+            extends: None,
+        };
+
+        let (nodes, end) = parser.nodes(&[])?;
+        debug_assert!(end.is_none());
+
+        Ok(Self {
+            extends: parser.extends,
+            nodes,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Text(String),
+    Expr { src: String, line: usize },
+    Tag { src: String, line: usize },
+}
+
+/// Splits template text into text, `{{ expressions }}` and `{% tags %}`,
+/// dropping `{# comments #}` and applying `-` whitespace control
+fn tokenize(text: &str) -> Result<Vec<Token>, HtmlTemplateError> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut trim_next = false;
+    let line_at = |pos: usize| 1 + text[..pos].matches('\n').count();
+
+    loop {
+        let next = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|x| text[pos..].find(x).map(|i| pos + i))
+            .min();
+
+        let end = next.unwrap_or(text.len());
+        let mut literal = &text[pos..end];
+        if trim_next {
+            literal = literal.trim_start();
+        }
+        if let Some(start) = next {
+            if text[start + 2..].starts_with('-') {
+                literal = literal.trim_end();
+            }
+        }
+        if !literal.is_empty() {
+            tokens.push(Token::Text(literal.to_string()));
+        }
+
+        let start = match next {
+            Some(start) => start,
+            None => break,
+        };
+        let line = line_at(start);
+        let close = match &text[start..start + 2] {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let inner_start = start + 2;
+        let inner_end = text[inner_start..]
+            .find(close)
+            .map(|i| inner_start + i)
+            .ok_or_else(|| {
+                HtmlTemplateError::new(
+                    line,
+                    format!("Missing closing {}", close),
+                )
+            })?;
+        pos = inner_end + 2;
+
+        let inner = &text[inner_start..inner_end];
+        trim_next = inner.ends_with('-');
+        let src = inner.trim_start_matches('-').trim_end_matches('-').trim();
+
+        match close {
+            "}}" => tokens.push(Token::Expr {
+                src: src.to_string(),
+                line,
+            }),
+            "%}" if src == "raw" => {
+                // Everything until the matching endraw is kept as-is
+                let (raw, end) = find_endraw(text, pos).ok_or_else(|| {
+                    HtmlTemplateError::new(line, "Missing endraw".to_string())
+                })?;
+                if !raw.is_empty() {
+                    tokens.push(Token::Text(raw.to_string()));
+                }
+                pos = end;
+                trim_next = false;
+            }
+            "%}" => tokens.push(Token::Tag {
+                src: src.to_string(),
+                line,
+            }),
+            _ => {}
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Finds the text before the next `{% endraw %}`, returning it alongside
+/// the position after the tag
+fn find_endraw(text: &str, pos: usize) -> Option<(&str, usize)> {
+    let mut search = pos;
+    while let Some(i) = text[search..].find("{%") {
+        let start = search + i;
+        let end = start + 2 + text[start + 2..].find("%}")?;
+        let inner = text[start + 2..end].trim_matches('-').trim();
+        if inner == "endraw" {
+            return Some((&text[pos..start], end + 2));
+        }
+        search = start + 2;
+    }
+    None
+}
+
+/// Represents a tag that ended a sequence of nodes
+struct EndTag {
+    keyword: String,
+    rest: String,
+    line: usize,
+}
+
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+    extends: Option<String>,
+}
+
+impl Parser {
+    /// Parses nodes until reaching a tag starting with one of the given
+    /// keywords, returning the nodes alongside the keyword, the rest of the
+    /// tag and its line
+    fn nodes(
+        &mut self,
+        until: &[&str],
+    ) -> Result<(Vec<Node>, Option<EndTag>), HtmlTemplateError> {
+        let mut nodes = Vec::new();
+
+        while let Some(token) = self.tokens.next() {
+            let (src, line) = match token {
+                Token::Text(x) => {
+                    nodes.push(Node::Text(x));
+                    continue;
+                }
+                Token::Expr { src, line } => {
+                    nodes.push(Node::Output {
+                        expr: parse_expr(&src, line)?,
+                        line,
+                    });
+                    continue;
+                }
+                Token::Tag { src, line } => (src, line),
+            };
+
+            let (keyword, rest) = match src.find(char::is_whitespace) {
+                Some(i) => (&src[..i], src[i..].trim()),
+                None => (src.as_str(), ""),
+            };
+
+            if until.contains(&keyword) {
+                let end = EndTag {
+                    keyword: keyword.to_string(),
+                    rest: rest.to_string(),
+                    line,
+                };
+                return Ok((nodes, Some(end)));
+            }
+
+            match keyword {
+                "if" => nodes.push(self.if_node(rest, line)?),
+                "for" => nodes.push(self.for_node(rest, line)?),
+                "block" => {
+                    let name = parse_name(rest, line)?;
+                    let (body, _) = self.expect_end(&["endblock"], line)?;
+                    nodes.push(Node::Block { name, body });
+                }
+                "include" => nodes.push(Node::Include {
+                    name: parse_string(rest, line)?,
+                    line,
+                }),
+                "extends" => {
+                    let only_whitespace = nodes.iter().all(
+                        |x| matches!(x, Node::Text(x) if x.trim().is_empty()),
+                    );
+                    if self.extends.is_some() || !only_whitespace {
+                        return Err(HtmlTemplateError::new(
+                            line,
+                            "extends must be the first tag of a template"
+                                .to_string(),
+                        ));
+                    }
+                    self.extends = Some(parse_string(rest, line)?);
+                }
+                _ => {
+                    return Err(HtmlTemplateError::new(
+                        line,
+                        format!("Unexpected tag {}", keyword),
+                    ))
+                }
+            }
+        }
+
+        Ok((nodes, None))
+    }
+
+    /// Parses nodes until one of the keywords, failing if the template ends
+    fn expect_end(
+        &mut self,
+        until: &[&str],
+        line: usize,
+    ) -> Result<(Vec<Node>, EndTag), HtmlTemplateError> {
+        match self.nodes(until)? {
+            (nodes, Some(end)) => Ok((nodes, end)),
+            (_, None) => Err(HtmlTemplateError::new(
+                line,
+                format!(
+                    "Missing {}",
+                    until.last().copied().unwrap_or_default()
+                ),
+            )),
+        }
+    }
+
+    fn if_node(
+        &mut self,
+        cond: &str,
+        line: usize,
+    ) -> Result<Node, HtmlTemplateError> {
+        let mut branches = Vec::new();
+        let mut otherwise = Vec::new();
+        let mut cond = parse_expr(cond, line)?;
+
+        loop {
+            let (
+                body,
+                EndTag {
+                    keyword,
+                    rest,
+                    line,
+                },
+            ) = self.expect_end(&["elif", "else", "endif"], line)?;
+            branches.push((cond, body));
+
+            match keyword.as_str() {
+                "elif" => cond = parse_expr(&rest, line)?,
+                "else" => {
+                    otherwise = self.expect_end(&["endif"], line)?.0;
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        Ok(Node::If {
+            branches,
+            otherwise,
+        })
+    }
+
+    fn for_node(
+        &mut self,
+        src: &str,
+        line: usize,
+    ) -> Result<Node, HtmlTemplateError> {
+        let (name, iter) = match src.split_once(" in ") {
+            Some((name, iter)) => (parse_name(name, line)?, iter),
+            None => {
+                return Err(HtmlTemplateError::new(
+                    line,
+                    "Expected for {name} in {expression}".to_string(),
+                ))
+            }
+        };
+        let iter = parse_expr(iter, line)?;
+
+        let (body, EndTag { keyword, line, .. }) =
+            self.expect_end(&["else", "endfor"], line)?;
+        let otherwise = if keyword == "else" {
+            self.expect_end(&["endfor"], line)?.0
+        } else {
+            Vec::new()
+        };
+
+        Ok(Node::For {
+            name,
+            iter,
+            body,
+            otherwise,
+        })
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+fn parse_name(src: &str, line: usize) -> Result<String, HtmlTemplateError> {
+    let src = src.trim();
+    if is_ident(src) {
+        Ok(src.to_string())
+    } else {
+        Err(HtmlTemplateError::new(
+            line,
+            format!("Invalid name {:?}", src),
+        ))
+    }
+}
+
+fn parse_string(src: &str, line: usize) -> Result<String, HtmlTemplateError> {
+    match parse_expr(src, line)? {
+        Expr::Literal(HtmlTemplateValue::String(x)) => Ok(x),
+        _ => Err(HtmlTemplateError::new(
+            line,
+            format!("Expected quoted name, but got {:?}", src),
+        )),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ExprToken {
+    Ident(String),
+    Str(String),
+    Num(i64),
+    Sym(&'static str),
+}
+
+impl fmt::Display for ExprToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(x) => write!(f, "`{}`", x),
+            Self::Str(x) => write!(f, "string \"{}\"", x),
+            Self::Num(x) => write!(f, "number {}", x),
+            Self::Sym(x) => write!(f, "`{}`", x),
+        }
+    }
+}
+
+/// Describes the token for an error, where no token is the end of the
+/// expression
+fn describe(token: Option<&ExprToken>) -> String {
+    match token {
+        Some(x) => x.to_string(),
+        None => "end of expression".to_string(),
+    }
+}
+
+fn tokenize_expr(
+    src: &str,
+    line: usize,
+) -> Result<Vec<ExprToken>, HtmlTemplateError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '"' | '\'' => {
+                let mut value = String::new();
+                let mut closed = false;
+                while let Some((_, x)) = chars.next() {
+                    match x {
+                        '\\' => value.extend(chars.next().map(|x| x.1)),
+                        x if x == c => {
+                            closed = true;
+                            break;
+                        }
+                        x => value.push(x),
+                    }
+                }
+                if !closed {
+                    return Err(HtmlTemplateError::new(
+                        line,
+                        format!("Missing closing {}", c),
+                    ));
+                }
+                tokens.push(ExprToken::Str(value));
+            }
+            '=' | '!' => {
+                if chars.next_if(|x| x.1 == '=').is_none() {
+                    return Err(HtmlTemplateError::new(
+                        line,
+                        format!("Expected = after {}", c),
+                    ));
+                }
+                tokens.push(ExprToken::Sym(if c == '=' { "==" } else { "!=" }));
+            }
+            '.' => tokens.push(ExprToken::Sym(".")),
+            '|' => tokens.push(ExprToken::Sym("|")),
+            '(' => tokens.push(ExprToken::Sym("(")),
+            ')' => tokens.push(ExprToken::Sym(")")),
+            ',' => tokens.push(ExprToken::Sym(",")),
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut end = i + c.len_utf8();
+                while let Some((j, x)) = chars.next_if(|x| x.1.is_ascii_digit())
+                {
+                    end = j + x.len_utf8();
+                }
+                let num = src[i..end].parse().map_err(|_| {
+                    HtmlTemplateError::new(
+                        line,
+                        format!("Invalid number {}", &src[i..end]),
+                    )
+                })?;
+                tokens.push(ExprToken::Num(num));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = i + c.len_utf8();
+                while let Some((j, x)) = chars.next_if(|x| {
+                    x.1.is_alphanumeric() || x.1 == '_' || x.1 == '-'
+                }) {
+                    end = j + x.len_utf8();
+                }
+                tokens.push(ExprToken::Ident(src[i..end].to_string()));
+            }
+            c => {
+                return Err(HtmlTemplateError::new(
+                    line,
+                    format!("Unexpected `{}` in expression", c),
+                ))
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_expr(src: &str, line: usize) -> Result<Expr, HtmlTemplateError> {
+    let mut parser = ExprParser {
+        tokens: tokenize_expr(src, line)?,
+        pos: 0,
+        line,
+    };
+    let expr = parser.or()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        token => Err(parser.error(format!("Unexpected {}", describe(token)))),
+    }
+}
+
+/// Parses expressions where `or` binds loosest, followed by `and`, `not`,
+/// comparisons and finally filters
+struct ExprParser {
+    tokens: Vec<ExprToken>,
+    pos: usize,
+    line: usize,
+}
+
+impl ExprParser {
+    fn error(&self, message: String) -> HtmlTemplateError {
+        HtmlTemplateError::new(self.line, message)
+    }
+
+    fn peek(&self) -> Option<&ExprToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_sym(&mut self, sym: &str) -> bool {
+        if matches!(self.peek(), Some(ExprToken::Sym(x)) if *x == sym) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(ExprToken::Ident(x)) if x == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, HtmlTemplateError> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(ExprToken::Ident(x)) => {
+                self.pos += 1;
+                Ok(x)
+            }
+            x => Err(self.error(format!(
+                "Expected name, but got {}",
+                describe(x.as_ref())
+            ))),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, HtmlTemplateError> {
+        let mut expr = self.and()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, HtmlTemplateError> {
+        let mut expr = self.not()?;
+        while self.eat_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, HtmlTemplateError> {
+        if self.eat_keyword("not") {
+            Ok(Expr::Not(Box::new(self.not()?)))
+        } else {
+            self.comparison()
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, HtmlTemplateError> {
+        let expr = self.filtered()?;
+        if self.eat_sym("==") {
+            Ok(Expr::Eq(Box::new(expr), Box::new(self.filtered()?)))
+        } else if self.eat_sym("!=") {
+            Ok(Expr::Ne(Box::new(expr), Box::new(self.filtered()?)))
+        } else {
+            Ok(expr)
+        }
+    }
+
+    fn filtered(&mut self) -> Result<Expr, HtmlTemplateError> {
+        let mut expr = self.primary()?;
+        while self.eat_sym("|") {
+            let name = self.ident()?;
+            let mut args = Vec::new();
+            if self.eat_sym("(") && !self.eat_sym(")") {
+                loop {
+                    args.push(self.or()?);
+                    if self.eat_sym(")") {
+                        break;
+                    } else if !self.eat_sym(",") {
+                        return Err(self.error("Expected , or )".to_string()));
+                    }
+                }
+            }
+            expr = Expr::Filter {
+                expr: Box::new(expr),
+                name,
+                args,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, HtmlTemplateError> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+
+        match token {
+            Some(ExprToken::Str(x)) => {
+                Ok(Expr::Literal(HtmlTemplateValue::String(x)))
+            }
+            Some(ExprToken::Num(x)) => {
+                Ok(Expr::Literal(HtmlTemplateValue::Number(x)))
+            }
+            Some(ExprToken::Sym("(")) => {
+                let expr = self.or()?;
+                if self.eat_sym(")") {
+                    Ok(expr)
+                } else {
+                    Err(self.error("Expected )".to_string()))
+                }
+            }
+            Some(ExprToken::Ident(x)) => match x.as_str() {
+                "true" => Ok(Expr::Literal(HtmlTemplateValue::Bool(true))),
+                "false" => Ok(Expr::Literal(HtmlTemplateValue::Bool(false))),
+                "none" => Ok(Expr::Literal(HtmlTemplateValue::Null)),
+                _ => {
+                    let mut path = vec![x];
+                    while self.eat_sym(".") {
+                        path.push(self.ident()?);
+                    }
+                    Ok(Expr::Path(path))
+                }
+            },
+            x => {
+                Err(self.error(format!("Unexpected {}", describe(x.as_ref()))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(x: &str) -> Expr {
+        Expr::Path(x.split('.').map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn parse_should_support_whitespace_control() {
+        let template = Template::parse("a  {{- x -}}  b {#- c -#} d").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![
+                Node::Text("a".to_string()),
+                Node::Output {
+                    expr: path("x"),
+                    line: 1
+                },
+                Node::Text("b".to_string()),
+                Node::Text("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_keep_raw_sections_as_text() {
+        let template =
+            Template::parse("{% raw %}{{ x }} {% if %}{% endraw %}").unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![Node::Text("{{ x }} {% if %}".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_should_support_expressions_with_precedence() {
+        let template =
+            Template::parse(r#"{{ not a.b == "c" or d | join(", ") }}"#)
+                .unwrap();
+        assert_eq!(
+            template.nodes,
+            vec![Node::Output {
+                expr: Expr::Or(
+                    Box::new(Expr::Not(Box::new(Expr::Eq(
+                        Box::new(path("a.b")),
+                        Box::new(Expr::Literal("c".into())),
+                    )))),
+                    Box::new(Expr::Filter {
+                        expr: Box::new(path("d")),
+                        name: "join".to_string(),
+                        args: vec![Expr::Literal(", ".into())],
+                    }),
+                ),
+                line: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_should_support_nested_tags() {
+        let template = Template::parse(
+            "{% extends 'base' %}\n\
+             {% block body %}\
+             {% for x in xs %}{% if x %}y{% elif z %}w{% else %}n{% endif %}\
+             {% else %}empty{% endfor %}\
+             {% endblock %}",
+        )
+        .unwrap();
+        assert_eq!(template.extends.as_deref(), Some("base"));
+        assert_eq!(
+            template.nodes[1],
+            Node::Block {
+                name: "body".to_string(),
+                body: vec![Node::For {
+                    name: "x".to_string(),
+                    iter: path("xs"),
+                    body: vec![Node::If {
+                        branches: vec![
+                            (path("x"), vec![Node::Text("y".to_string())]),
+                            (path("z"), vec![Node::Text("w".to_string())]),
+                        ],
+                        otherwise: vec![Node::Text("n".to_string())],
+                    }],
+                    otherwise: vec![Node::Text("empty".to_string())],
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_should_report_line_of_errors() {
+        let err = Template::parse("a\nb\n{% if x %}").unwrap_err();
+        assert_eq!(err.line, 3);
+
+        let err = Template::parse("a\n{{ x ==").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        let err = Template::parse("{% endfor %}").unwrap_err();
+        assert_eq!(err.line, 1);
+
+        let err = Template::parse("a {% extends 'x' %}").unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+use std::{collections::BTreeMap, fmt};
+
+/// Represents a value that can be referenced from within a template
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum HtmlTemplateValue {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(i64),
+
+    /// Text that is escaped when written into a template
+    String(String),
+
+    /// Text that is already html and written into a template as-is
+    Html(String),
+
+    List(Vec<HtmlTemplateValue>),
+    Map(BTreeMap<String, HtmlTemplateValue>),
+}
+
+impl HtmlTemplateValue {
+    /// Creates a new map value from pairs of keys and values
+    pub fn map<K, V, I>(iter: I) -> Self
+    where
+        K: Into<String>,
+        V: Into<HtmlTemplateValue>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        Self::Map(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+
+    /// Returns true if the value is considered true within a condition,
+    /// which is everything but null, false, zero, and empty text, lists
+    /// and maps
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Null => false,
+            Self::Bool(x) => *x,
+            Self::Number(x) => *x != 0,
+            Self::String(x) | Self::Html(x) => !x.is_empty(),
+            Self::List(x) => !x.is_empty(),
+            Self::Map(x) => !x.is_empty(),
+        }
+    }
+
+    /// Returns the value of a field if this is a map
+    pub fn get(&self, key: &str) -> Option<&HtmlTemplateValue> {
+        match self {
+            Self::Map(x) => x.get(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of items, characters or fields in the value
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Null | Self::Bool(_) | Self::Number(_) => 0,
+            Self::String(x) | Self::Html(x) => x.chars().count(),
+            Self::List(x) => x.len(),
+            Self::Map(x) => x.len(),
+        }
+    }
+
+    /// Returns true if the value has no items, characters or fields
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for HtmlTemplateValue {
+    /// Writes out the value as text without any escaping, where lists are
+    /// joined by commas and maps write nothing
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null | Self::Map(_) => Ok(()),
+            Self::Bool(x) => write!(f, "{}", x),
+            Self::Number(x) => write!(f, "{}", x),
+            Self::String(x) | Self::Html(x) => write!(f, "{}", x),
+            Self::List(x) => {
+                for (i, value) in x.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<bool> for HtmlTemplateValue {
+    fn from(x: bool) -> Self {
+        Self::Bool(x)
+    }
+}
+
+impl From<i64> for HtmlTemplateValue {
+    fn from(x: i64) -> Self {
+        Self::Number(x)
+    }
+}
+
+impl From<usize> for HtmlTemplateValue {
+    fn from(x: usize) -> Self {
+        Self::Number(x as i64)
+    }
+}
+
+impl From<&str> for HtmlTemplateValue {
+    fn from(x: &str) -> Self {
+        Self::String(x.to_string())
+    }
+}
+
+impl From<String> for HtmlTemplateValue {
+    fn from(x: String) -> Self {
+        Self::String(x)
+    }
+}
+
+impl From<NaiveDate> for HtmlTemplateValue {
+    fn from(x: NaiveDate) -> Self {
+        Self::String(x.to_string())
+    }
+}
+
+impl<T: Into<HtmlTemplateValue>> From<Option<T>> for HtmlTemplateValue {
+    fn from(x: Option<T>) -> Self {
+        x.map(Into::into).unwrap_or_default()
+    }
+}
+
+impl<T: Into<HtmlTemplateValue>> From<Vec<T>> for HtmlTemplateValue {
+    fn from(x: Vec<T>) -> Self {
+        Self::List(x.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<HtmlTemplateValue>> From<BTreeMap<String, T>>
+    for HtmlTemplateValue
+{
+    fn from(x: BTreeMap<String, T>) -> Self {
+        Self::map(x)
+    }
+}
@@ -0,0 +1,670 @@
+use super::{
+    sanitize, utils, HtmlFormatter, HtmlOutputError, HtmlOutputResult,
+    HtmlStripped, HtmlTransclusionConfig, HtmlTransclusionElementConfig,
+};
+use crate::lang::{elements::*, output::Output, Language};
+use std::{
+    ffi::OsStr,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+use uriparse::{Scheme, URIReference};
+use voca_rs::escape;
+
+/// Attributes that are written without a value when enabled by a property,
+/// and left out when the property is `false`, `no`, `off` or `0`
+const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "allowfullscreen",
+    "autoplay",
+    "controls",
+    "loop",
+    "muted",
+    "playsinline",
+];
+
+/// Represents the elements that transclusions are written as
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HtmlTransclusionElement {
+    Img,
+    Video,
+    Audio,
+    Iframe,
+    Object,
+}
+
+impl HtmlTransclusionElement {
+    /// Returns the name of the tag of the element
+    pub fn as_tag(self) -> &'static str {
+        match self {
+            Self::Img => "img",
+            Self::Video => "video",
+            Self::Audio => "audio",
+            Self::Iframe => "iframe",
+            Self::Object => "object",
+        }
+    }
+}
+
+impl HtmlTransclusionConfig {
+    /// Returns the element to write a transclusion of the uri as, based on
+    /// the extension of its path or the media type of a `data:` uri, falling
+    /// back to an image when no other element matches
+    pub fn element_for(
+        &self,
+        uri: &URIReference<'_>,
+    ) -> HtmlTransclusionElement {
+        let elements = [
+            HtmlTransclusionElement::Video,
+            HtmlTransclusionElement::Audio,
+            HtmlTransclusionElement::Iframe,
+            HtmlTransclusionElement::Object,
+        ];
+
+        // NOTE: Only one of the extension and media type is ever non-empty,
+        //       and empty values never match
+        let (ext, media_type) = match uri.scheme() {
+            Some(Scheme::Data) => {
+                let path = uri.path().to_string();
+                let media_type = path
+                    .split(&[';', ','][..])
+                    .next()
+                    .unwrap_or_default()
+                    .to_lowercase();
+                (String::new(), media_type)
+            }
+            _ => {
+                let ext = uri
+                    .path()
+                    .segments()
+                    .last()
+                    .and_then(|x| x.as_str().rsplit_once('.'))
+                    .map(|(_, ext)| ext.to_lowercase())
+                    .unwrap_or_default();
+                (ext, String::new())
+            }
+        };
+
+        elements
+            .iter()
+            .copied()
+            .find(|x| {
+                self.element_config(*x).is_some_and(|x| {
+                    x.matches_extension(&ext)
+                        || x.matches_media_type(&media_type)
+                })
+            })
+            .unwrap_or(HtmlTransclusionElement::Img)
+    }
+
+    /// Returns the config of the element, or None for images as they are
+    /// what transclusions are written as when matching no other element
+    pub fn element_config(
+        &self,
+        element: HtmlTransclusionElement,
+    ) -> Option<&HtmlTransclusionElementConfig> {
+        match element {
+            HtmlTransclusionElement::Img => None,
+            HtmlTransclusionElement::Video => Some(&self.video),
+            HtmlTransclusionElement::Audio => Some(&self.audio),
+            HtmlTransclusionElement::Iframe => Some(&self.iframe),
+            HtmlTransclusionElement::Object => Some(&self.object),
+        }
+    }
+
+    /// Returns true if a property with the given name is a valid attribute
+    /// of the element, which is always the case for images
+    pub fn is_attribute_valid(
+        &self,
+        element: HtmlTransclusionElement,
+        name: &str,
+    ) -> bool {
+        match self.element_config(element) {
+            Some(config) => {
+                matches_any(&self.global_attributes, name)
+                    || matches_any(&config.attributes, name)
+            }
+            None => true,
+        }
+    }
+}
+
+impl HtmlTransclusionElementConfig {
+    /// Returns true if a path with the given extension is written as the
+    /// element
+    pub fn matches_extension(&self, ext: &str) -> bool {
+        !ext.is_empty()
+            && self.extensions.iter().any(|x| x.eq_ignore_ascii_case(ext))
+    }
+
+    /// Returns true if a `data:` uri with the given media type is written as
+    /// the element
+    pub fn matches_media_type(&self, media_type: &str) -> bool {
+        !media_type.is_empty() && matches_any(&self.media_types, media_type)
+    }
+}
+
+/// Returns true if the name matches any of the patterns ignoring case, where
+/// a pattern ending in `*` matches any name starting with what comes before
+fn matches_any(patterns: &[String], name: &str) -> bool {
+    let name = name.to_lowercase();
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.to_lowercase();
+        match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        }
+    })
+}
+
+/// Writes the element of a transclusion that is not a wiki page, where the
+/// description becomes the alternative text of an image, the title of an
+/// iframe or the fallback content of other elements
+pub(super) fn write_element<'a>(
+    f: &mut HtmlFormatter,
+    element: HtmlTransclusionElement,
+    src: &str,
+    description: Option<&Description>,
+    properties: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> HtmlOutputResult {
+    let tag = element.as_tag();
+    let description =
+        description.map(|x| escape::escape_html(x.to_string().as_str()));
+
+    if element == HtmlTransclusionElement::Img {
+        write!(f, "<img src=\"{}\"", src)?;
+
+        if let Some(desc) = description {
+            write!(f, " alt=\"{}\"", desc)?;
+        }
+
+        sanitize::write_attributes(f, tag, properties)?;
+
+        write!(f, " />")?;
+        return Ok(());
+    }
+
+    let properties: Vec<(&str, &str)> = properties.into_iter().collect();
+    let has_property = |name: &str| {
+        properties.iter().any(|(k, _)| k.eq_ignore_ascii_case(name))
+    };
+
+    let src_name = if element == HtmlTransclusionElement::Object {
+        "data"
+    } else {
+        "src"
+    };
+    write!(f, "<{} {}=\"{}\"", tag, src_name, src)?;
+
+    let is_media = matches!(
+        element,
+        HtmlTransclusionElement::Video | HtmlTransclusionElement::Audio
+    );
+    if is_media
+        && f.config().transclusion.media_controls
+        && !has_property("controls")
+    {
+        write!(f, " controls")?;
+    }
+
+    if element == HtmlTransclusionElement::Iframe && !has_property("title") {
+        if let Some(desc) = description.as_deref() {
+            write!(f, " title=\"{}\"", desc)?;
+        }
+    }
+
+    let mut attributes = Vec::new();
+    for (name, value) in properties.iter().copied() {
+        let is_valid =
+            f.config().transclusion.is_attribute_valid(element, name);
+        let is_boolean = BOOLEAN_ATTRIBUTES
+            .iter()
+            .any(|x| x.eq_ignore_ascii_case(name));
+
+        if !is_valid
+            || (is_boolean && !f.config().sanitize.is_attribute_allowed(name))
+        {
+            f.add_stripped(HtmlStripped::Attribute {
+                element: tag.to_string(),
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        } else if is_boolean {
+            let is_enabled = !matches!(
+                value.trim().to_lowercase().as_str(),
+                "false" | "no" | "off" | "0"
+            );
+            if is_enabled {
+                write!(f, " {}", name.to_lowercase())?;
+            }
+        } else {
+            attributes.push((name, value));
+        }
+    }
+    sanitize::write_attributes(f, tag, attributes)?;
+
+    write!(f, ">")?;
+    if element != HtmlTransclusionElement::Iframe {
+        if let Some(desc) = description {
+            write!(f, "{}", desc)?;
+        }
+    }
+    write!(f, "</{}>", tag)?;
+
+    Ok(())
+}
+
+/// Returns the path to the wiki page targeted by a transclusion, or None if
+/// the target is not an existing page of the current wiki's extension (such
+/// as an image), falls outside of the root of the wiki, or there is no
+/// active wiki to contain it
+///
+/// Like other transclusions, an absolute target is relative to the root of
+/// the wiki while a relative target is relative to the directory of the page
+/// containing the transclusion, and a target without an extension uses the
+/// extension of the wiki
+pub(super) fn find_page(
+    f: &HtmlFormatter,
+    data: &LinkData<'_>,
+) -> Option<PathBuf> {
+    if !data.is_local() || data.is_local_anchor() || data.is_path_dir() {
+        return None;
+    }
+
+    let wiki = f.config().find_active_wiki()?;
+    let path = data.to_path_buf();
+    let path = if data.uri_ref.path().is_absolute() {
+        wiki.path.join(utils::make_path_relative(path))
+    } else {
+        f.current_page()
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(path)
+    };
+    let path = utils::normalize_path(&path);
+    wiki.path_within(&path)?;
+
+    let path = match path.extension().and_then(OsStr::to_str) {
+        Some(ext) if ext == wiki.ext => path,
+        Some(_) => return None,
+        None => path.with_extension(&wiki.ext),
+    };
+
+    if path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Writes the content of the wiki page at the given path, limited to the
+/// section under the header named by the anchor if provided
+///
+/// Placeholders of the page are skipped so that they do not change the
+/// title, date or template of the page containing the transclusion
+///
+/// Everything else is written as if it were part of the page containing the
+/// transclusion, so headers and tags of the transcluded page are added to
+/// its table of contents and tags, and their ids are made unique alongside
+/// the ids of the page (e.g. `footer-1`)
+pub(super) fn write_page(
+    f: &mut HtmlFormatter,
+    path: PathBuf,
+    anchor: Option<&Anchor<'_>>,
+) -> HtmlOutputResult {
+    if utils::normalize_path(f.config().active_page()) == path
+        || f.get_transclusions().contains(&path)
+    {
+        return Err(HtmlOutputError::TransclusionCycle(path));
+    }
+
+    let max_depth = f.config().transclusion.max_depth;
+    if f.get_transclusions().len() >= max_depth {
+        return Err(HtmlOutputError::TransclusionTooDeep { path, max_depth });
+    }
+
+    let text = fs::read_to_string(&path).map_err(|source| {
+        HtmlOutputError::TransclusionNotLoaded {
+            path: path.clone(),
+            source,
+        }
+    })?;
+    let page: Page = Language::from_vimwiki_str(&text).parse().map_err(
+        |x: crate::ParseError| HtmlOutputError::TransclusionNotParsed {
+            path: path.clone(),
+            message: x.to_string(),
+        },
+    )?;
+
+    let elements = match anchor {
+        Some(anchor) => find_section(&page, anchor).ok_or_else(|| {
+            HtmlOutputError::TransclusionSectionMissing {
+                path: path.clone(),
+                anchor: anchor.to_encoded_uri_fragment(),
+            }
+        })?,
+        None => page.elements(),
+    };
+
+    f.push_transclusion(path);
+    let result = write_elements(f, elements);
+    f.pop_transclusion();
+    result
+}
+
+fn write_elements(
+    f: &mut HtmlFormatter,
+    elements: &[Located<BlockElement<'_>>],
+) -> HtmlOutputResult {
+    write!(f, "<div class=\"transclusion\">")?;
+
+    let mut is_first = true;
+    for element in elements {
+        if matches!(element.as_inner(), BlockElement::Placeholder(_)) {
+            continue;
+        }
+
+        if !is_first {
+            writeln!(f)?;
+        }
+        is_first = false;
+
+        element.as_inner().fmt(f)?;
+    }
+
+    write!(f, "</div>")?;
+    Ok(())
+}
+
+/// Finds the elements of the section that starts with the header whose text
+/// matches the last part of the anchor, ending before the next header of the
+/// same or higher level
+fn find_section<'a, 'b>(
+    page: &'b Page<'a>,
+    anchor: &Anchor<'_>,
+) -> Option<&'b [Located<BlockElement<'a>>]> {
+    let text = anchor
+        .iter()
+        .last()
+        .map(|x| LinkData::decode_uri(x.as_bytes()))?;
+    let elements = page.elements();
+
+    let (start, level) =
+        elements
+            .iter()
+            .enumerate()
+            .find_map(|(idx, element)| match element.as_inner() {
+                BlockElement::Header(x)
+                    if x.content.to_string().trim() == text =>
+                {
+                    Some((idx, x.level))
+                }
+                _ => None,
+            })?;
+
+    let end = elements[start + 1..]
+        .iter()
+        .position(|element| {
+            matches!(
+                element.as_inner(),
+                BlockElement::Header(x) if x.level <= level
+            )
+        })
+        .map_or(elements.len(), |idx| start + 1 + idx);
+
+    Some(&elements[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HtmlConfig, HtmlRuntimeConfig, HtmlWikiConfig, ToHtmlString};
+    use std::{convert::TryFrom, path::Path};
+
+    fn to_html(root: &Path, page: &str) -> Result<String, HtmlOutputError> {
+        let path = root.join(page);
+        let text = fs::read_to_string(&path).unwrap();
+        let page: Page = Language::from_vimwiki_str(&text).parse().unwrap();
+        page.to_html_string(HtmlConfig {
+            wikis: vec![HtmlWikiConfig {
+                path: root.to_path_buf(),
+                path_html: root.join("html"),
+                ..Default::default()
+            }],
+            runtime: HtmlRuntimeConfig {
+                wiki_index: Some(0),
+                page: path,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    fn write_file(root: &Path, path: &str, text: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn element_for_should_use_extension_or_media_type_of_data_uri() {
+        let element_for = |config: &HtmlTransclusionConfig, uri: &str| {
+            config.element_for(&URIReference::try_from(uri).unwrap())
+        };
+        let mut config = HtmlTransclusionConfig::default();
+
+        assert_eq!(
+            element_for(&config, "a/b.webm"),
+            HtmlTransclusionElement::Video
+        );
+        assert_eq!(
+            element_for(&config, "https://example.com/a.ogg?x=y"),
+            HtmlTransclusionElement::Audio
+        );
+        assert_eq!(
+            element_for(&config, "file:/a/b.PDF"),
+            HtmlTransclusionElement::Iframe
+        );
+        assert_eq!(
+            element_for(&config, "data:text/plain,hello"),
+            HtmlTransclusionElement::Object
+        );
+        assert_eq!(
+            element_for(&config, "data:image/png;base64,AAAA"),
+            HtmlTransclusionElement::Img
+        );
+        assert_eq!(
+            element_for(&config, "a/b.png"),
+            HtmlTransclusionElement::Img
+        );
+        assert_eq!(element_for(&config, "a/mp4"), HtmlTransclusionElement::Img);
+
+        config.video.extensions.push(String::from("mkv"));
+        config.iframe.extensions.clear();
+        assert_eq!(
+            element_for(&config, "a/b.mkv"),
+            HtmlTransclusionElement::Video
+        );
+        assert_eq!(
+            element_for(&config, "a/b.pdf"),
+            HtmlTransclusionElement::Img
+        );
+    }
+
+    #[test]
+    fn is_attribute_valid_should_check_global_and_element_attributes() {
+        let config = HtmlTransclusionConfig::default();
+        let video = HtmlTransclusionElement::Video;
+        assert!(config.is_attribute_valid(video, "class"));
+        assert!(config.is_attribute_valid(video, "data-start"));
+        assert!(config.is_attribute_valid(video, "Poster"));
+        assert!(!config.is_attribute_valid(video, "sandbox"));
+        assert!(config
+            .is_attribute_valid(HtmlTransclusionElement::Iframe, "sandbox"));
+        assert!(
+            config.is_attribute_valid(HtmlTransclusionElement::Img, "anything")
+        );
+    }
+
+    #[test]
+    fn transclusion_of_page_should_inline_its_content() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "index.wiki", "{{snippets/footer}}\n");
+        write_file(
+            dir.path(),
+            "snippets/footer.wiki",
+            "%title Footer\nsome *footer*\n",
+        );
+
+        assert_eq!(
+            to_html(dir.path(), "index.wiki").unwrap(),
+            "<div class=\"transclusion\"><p>some <strong id=\"footer\">footer</strong></p></div>\n"
+        );
+    }
+
+    #[test]
+    fn transclusion_of_page_should_resolve_nested_pages_relative_to_page() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "index.wiki", "{{snippets/a.wiki}}\n");
+        write_file(dir.path(), "snippets/a.wiki", "a\n\n{{b}}\n");
+        write_file(dir.path(), "snippets/b.wiki", "b\n");
+
+        assert_eq!(
+            to_html(dir.path(), "index.wiki").unwrap(),
+            "<div class=\"transclusion\"><p>a</p>\n<div class=\"transclusion\"><p>b</p></div></div>\n"
+        );
+    }
+
+    #[test]
+    fn transclusion_of_page_should_support_selecting_a_section() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "index.wiki", "{{/other#Two}}\n");
+        write_file(
+            dir.path(),
+            "other.wiki",
+            "= One =\none\n= Two =\ntwo\n== Three ==\nthree\n= Four =\nfour\n",
+        );
+
+        let html = to_html(dir.path(), "index.wiki").unwrap();
+        assert!(html.contains("two"), "{}", html);
+        assert!(html.contains("three"), "{}", html);
+        assert!(!html.contains("one"), "{}", html);
+        assert!(!html.contains("four"), "{}", html);
+
+        write_file(dir.path(), "index.wiki", "{{/other#Missing}}\n");
+        assert!(matches!(
+            to_html(dir.path(), "index.wiki"),
+            Err(HtmlOutputError::TransclusionSectionMissing { .. })
+        ));
+    }
+
+    #[test]
+    fn transclusion_of_page_should_fail_on_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "index.wiki", "{{a}}\n");
+        write_file(dir.path(), "a.wiki", "{{b}}\n");
+        write_file(dir.path(), "b.wiki", "{{index}}\n");
+
+        assert!(matches!(
+            to_html(dir.path(), "index.wiki"),
+            Err(HtmlOutputError::TransclusionCycle(x)) if x.ends_with("index.wiki")
+        ));
+    }
+
+    #[test]
+    fn transclusion_of_page_should_fail_when_too_deep() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "index.wiki", "{{p0}}\n");
+        for i in 0..10 {
+            write_file(
+                dir.path(),
+                &format!("p{}.wiki", i),
+                &format!("{{{{p{}}}}}\n", i + 1),
+            );
+        }
+        write_file(dir.path(), "p10.wiki", "end\n");
+
+        assert!(matches!(
+            to_html(dir.path(), "index.wiki"),
+            Err(HtmlOutputError::TransclusionTooDeep { max_depth: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn transclusion_of_page_alongside_other_content_should_link_to_it() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(
+            dir.path(),
+            "index.wiki",
+            "see {{footer}} or {{footer|the footer}}\n",
+        );
+        write_file(dir.path(), "footer.wiki", "footer\n");
+
+        assert_eq!(
+            to_html(dir.path(), "index.wiki").unwrap(),
+            concat!(
+                "<p>see <a href=\"footer.html\">footer</a> or ",
+                "<a href=\"footer.html\">the footer</a></p>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn transclusion_of_page_should_share_ids_of_page_containing_it() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "index.wiki", "= Footer =\n{{footer}}\n");
+        write_file(dir.path(), "footer.wiki", "= Footer =\nfooter\n");
+
+        let html = to_html(dir.path(), "index.wiki").unwrap();
+        assert!(html.contains("id=\"footer-1\""), "{}", html);
+    }
+
+    #[test]
+    fn transclusion_of_page_outside_of_wiki_should_remain_an_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let wiki = dir.path().join("wiki");
+        write_file(
+            &wiki,
+            "index.wiki",
+            "{{../private/secret}} {{/../private/secret}}\n",
+        );
+        write_file(dir.path(), "private/secret.wiki", "secret\n");
+
+        assert_eq!(
+            to_html(&wiki, "index.wiki").unwrap(),
+            "<p><img src=\"../private/secret\" /> <img src=\"../private/secret\" /></p>\n"
+        );
+    }
+
+    #[test]
+    fn transclusion_of_page_without_active_wiki_should_remain_an_image() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "footer.wiki", "footer\n");
+        let page: Page =
+            Language::from_vimwiki_str("{{footer}}\n").parse().unwrap();
+
+        assert_eq!(
+            page.to_html_string(HtmlConfig {
+                runtime: HtmlRuntimeConfig {
+                    page: dir.path().join("index.wiki"),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .unwrap(),
+            "<p><img src=\"footer\" /></p>\n"
+        );
+    }
+
+    #[test]
+    fn transclusion_of_missing_page_or_other_file_should_remain_an_image() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(dir.path(), "index.wiki", "{{missing}} {{img.png}}\n");
+        write_file(dir.path(), "img.png", "");
+
+        assert_eq!(
+            to_html(dir.path(), "index.wiki").unwrap(),
+            "<p><img src=\"missing\" /> <img src=\"img.png\" /></p>\n"
+        );
+    }
+}
@@ -362,7 +362,7 @@ fn make_diary_absolute_output_path(
 
 /// Given a src and target path, creates a relative reference
 #[inline]
-fn make_relative_link<P1: AsRef<Path>, P2: AsRef<Path>>(
+pub(super) fn make_relative_link<P1: AsRef<Path>, P2: AsRef<Path>>(
     src: P1,
     target: P2,
 ) -> Result<RelativeReference<'static>, RelativeReferenceError> {
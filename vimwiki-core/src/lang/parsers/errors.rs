@@ -75,6 +75,24 @@ impl<'a> LangParserError<'a> {
             next: None,
         }
     }
+
+    /// Returns the context describing what was being parsed when the error
+    /// occurred
+    pub fn context(&self) -> &str {
+        self.ctx.as_ref()
+    }
+
+    /// Returns the byte offset within the original input where the error
+    /// occurred
+    pub fn offset(&self) -> usize {
+        self.input.start_offset()
+    }
+
+    /// Returns the error that this error wraps, if any, which is the next
+    /// (more specific) error in the chain of contexts
+    pub fn next(&self) -> Option<&LangParserError<'a>> {
+        self.next.as_deref()
+    }
 }
 
 impl<'a, E> FromExternalError<Span<'a>, E> for LangParserError<'a> {
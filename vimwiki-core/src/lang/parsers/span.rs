@@ -235,20 +235,20 @@ impl<'a> Span<'a> {
         // Iterate through all space and tabs until either we detect some
         // character that is not space (gap in index) or we find no more
         // whitespace even though we have more bytes remaining
-        let mut last_pos = 0;
+        let mut next_pos = 0;
         for pos in memchr2_iter(b' ', b'\t', self.as_remaining()) {
             // If we skipped at least one position beyond what would be next,
             // there was something that didn't match and we can exit
-            if pos - last_pos > 1 {
+            if pos != next_pos {
                 return false;
             }
 
-            last_pos = pos;
+            next_pos = pos + 1;
         }
 
-        // If our last position doesn't correspond to the end of the remaining
+        // If our next position doesn't correspond to the end of the remaining
         // bytes, there is something that is not whitespace
-        last_pos == (len - 1)
+        next_pos == len
     }
 
     /// Calculates the line and column position of this span using newline (\n)
@@ -746,6 +746,30 @@ impl<'a> Slice<RangeFull> for Span<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn is_only_whitespace_should_return_true_if_only_spaces_and_tabs_remain() {
+        assert!(Span::from("").is_only_whitespace());
+        assert!(Span::from(" ").is_only_whitespace());
+        assert!(Span::from(" \t ").is_only_whitespace());
+    }
+
+    #[test]
+    fn is_only_whitespace_should_return_false_if_any_other_character_remains() {
+        assert!(!Span::from("a").is_only_whitespace());
+        assert!(!Span::from("a ").is_only_whitespace());
+        assert!(!Span::from(" a").is_only_whitespace());
+        assert!(!Span::from(" a ").is_only_whitespace());
+        assert!(!Span::from("ab").is_only_whitespace());
+    }
+
+    #[test]
+    fn is_only_whitespace_should_only_consider_remaining_bytes() {
+        assert!(Span::from("a  ").advance_start_by(1).is_only_whitespace());
+        assert!(Span::from("abc").advance_start_by(3).is_only_whitespace());
+        assert!(!Span::from(" ab").advance_start_by(2).is_only_whitespace());
+        assert!(!Span::from("a b").advance_start_by(1).is_only_whitespace());
+    }
+
     #[test]
     fn trim_start_should_return_exact_copy_if_no_leading_whitespace() {
         let span = Span::from("some text");
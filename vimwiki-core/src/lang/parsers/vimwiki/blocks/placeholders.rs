@@ -247,4 +247,15 @@ mod tests {
             Placeholder::other_from_str("other", "something else"),
         );
     }
+
+    #[test]
+    fn placeholder_fallback_should_succeed_if_value_is_a_single_character() {
+        let input = Span::from("%tags c");
+        let (input, placeholder) = placeholder(input).unwrap();
+        assert!(input.is_empty(), "Did not consume placeholder");
+        assert_eq!(
+            placeholder.into_inner(),
+            Placeholder::other_from_str("tags", "c"),
+        );
+    }
 }
@@ -15,17 +15,6 @@ pub mod blocks;
 
 pub fn page<'a>(input: Span<'a>) -> IResult<Page<'a>> {
     fn inner<'a>(input: Span<'a>) -> IResult<Page<'a>> {
-        // Parses one or more lines, either eating blank lines or producing
-        // a block element
-        fn maybe_block_element(
-            input: Span,
-        ) -> IResult<Option<Located<BlockElement>>> {
-            alt((
-                value(None, blank_line),
-                map(blocks::top_level_block_element, Some),
-            ))(input)
-        }
-
         map(all_consuming(many0(maybe_block_element)), |mut elements| {
             Page::new(elements.drain(..).flatten().collect())
         })(input)
@@ -34,6 +23,17 @@ pub fn page<'a>(input: Span<'a>) -> IResult<Page<'a>> {
     context("Page", inner)(input)
 }
 
+/// Parses one or more lines, either eating blank lines or producing a
+/// top-level block element
+pub fn maybe_block_element(
+    input: Span,
+) -> IResult<Option<Located<BlockElement>>> {
+    alt((
+        value(None, blank_line),
+        map(blocks::top_level_block_element, Some),
+    ))(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
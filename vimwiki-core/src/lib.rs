@@ -4,6 +4,15 @@ mod utils;
 // Export all elements at top level
 pub use lang::elements::*;
 
+// Export our lossless syntax tree at top level
+pub use lang::cst::{
+    Cst, CstElement, CstNode, CstNodeKind, CstToken, CstTokenKind, Trivia,
+    TriviaKind,
+};
+
+// Export our types to support reparsing only the edited parts of a page
+pub use lang::incremental::{ReparsedPage, TextEdit};
+
 // Export all outputs at top level
 pub use lang::output::*;
 
@@ -25,3 +34,6 @@ pub mod vendor {
 
 #[cfg(feature = "timekeeper")]
 pub mod timekeeper;
+
+#[cfg(feature = "typescript")]
+pub mod typescript;
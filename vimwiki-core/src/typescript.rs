@@ -0,0 +1,62 @@
+use crate::{Element, Page, PageMetadata};
+use std::{any::TypeId, collections::HashSet};
+use ts_rs::{TypeVisitor, TS};
+
+/// Returns typescript definitions for the serialized form of a `Page` and
+/// every type it contains, along with `Element` and `PageMetadata`, sorted
+/// by name
+///
+/// ## Examples
+///
+/// ```
+/// let definitions = vimwiki_core::typescript::definitions();
+/// assert!(definitions.contains("export type Page = "));
+/// assert!(definitions.contains("export type Located<T> = "));
+/// ```
+pub fn definitions() -> String {
+    let mut visitor = DeclVisitor::default();
+    visitor.visit::<Page<'static>>();
+    visitor.visit::<Element<'static>>();
+    visitor.visit::<PageMetadata>();
+
+    let mut decls = visitor.decls;
+    decls.sort();
+    decls
+        .into_iter()
+        .map(|(_, decl)| {
+            let decl = decl
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<&str>>()
+                .join("\n");
+            format!("export {}\n", decl)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Visits a type and all of its dependencies, collecting the declaration of
+/// each type that can be exported
+#[derive(Default)]
+struct DeclVisitor {
+    seen: HashSet<TypeId>,
+    names: HashSet<String>,
+    decls: Vec<(String, String)>,
+}
+
+impl TypeVisitor for DeclVisitor {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        if !self.seen.insert(TypeId::of::<T>()) {
+            return;
+        }
+
+        // Generic types share a single declaration regardless of the types
+        // they are given, so only declare each one once
+        if T::output_path().is_some() && self.names.insert(T::ident()) {
+            self.decls.push((T::ident(), T::decl()));
+        }
+
+        T::visit_dependencies(self);
+        T::visit_generics(self);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::parser::fixtures::VimwikiFile;
+use vimwiki_core::*;
+
+#[test]
+fn test() {
+    let contents: String = VimwikiFile::MiscDepthCheck
+        .load()
+        .unwrap()
+        .chars()
+        .filter(|c| *c != '\r')
+        .collect();
+    let page: Page = Language::from_vimwiki_str(&contents).parse().unwrap();
+
+    // Every element along a path must contain the offset and be nested
+    // within the element before it
+    for offset in 0..=contents.len() {
+        let path = page.path_at(offset);
+        for (i, located) in path.iter().enumerate() {
+            assert!(located.region().contains(offset), "@ {}", offset);
+            if i > 0 {
+                let parent = path[i - 1].region();
+                assert!(
+                    located.region().depth() >= parent.depth(),
+                    "@ {}",
+                    offset
+                );
+            }
+        }
+    }
+
+    // Bold text within a paragraph
+    let offset = contents.find("bold*,").unwrap();
+    let path = page.path_at(offset);
+    assert!(matches!(
+        path.first().unwrap().as_inner(),
+        Element::Block(BlockElement::Paragraph(_))
+    ));
+    assert!(matches!(
+        path.last().unwrap().as_inner(),
+        Element::Inline(InlineElement::Text(x)) if x.as_str() == "bold"
+    ));
+    assert!(path.iter().any(|x| matches!(
+        x.as_inner(),
+        Element::Inline(InlineElement::DecoratedText(_))
+    )));
+
+    // Link within a paragraph
+    let offset = contents.find("links]]").unwrap();
+    assert!(page.path_at(offset).iter().any(|x| matches!(
+        x.as_inner(),
+        Element::Inline(InlineElement::Link(_))
+    )));
+
+    // Text within the most nested list item, looked up by line and column
+    let index = LineIndex::new(&contents);
+    let path = page.path_at_line_column(&index, LineColumn::new(8, 16));
+    let items = path
+        .iter()
+        .filter(|x| {
+            matches!(
+                x.as_inner(),
+                Element::InlineBlock(InlineBlockElement::ListItem(_))
+            )
+        })
+        .count();
+    assert_eq!(items, 4);
+    assert_eq!(
+        page.element_at(index.offset(LineColumn::new(8, 16)).unwrap()),
+        path.last().cloned()
+    );
+
+    // Nothing is found for blank lines or out of bounds
+    assert!(page.path_at(contents.find("\n\n").unwrap() + 1).is_empty());
+    assert!(page.path_at(contents.len() + 10).is_empty());
+    assert!(page
+        .path_at_line_column(&index, LineColumn::new(100, 0))
+        .is_empty());
+}
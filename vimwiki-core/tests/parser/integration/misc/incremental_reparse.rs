@@ -0,0 +1,90 @@
+use crate::parser::fixtures::VimwikiFile;
+use vimwiki_core::*;
+
+/// Small, deterministic generator so that edits are repeatable between runs
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, max: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % max.max(1)
+    }
+}
+
+#[test]
+fn test() {
+    let replacements = [
+        "",
+        "\n",
+        "\n\n",
+        "x",
+        "= header =\n",
+        "- item\n",
+        "* ",
+        "*",
+        "{{{\n",
+        "}}}\n",
+        "|a|b|\n",
+        "term:: ",
+        "> ",
+        "%title ",
+        "[[",
+        "]]",
+        "  ",
+    ];
+
+    for file in vec![
+        VimwikiFile::Issue119,
+        VimwikiFile::Issue120,
+        VimwikiFile::Issue122,
+        VimwikiFile::MiscCommentInDefinitionList,
+        VimwikiFile::MiscDepthCheck,
+        VimwikiFile::MiscWindowsSupport,
+        VimwikiFile::PandocVimwikiReader,
+        VimwikiFile::VimwikiWikiIndex,
+        VimwikiFile::VimwikiWikiTroubleshooting,
+        VimwikiFile::VimwikiWikiTipsAndSnips,
+        VimwikiFile::VimwikiWikiRelatedTools,
+    ] {
+        let mut text = file.load().unwrap();
+        let mut page: Page<'static> = Language::from_vimwiki_str(&text)
+            .parse::<Page>()
+            .unwrap()
+            .into_owned();
+        let mut rng = Lcg(text.len() as u64);
+
+        for _ in 0..20 {
+            // Pick a region that starts and ends on character boundaries
+            let mut start = rng.next(text.len() + 1);
+            while !text.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = (start + rng.next(20)).min(text.len());
+            while !text.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let edit = TextEdit::new(
+                start..end,
+                replacements[rng.next(replacements.len())],
+            );
+            let new_text = edit.apply(&text);
+
+            let expected: Page =
+                Language::from_vimwiki_str(&new_text).parse().unwrap();
+            let reparsed = page.reparse(&new_text, &edit).unwrap();
+            assert!(
+                reparsed.page.strict_eq(&expected),
+                "{:?}: {:?} produced different page",
+                file.to_path(),
+                edit,
+            );
+
+            page = reparsed.page.into_owned();
+            text = new_text;
+        }
+    }
+}
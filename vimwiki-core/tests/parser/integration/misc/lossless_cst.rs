@@ -0,0 +1,38 @@
+use crate::parser::fixtures::VimwikiFile;
+use vimwiki_core::*;
+
+#[test]
+fn test() {
+    for file in vec![
+        VimwikiFile::Issue119,
+        VimwikiFile::Issue120,
+        VimwikiFile::Issue122,
+        VimwikiFile::MiscCommentInDefinitionList,
+        VimwikiFile::MiscDepthCheck,
+        VimwikiFile::MiscWindowsSupport,
+        VimwikiFile::PandocVimwikiReader,
+        VimwikiFile::VimwikiWikiIndex,
+        VimwikiFile::VimwikiWikiTroubleshooting,
+        VimwikiFile::VimwikiWikiTipsAndSnips,
+        VimwikiFile::VimwikiWikiRelatedTools,
+    ] {
+        let contents = file.load().unwrap();
+        let cst: Cst = Language::from_vimwiki_str(&contents).parse().unwrap();
+
+        // Every byte should be reproduced exactly, including \r\n
+        assert_eq!(cst.to_source(), contents, "{:?}", file.to_path());
+
+        // Tokens and their trivia should tile the input with no gaps
+        let mut cursor = 0;
+        for token in cst.tokens() {
+            let region = token.full_region();
+            assert_eq!(region.offset(), cursor, "{:?}", file.to_path());
+            cursor += region.len();
+        }
+        assert_eq!(cursor, contents.len(), "{:?}", file.to_path());
+
+        // The page should be the same as one parsed directly
+        let page: Page = Language::from_vimwiki_str(&contents).parse().unwrap();
+        assert_eq!(cst.into_page(), page, "{:?}", file.to_path());
+    }
+}
@@ -1,3 +1,7 @@
 mod comment_in_definition_list;
 mod depth_check;
+mod element_lookup;
+mod incremental_reparse;
+mod lossless_cst;
+mod serde_round_trip;
 mod windows_support;
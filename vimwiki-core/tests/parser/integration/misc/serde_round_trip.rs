@@ -0,0 +1,31 @@
+use crate::parser::fixtures::VimwikiFile;
+use vimwiki_core::*;
+
+#[test]
+fn test() {
+    for file in vec![
+        VimwikiFile::Issue119,
+        VimwikiFile::Issue120,
+        VimwikiFile::Issue122,
+        VimwikiFile::MiscCommentInDefinitionList,
+        VimwikiFile::MiscDepthCheck,
+        VimwikiFile::MiscWindowsSupport,
+        VimwikiFile::PandocVimwikiReader,
+        VimwikiFile::VimwikiWikiIndex,
+        VimwikiFile::VimwikiWikiTroubleshooting,
+        VimwikiFile::VimwikiWikiTipsAndSnips,
+        VimwikiFile::VimwikiWikiRelatedTools,
+    ] {
+        let contents = file.load().unwrap();
+        let page: Page = Language::from_vimwiki_str(&contents).parse().unwrap();
+        let page = page.into_owned();
+
+        let json = serde_json::to_string(&page)
+            .unwrap_or_else(|x| panic!("{:?}: {}", file.to_path(), x));
+        let other: Page<'static> = serde_json::from_str(&json)
+            .unwrap_or_else(|x| panic!("{:?}: {}", file.to_path(), x));
+
+        // Regions must survive the round trip alongside the elements
+        assert!(other.strict_eq(&page), "{:?}", file.to_path());
+    }
+}
@@ -0,0 +1,257 @@
+// Generated from the vimwiki element model, do not edit by hand!
+// Run `VIMWIKI_UPDATE_TYPESCRIPT=1 cargo test -p vimwiki-wasm` to regenerate.
+export namespace ast {
+    export type BlockElement = { "type": "blockquote", "data": Blockquote } | { "type": "code_block", "data": CodeBlock } | { "type": "definition_list", "data": DefinitionList } | { "type": "divider", "data": Divider } | { "type": "header", "data": Header } | { "type": "list", "data": List } | { "type": "math_block", "data": MathBlock } | { "type": "paragraph", "data": Paragraph } | { "type": "placeholder", "data": Placeholder } | { "type": "table", "data": Table };
+
+    export type Blockquote = {
+    /**
+     * Represents the lines of text contained within the blockquote include
+     * potential blank lines
+     */
+    lines: Array<string>, };
+
+    export type Cell = { "type": "content", "data": InlineElementContainer } | { "type": "span", "data": CellSpan } | { "type": "align", "data": ColumnAlign };
+
+    export type CellPos = {
+    /**
+     * Represents the row number of a cell starting from 0
+     */
+    row: number,
+    /**
+     * Represents the coumn number of a cell starting from 0
+     */
+    col: number, };
+
+    export type CellSpan = { "type": "from_left" } | { "type": "from_above" };
+
+    export type CodeBlock = {
+    /**
+     * Represents the language associated with the code block if it has one
+     */
+    language: string | null,
+    /**
+     * Represents metadata associated with the code block in the form of
+     * key/value pairs
+     */
+    metadata: { [key in string]?: string },
+    /**
+     * Represents the lines of text contained within the code block
+     */
+    lines: Array<string>, };
+
+    export type CodeInline = string;
+
+    export type ColumnAlign = { "type": "none" } | { "type": "left" } | { "type": "center" } | { "type": "right" };
+
+    export type Comment = { "type": "line", "data": LineComment } | { "type": "multi_line", "data": MultiLineComment };
+
+    export type DecoratedText = { "type": "bold", "data": Array<Located<DecoratedTextContent>> } | { "type": "italic", "data": Array<Located<DecoratedTextContent>> } | { "type": "strikeout", "data": Array<Located<DecoratedTextContent>> } | { "type": "superscript", "data": Array<Located<DecoratedTextContent>> } | { "type": "subscript", "data": Array<Located<DecoratedTextContent>> };
+
+    export type DecoratedTextContent = { "type": "text", "data": Text } | { "type": "decorated_text", "data": DecoratedText } | { "type": "keyword", "data": Keyword } | { "type": "link", "data": Link };
+
+    export type DefinitionBundle = { definitions: Array<Located<DefinitionListValue>>, };
+
+    export type DefinitionList = { items: Array<Located<TermAndDefinitions>>, };
+
+    export type DefinitionListValue = InlineElementContainer;
+
+    export type Description = { "type": "text", "data": string } | { "type": "transclusion_link", "data": LinkData };
+
+    export type Divider = null;
+
+    export type Element = { "type": "block", "data": BlockElement } | { "type": "inline", "data": InlineElement } | { "type": "inline_block", "data": InlineBlockElement };
+
+    export type Header = {
+    /**
+     * Represents the content contained within the header
+     */
+    content: InlineElementContainer,
+    /**
+     * Represents the level of the header (1, 2, 3, etc)
+     */
+    level: number,
+    /**
+     * Represents whether or not the header is centered
+     */
+    centered: boolean, };
+
+    export type InlineBlockElement = { "type": "list_item", "data": ListItem } | { "type": "term", "data": DefinitionListValue } | { "type": "definition", "data": DefinitionListValue };
+
+    export type InlineElement = { "type": "text", "data": Text } | { "type": "decorated_text", "data": DecoratedText } | { "type": "keyword", "data": Keyword } | { "type": "link", "data": Link } | { "type": "tags", "data": Tags } | { "type": "code", "data": CodeInline } | { "type": "math", "data": MathInline } | { "type": "comment", "data": Comment };
+
+    export type InlineElementContainer = { elements: Array<Located<InlineElement>>, };
+
+    export type Keyword = { "type": "todo" } | { "type": "done" } | { "type": "started" } | { "type": "fixme" } | { "type": "fixed" } | { "type": "xxx" };
+
+    export type LineComment = string;
+
+    export type Link = { "type": "wiki", "data": { data: LinkData, } } | { "type": "indexed_inter_wiki", "data": { index: number, data: LinkData, } } | { "type": "named_inter_wiki", "data": { name: string, data: LinkData, } } | { "type": "diary", "data": { date: string, data: LinkData, } } | { "type": "raw", "data": { data: LinkData, } } | { "type": "transclusion", "data": { data: LinkData, } };
+
+    export type LinkData = { uri_ref: URIReference, description: Description | null, properties: { [key in string]?: string } | null, };
+
+    export type List = {
+    /**
+     * Represents items contained within the list
+     */
+    items: Array<Located<ListItem>>, };
+
+    export type ListItem = {
+    /**
+     * Represents the type of list item
+     */
+    ty: ListItemType,
+    /**
+     * Represents the suffix of the list
+     */
+    suffix: ListItemSuffix,
+    /**
+     * Represents the position of the item in the list starting from 0
+     */
+    pos: number,
+    /**
+     * Represents the contents of the list item
+     */
+    contents: ListItemContents,
+    /**
+     * Represents attributes associated with the list item
+     */
+    attributes: ListItemAttributes, };
+
+    export type ListItemAttributes = {
+    /**
+     * The TODO status for a list item, if it has been associated with TODO
+     */
+    todo_status: ListItemTodoStatus | null, };
+
+    export type ListItemContents = Array<Located<BlockElement>>;
+
+    export type ListItemSuffix = { "type": "none" } | { "type": "period" } | { "type": "paren" };
+
+    export type ListItemTodoStatus = { "type": "incomplete" } | { "type": "partially_complete1" } | { "type": "partially_complete2" } | { "type": "partially_complete3" } | { "type": "complete" } | { "type": "rejected" };
+
+    export type ListItemType = { "type": "ordered", "data": OrderedListItemType } | { "type": "unordered", "data": UnorderedListItemType };
+
+    export type Located<T> = T & { region: Region };
+
+    export type MathBlock = {
+    /**
+     * Represents the lines of text contained within the math block
+     */
+    lines: Array<string>,
+    /**
+     * Represents the environment associated with the math block if it has one
+     */
+    environment: string | null, };
+
+    export type MathInline = string;
+
+    export type MultiLineComment = Array<string>;
+
+    export type OrderedListItemType = { "type": "number" } | { "type": "pound" } | { "type": "lowercase_alphabet" } | { "type": "uppercase_alphabet" } | { "type": "lowercase_roman" } | { "type": "uppercase_roman" };
+
+    export type Page = {
+    /**
+     * Comprised of the elements within a page
+     */
+    elements: Array<Located<BlockElement>>, };
+
+    export type PageMetadata = {
+    /**
+     * Title of the page from `%title`
+     */
+    title: string | null,
+    /**
+     * Date of the page from `%date`
+     */
+    date: string | null,
+    /**
+     * Name of the template to use for the page from `%template`
+     */
+    template: string | null,
+    /**
+     * Whether or not the page should be excluded from html output,
+     * from `%nohtml`
+     */
+    nohtml: boolean,
+    /**
+     * Values of all other placeholders by name, in the order they appear
+     */
+    values: { [key in string]?: Array<string> }, };
+
+    export type Paragraph = {
+    /**
+     * Represents the lines of content contained within the paragraph
+     */
+    lines: Array<InlineElementContainer>, };
+
+    export type Placeholder = { "type": "title", "data": string } | { "type": "no_html" } | { "type": "template", "data": string } | { "type": "date", "data": string } | { "type": "other", "data": { name: string, value: string, } };
+
+    export type Region = {
+    /**
+     * Position within some byte array this region begins
+     */
+    offset: number,
+    /**
+     * Length of this region from the offset
+     */
+    len: number,
+    /**
+     * Additional information highlighting how deep into a stack of elements
+     * the current location is. This has no bearing on the actual location
+     * in a byte stream
+     */
+    depth: number, };
+
+    export type Table = {
+    /**
+     * Represents the table's data (cells) as a mapping between a cell's
+     * position and its actual content (private)
+     */
+    cells: Array<[CellPos, Located<Cell>]>,
+    /**
+     * Represents the total rows contained in the table (private)
+     */
+    row_cnt: number,
+    /**
+     * Represents the total columns contained in the table (private)
+     */
+    col_cnt: number,
+    /**
+     * Represents whether or not the table is centered
+     */
+    centered: boolean, };
+
+    export type Tag = string;
+
+    export type Tags = Array<Tag>;
+
+    export type TermAndDefinitions = { term: Located<DefinitionListValue>, definitions: Located<DefinitionBundle>, };
+
+    export type Text = string;
+
+    export type URIAuthority = { host: URIHost, password: URIPassword | null, port: number | null, username: URIUsername | null, };
+
+    export type URIFragment = { fragment: string, normalized: boolean, };
+
+    export type URIHost = { "IPv4Address": string } | { "IPv6Address": string } | { "RegisteredName": URIRegisteredName };
+
+    export type URIPassword = { normalized: boolean, password: string, };
+
+    export type URIPath = { absolute: boolean, double_dot_segment_count: number, leading_double_dot_segment_count: number, segments: Array<URISegment>, single_dot_segment_count: number, unnormalized_count: number, };
+
+    export type URIQuery = { normalized: boolean, query: string, };
+
+    export type URIReference = { authority: URIAuthority | null, fragment: URIFragment | null, path: URIPath, query: URIQuery | null, scheme: URIScheme | null, };
+
+    export type URIRegisteredName = { normalized: boolean, registered_name: string, };
+
+    export type URIScheme = string | { Unregistered: URIUnregisteredScheme, };
+
+    export type URISegment = { normalized: boolean, segment: string, };
+
+    export type URIUnregisteredScheme = { normalized: boolean, scheme: string, };
+
+    export type URIUsername = { normalized: boolean, username: string, };
+
+    export type UnorderedListItemType = { "type": "hyphen" } | { "type": "asterisk" } | { "type": "other", "data": string };
+}
@@ -3,9 +3,9 @@ use std::{borrow::Cow, collections::HashMap, convert::TryFrom};
 use vimwiki::{
     self as v,
     vendor::{chrono, uriparse},
-    ToHtmlString,
+    ToHtmlPage, ToHtmlString, ToVimwikiString,
 };
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
 
 /// Represents a wrapper around a vimwiki page
 #[wasm_bindgen]
@@ -13,6 +13,19 @@ pub struct Page(v::Page<'static>);
 
 #[wasm_bindgen]
 impl Page {
+    /// Creates a new page from the given block elements
+    #[wasm_bindgen(constructor)]
+    pub fn new(elements: js_sys::Array) -> Page {
+        Self::with_elements(
+            elements
+                .iter()
+                .filter_map(|x| {
+                    utils::cast_value::<BlockElement>(x, "BlockElement").ok()
+                })
+                .collect(),
+        )
+    }
+
     /// Returns top-level element at the given index if it exists
     pub fn element_at(&self, idx: usize) -> Option<BlockElement> {
         self.0.elements.get(idx).map(|x| {
@@ -28,6 +41,155 @@ impl Page {
     pub fn element_cnt(&self) -> usize {
         self.0.elements.len()
     }
+
+    /// Inserts a top-level element at the given index, failing if the index
+    /// is greater than the total number of elements
+    pub fn insert_element(
+        &mut self,
+        idx: usize,
+        element: BlockElement,
+    ) -> Result<(), JsValue> {
+        self.try_insert_element(idx, element).map_err(JsValue::from)
+    }
+
+    /// Returns the metadata declared by the placeholders within the page,
+    /// such as its title and any custom `%name value` placeholders
+    #[wasm_bindgen(getter)]
+    pub fn metadata(&self) -> Result<crate::AstPageMetadata, JsValue> {
+        utils::to_js(&self.0.metadata()).map(JsCast::unchecked_into)
+    }
+
+    /// Adds a top-level element to the end of the page
+    pub fn push_element(&mut self, element: BlockElement) {
+        self.0.elements.push(element.0);
+    }
+
+    /// Removes the top-level element at the given index, returning it if it
+    /// exists
+    pub fn remove_element(&mut self, idx: usize) -> Option<BlockElement> {
+        if idx < self.0.elements.len() {
+            Some(BlockElement(self.0.elements.remove(idx)))
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the top-level element at the given index, returning the
+    /// element that was replaced
+    pub fn replace_element(
+        &mut self,
+        idx: usize,
+        element: BlockElement,
+    ) -> Result<BlockElement, JsValue> {
+        self.try_replace_element(idx, element)
+            .map_err(JsValue::from)
+    }
+
+    /// Converts the page to an HTML string, optionally taking a config object
+    /// that can include a `runtime` object with the `page` path being
+    /// converted and the `wiki_index` of its wiki
+    pub fn to_html_string(&self, config: &JsValue) -> Result<String, JsValue> {
+        let config = utils::html_config_from_js(config)?;
+        self.render_html(config).map_err(JsValue::from)
+    }
+
+    /// Converts the page to a complete HTML page using the config's template,
+    /// optionally taking a config object like `to_html_string`
+    pub fn to_html_page(&self, config: &JsValue) -> Result<String, JsValue> {
+        let config = utils::html_config_from_js(config)?;
+        self.render_html_page(config).map_err(JsValue::from)
+    }
+
+    /// Returns the innermost element containing the byte offset if it exists,
+    /// where the offset is measured in bytes of UTF-8 like the regions of
+    /// elements rather than in UTF-16 code units like javascript strings
+    pub fn element_at_offset(&self, byte_offset: usize) -> Option<Element> {
+        self.path(byte_offset).pop()
+    }
+
+    /// Returns the elements containing the byte offset, starting with the
+    /// top-level element and ending with the innermost element, where the
+    /// offset is measured in bytes of UTF-8 like the regions of elements; use
+    /// `path_at_line_column` to look up a position within a javascript string
+    pub fn path_at(&self, byte_offset: usize) -> js_sys::Array {
+        self.path(byte_offset)
+            .into_iter()
+            .map(JsValue::from)
+            .collect()
+    }
+
+    /// Returns the elements containing the line and column, where the
+    /// column is measured in UTF-16 code units like javascript strings and
+    /// the text is what was used to parse the page
+    pub fn path_at_line_column(
+        &self,
+        text: &str,
+        line: usize,
+        column: usize,
+    ) -> js_sys::Array {
+        let index =
+            v::LineIndex::new(text).with_encoding(v::ColumnEncoding::Utf16);
+        self.0
+            .path_at_line_column(&index, v::LineColumn::new(line, column))
+            .into_iter()
+            .map(|x| JsValue::from(Element(x.map(v::Element::into_owned))))
+            .collect()
+    }
+}
+
+impl Page {
+    /// Creates a new page from the given block elements
+    fn with_elements(elements: Vec<BlockElement>) -> Self {
+        Self(v::Page::new(elements.into_iter().map(|x| x.0).collect()))
+    }
+
+    /// Inserts a top-level element at the given index, failing if the index
+    /// is greater than the total number of elements
+    fn try_insert_element(
+        &mut self,
+        idx: usize,
+        element: BlockElement,
+    ) -> Result<(), String> {
+        if idx > self.0.elements.len() {
+            return Err(out_of_bounds(idx, self.0.elements.len()));
+        }
+
+        self.0.elements.insert(idx, element.0);
+        Ok(())
+    }
+
+    /// Replaces the top-level element at the given index, returning the
+    /// element that was replaced
+    fn try_replace_element(
+        &mut self,
+        idx: usize,
+        element: BlockElement,
+    ) -> Result<BlockElement, String> {
+        let len = self.0.elements.len();
+        self.0
+            .elements
+            .get_mut(idx)
+            .map(|x| BlockElement(std::mem::replace(x, element.0)))
+            .ok_or_else(|| out_of_bounds(idx, len))
+    }
+
+    /// Converts the page to a complete HTML page using the config's template
+    fn render_html_page(
+        &self,
+        config: v::HtmlConfig,
+    ) -> Result<String, String> {
+        self.0.to_html_page(config).map_err(|x| x.to_string())
+    }
+
+    /// Returns the elements containing the byte offset, starting with the
+    /// top-level element and ending with the innermost element
+    fn path(&self, byte_offset: usize) -> Vec<Element> {
+        self.0
+            .path_at(byte_offset)
+            .into_iter()
+            .map(|x| Element(x.map(v::Element::into_owned)))
+            .collect()
+    }
 }
 
 /// Represents a wrapper around a vimwiki element
@@ -565,6 +727,32 @@ pub struct Header(v::Located<v::Header<'static>>);
 
 #[wasm_bindgen]
 impl Header {
+    /// Creates a new header with the given text, level and centered status
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        txt: &str,
+        level: usize,
+        centered: bool,
+        region: Option<Region>,
+    ) -> Result<Header, JsValue> {
+        Self::try_new(txt, level, centered, region).map_err(JsValue::from)
+    }
+
+    /// Sets the level of the header, failing if not within 1 and 6
+    pub fn set_level(&mut self, level: usize) -> Result<(), JsValue> {
+        self.try_set_level(level).map_err(JsValue::from)
+    }
+
+    /// Sets whether or not the header is centered
+    pub fn set_centered(&mut self, centered: bool) {
+        self.0.centered = centered;
+    }
+
+    /// Replaces the content of the header with the given text
+    pub fn set_text(&mut self, txt: &str) {
+        self.0.content = text_container(txt);
+    }
+
     /// Represents the level of the header
     #[wasm_bindgen(getter)]
     pub fn level(&self) -> usize {
@@ -589,12 +777,86 @@ impl Header {
     }
 }
 
+impl Header {
+    /// Creates a new header, failing if the level is not within 1 and 6
+    fn try_new(
+        txt: &str,
+        level: usize,
+        centered: bool,
+        region: Option<Region>,
+    ) -> Result<Self, String> {
+        validate_header_level(level)?;
+        Ok(Self(v::Located::new(
+            v::Header::new(text_container(txt), level, centered),
+            region.map(|x| x.0).unwrap_or_default(),
+        )))
+    }
+
+    /// Sets the level of the header, failing if not within 1 and 6
+    fn try_set_level(&mut self, level: usize) -> Result<(), String> {
+        validate_header_level(level)?;
+        self.0.level = level;
+        Ok(())
+    }
+}
+
 /// Represents a wrapper around a vimwiki list
 #[wasm_bindgen]
 pub struct List(v::Located<v::List<'static>>);
 
 #[wasm_bindgen]
 impl List {
+    /// Creates a new list from the given list items
+    #[wasm_bindgen(constructor)]
+    pub fn new(items: js_sys::Array, region: Option<Region>) -> List {
+        Self::with_items(
+            items
+                .iter()
+                .filter_map(|x| {
+                    utils::cast_value::<ListItem>(x, "ListItem").ok()
+                })
+                .collect(),
+            region,
+        )
+    }
+
+    /// Inserts a list item at the given index, failing if the index is
+    /// greater than the total number of items
+    pub fn insert_item(
+        &mut self,
+        idx: usize,
+        item: ListItem,
+    ) -> Result<(), JsValue> {
+        self.try_insert_item(idx, item).map_err(JsValue::from)
+    }
+
+    /// Adds a list item to the end of the list
+    pub fn push_item(&mut self, item: ListItem) {
+        self.0.items.push(item.0);
+        self.renumber();
+    }
+
+    /// Removes the list item at the given index, returning it if it exists
+    pub fn remove_item(&mut self, idx: usize) -> Option<ListItem> {
+        if idx < self.0.items.len() {
+            let item = self.0.items.remove(idx);
+            self.renumber();
+            Some(ListItem(item))
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the list item at the given index, returning the item that
+    /// was replaced
+    pub fn replace_item(
+        &mut self,
+        idx: usize,
+        item: ListItem,
+    ) -> Result<ListItem, JsValue> {
+        self.try_replace_item(idx, item).map_err(JsValue::from)
+    }
+
     /// Returns list item at the given index if it exists
     pub fn item_at(&self, idx: usize) -> Option<ListItem> {
         self.0.items.get(idx).map(|x| {
@@ -609,12 +871,115 @@ impl List {
     }
 }
 
+impl List {
+    /// Creates a new list from the given list items
+    fn with_items(items: Vec<ListItem>, region: Option<Region>) -> Self {
+        let mut list = Self(v::Located::new(
+            v::List::new(items.into_iter().map(|x| x.0).collect()),
+            region.map(|x| x.0).unwrap_or_default(),
+        ));
+        list.renumber();
+        list
+    }
+
+    /// Inserts a list item at the given index, failing if the index is
+    /// greater than the total number of items
+    fn try_insert_item(
+        &mut self,
+        idx: usize,
+        item: ListItem,
+    ) -> Result<(), String> {
+        if idx > self.0.items.len() {
+            return Err(out_of_bounds(idx, self.0.items.len()));
+        }
+
+        self.0.items.insert(idx, item.0);
+        self.renumber();
+        Ok(())
+    }
+
+    /// Replaces the list item at the given index, returning the item that
+    /// was replaced
+    fn try_replace_item(
+        &mut self,
+        idx: usize,
+        item: ListItem,
+    ) -> Result<ListItem, String> {
+        let len = self.0.items.len();
+        let old = self
+            .0
+            .items
+            .get_mut(idx)
+            .map(|x| ListItem(std::mem::replace(x, item.0)))
+            .ok_or_else(|| out_of_bounds(idx, len))?;
+        self.renumber();
+        Ok(old)
+    }
+
+    /// Updates the position of each item to match its index in the list
+    fn renumber(&mut self) {
+        for (pos, item) in self.0.items.iter_mut().enumerate() {
+            item.pos = pos;
+        }
+    }
+}
+
 /// Represents a wrapper around a vimwiki list item
 #[wasm_bindgen]
 pub struct ListItem(v::Located<v::ListItem<'static>>);
 
 #[wasm_bindgen]
 impl ListItem {
+    /// Creates a new list item containing a paragraph of the given text,
+    /// being numbered (1.) if ordered or a hyphen (-) otherwise
+    #[wasm_bindgen(constructor)]
+    pub fn new(txt: &str, ordered: bool, region: Option<Region>) -> ListItem {
+        let (ty, suffix) = if ordered {
+            (
+                v::ListItemType::Ordered(v::OrderedListItemType::Number),
+                v::ListItemSuffix::Period,
+            )
+        } else {
+            (
+                v::ListItemType::Unordered(v::UnorderedListItemType::Hyphen),
+                v::ListItemSuffix::None,
+            )
+        };
+
+        Self(v::Located::new(
+            v::ListItem::new(
+                ty,
+                suffix,
+                0,
+                v::ListItemContents::new(vec![v::Located::from(
+                    v::BlockElement::from(v::Paragraph::new(vec![
+                        text_container(txt),
+                    ])),
+                )]),
+                v::ListItemAttributes::default(),
+            ),
+            region.map(|x| x.0).unwrap_or_default(),
+        ))
+    }
+
+    /// Sets the todo status of the list item, removing it if not provided
+    pub fn set_todo_status(&mut self, status: Option<ListItemTodoStatus>) {
+        self.0.attributes.todo_status =
+            status.as_ref().and_then(ListItemTodoStatus::to_vimwiki);
+    }
+
+    /// Toggles the todo status of the list item, marking it complete unless
+    /// it is already complete, in which case it becomes incomplete; an item
+    /// without a todo status becomes an incomplete todo item
+    pub fn toggle_todo(&mut self) {
+        self.0.attributes.todo_status = match self.0.attributes.todo_status {
+            None | Some(v::ListItemTodoStatus::Complete) => {
+                Some(v::ListItemTodoStatus::Incomplete)
+            }
+            Some(_) => Some(v::ListItemTodoStatus::Complete),
+        };
+    }
+
     /// Represents position of list item within list
     #[wasm_bindgen(getter)]
     pub fn pos(&self) -> usize {
@@ -837,6 +1202,16 @@ pub struct Paragraph(v::Located<v::Paragraph<'static>>);
 
 #[wasm_bindgen]
 impl Paragraph {
+    /// Creates a new paragraph from the given text, where each line of the
+    /// text becomes a line within the paragraph
+    #[wasm_bindgen(constructor)]
+    pub fn new(txt: &str, region: Option<Region>) -> Paragraph {
+        Self(v::Located::new(
+            v::Paragraph::new(txt.lines().map(text_container).collect()),
+            region.map(|x| x.0).unwrap_or_default(),
+        ))
+    }
+
     /// Returns line as inline element container at the given index if it exists
     pub fn line_at(&self, idx: usize) -> Option<InlineElementContainer> {
         self.0
@@ -968,6 +1343,48 @@ impl Table {
         )))
     }
 
+    /// Adds a row of cells to the end of the table
+    pub fn push_row(&mut self, cells: js_sys::Array) {
+        let row = self.0.row_cnt();
+        self.insert_cells_at_row(row, cells_from_js(cells));
+    }
+
+    /// Inserts a row of cells at the given row index, shifting down all rows
+    /// at or after the index, failing if the index is greater than the total
+    /// number of rows
+    pub fn insert_row(
+        &mut self,
+        idx: usize,
+        cells: js_sys::Array,
+    ) -> Result<(), JsValue> {
+        self.try_insert_row(idx, cells_from_js(cells))
+            .map_err(JsValue::from)
+    }
+
+    /// Removes the row at the given index, shifting up all rows after it,
+    /// returning true if the row existed
+    pub fn remove_row(&mut self, idx: usize) -> bool {
+        if idx >= self.0.row_cnt() {
+            return false;
+        }
+
+        let cells = self
+            .0
+            .as_data()
+            .iter()
+            .filter(|(pos, _)| pos.row != idx)
+            .map(|(pos, cell)| {
+                let row = if pos.row > idx { pos.row - 1 } else { pos.row };
+                (v::CellPos { row, col: pos.col }, cell.clone())
+            })
+            .collect::<Vec<_>>();
+        self.0 = v::Located::new(
+            v::Table::new(cells, self.0.centered),
+            self.0.region(),
+        );
+        true
+    }
+
     /// Returns cell at the given row & column if it exists
     pub fn cell_at(&self, row: usize, col: usize) -> Option<Cell> {
         self.0.get_cell(row, col).map(|x| {
@@ -994,6 +1411,42 @@ impl Table {
     }
 }
 
+impl Table {
+    /// Inserts a row of cells at the given row index, failing if the index
+    /// is greater than the total number of rows
+    fn try_insert_row(
+        &mut self,
+        idx: usize,
+        cells: Vec<Cell>,
+    ) -> Result<(), String> {
+        if idx > self.0.row_cnt() {
+            return Err(out_of_bounds(idx, self.0.row_cnt()));
+        }
+
+        self.insert_cells_at_row(idx, cells);
+        Ok(())
+    }
+
+    /// Rebuilds the table with the cells placed in a new row at the given
+    /// index, shifting down all rows at or after the index
+    fn insert_cells_at_row(&mut self, idx: usize, cells: Vec<Cell>) {
+        let existing = self.0.as_data().iter().map(|(pos, cell)| {
+            let row = if pos.row >= idx { pos.row + 1 } else { pos.row };
+            (v::CellPos { row, col: pos.col }, cell.clone())
+        });
+        let new = cells
+            .into_iter()
+            .enumerate()
+            .map(|(col, x)| (v::CellPos { row: idx, col }, x.0));
+        let cells = existing.chain(new).collect::<Vec<_>>();
+
+        self.0 = v::Located::new(
+            v::Table::new(cells, self.0.centered),
+            self.0.region(),
+        );
+    }
+}
+
 /// Represents a wrapper around a vimwiki table cell
 #[wasm_bindgen]
 pub struct Cell(v::Located<v::Cell<'static>>);
@@ -1671,6 +2124,11 @@ impl Region {
     pub fn depth(&self) -> u16 {
         self.0.depth()
     }
+
+    /// Returns true if the offset is contained within this region
+    pub fn contains(&self, offset: usize) -> bool {
+        self.0.contains(offset)
+    }
 }
 
 /// Provide From impl;
@@ -1733,7 +2191,7 @@ macro_rules! impl_convert {
         impl $name {
             /// Convert to a JavaScript value
             pub fn to_js(&self) -> JsValue {
-                JsValue::from_serde(&self.0).unwrap()
+                utils::to_js(&self.0).unwrap()
             }
 
             /// Convert to a debug string
@@ -1751,15 +2209,32 @@ macro_rules! impl_convert {
             pub fn to_html_str(&self, config: &JsValue) -> Result<String, JsValue> {
                 // Attempt to read a config from a JS object, but if not provided
                 // default to the standard config
-                let config: v::HtmlConfig = if !config.is_undefined() && !config.is_null() {
-                    config.into_serde().map_err(|x| JsValue::from(x.to_string()))?
-                } else {
-                    Default::default()
-                };
+                let config = utils::html_config_from_js(config)?;
+                self.render_html(config).map_err(JsValue::from)
+            }
 
-                self.0
-                    .to_html_string(config)
-                    .map_err(|x| x.to_string().into())
+            /// Convert to a vimwiki string, optionally taking a config object
+            pub fn to_vimwiki_string(
+                &self,
+                config: &JsValue,
+            ) -> Result<String, JsValue> {
+                let config = utils::vimwiki_config_from_js(config)?;
+                self.render_vimwiki(config).map_err(JsValue::from)
+            }
+        }
+
+        impl $name {
+            /// Converts to an HTML string using the config
+            fn render_html(&self, config: v::HtmlConfig) -> Result<String, String> {
+                self.0.to_html_string(config).map_err(|x| x.to_string())
+            }
+
+            /// Converts to a vimwiki string using the config
+            fn render_vimwiki(
+                &self,
+                config: v::VimwikiConfig,
+            ) -> Result<String, String> {
+                self.0.to_vimwiki_string(config).map_err(|x| x.to_string())
             }
         }
 
@@ -1875,3 +2350,360 @@ impl_region!(
     DecoratedText Link Tags CodeInline MathInline Comment Text
     DecoratedTextContent ListItem
 );
+
+macro_rules! impl_into_block {
+    ($name:ident $($tail:tt)*) => {
+        #[wasm_bindgen]
+        impl $name {
+            /// Converts into a block element, such as to add to a page
+            pub fn into_block_element(self) -> BlockElement {
+                BlockElement(self.0.map(v::BlockElement::from))
+            }
+        }
+
+        impl_into_block!($($tail)*);
+    };
+    () => {};
+}
+
+impl_into_block!(
+    Blockquote CodeBlock DefinitionList Divider Header List MathBlock
+    Paragraph Placeholder Table
+);
+
+/// Creates a container of a single text element
+fn text_container(txt: &str) -> v::InlineElementContainer<'static> {
+    v::InlineElementContainer::new(vec![v::Located::from(
+        v::InlineElement::Text(v::Text::from(txt)),
+    )])
+    .into_owned()
+}
+
+/// Collects the cells within the array, skipping anything that is not a cell
+fn cells_from_js(cells: js_sys::Array) -> Vec<Cell> {
+    cells
+        .iter()
+        .filter_map(|x| utils::cast_value::<Cell>(x, "Cell").ok())
+        .collect()
+}
+
+/// Ensures that the level is a valid header level
+fn validate_header_level(level: usize) -> Result<(), String> {
+    if (v::Header::MIN_LEVEL..=v::Header::MAX_LEVEL).contains(&level) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Header level {} must be between {} and {}",
+            level,
+            v::Header::MIN_LEVEL,
+            v::Header::MAX_LEVEL
+        ))
+    }
+}
+
+/// Produces an error for an index that is beyond the given length
+fn out_of_bounds(idx: usize, len: usize) -> String {
+    format!("Index {} is out of bounds for length {}", idx, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Page {
+        let page: v::Page = v::Language::from_vimwiki_str(s).parse().unwrap();
+        Page::from(page.into_owned())
+    }
+
+    fn paragraph(txt: &str) -> BlockElement {
+        Paragraph::new(txt, None).into_block_element()
+    }
+
+    fn page_text(page: &Page) -> String {
+        page.render_vimwiki(Default::default()).unwrap()
+    }
+
+    fn list_text(list: &List) -> String {
+        list.render_vimwiki(Default::default()).unwrap()
+    }
+
+    fn cell(txt: &str) -> Cell {
+        Cell::new(txt, None).unwrap()
+    }
+
+    fn cell_text(table: &Table, row: usize, col: usize) -> Option<String> {
+        table
+            .cell_at(row, col)
+            .and_then(|x| x.0.get_content().map(ToString::to_string))
+    }
+
+    #[test]
+    fn page_should_insert_elements_within_bounds() {
+        let mut page = Page::with_elements(vec![paragraph("b")]);
+        page.insert_element(0, paragraph("a")).unwrap();
+        page.insert_element(2, paragraph("d")).unwrap();
+        page.push_element(paragraph("e"));
+        assert_eq!(page.element_cnt(), 4);
+        assert_eq!(page_text(&page), "a\n\nb\n\nd\n\ne\n");
+
+        assert_eq!(
+            page.try_insert_element(5, paragraph("f")).unwrap_err(),
+            "Index 5 is out of bounds for length 4"
+        );
+        assert_eq!(
+            page.try_insert_element(usize::MAX, paragraph("f"))
+                .unwrap_err(),
+            format!("Index {} is out of bounds for length 4", usize::MAX)
+        );
+        assert_eq!(page.element_cnt(), 4);
+    }
+
+    #[test]
+    fn page_should_remove_and_replace_elements_within_bounds() {
+        let mut page = Page::with_elements(vec![
+            paragraph("a"),
+            paragraph("b"),
+            paragraph("c"),
+        ]);
+
+        let old = page.replace_element(1, paragraph("x")).unwrap();
+        assert!(old.is_paragraph());
+        assert!(page.try_replace_element(3, paragraph("y")).is_err());
+
+        assert!(page.remove_element(0).is_some());
+        assert!(page.remove_element(2).is_none());
+        assert!(page.remove_element(usize::MAX).is_none());
+        assert_eq!(page_text(&page), "x\n\nc\n");
+
+        assert!(page.element_at(1).is_some());
+        assert!(page.element_at(2).is_none());
+    }
+
+    #[test]
+    fn header_should_only_accept_levels_within_range() {
+        assert!(Header::try_new("text", 0, false, None).is_err());
+        assert!(Header::try_new("text", 7, false, None).is_err());
+
+        let mut header = Header::try_new("text", 1, false, None).unwrap();
+        assert_eq!(
+            header.try_set_level(7).unwrap_err(),
+            "Header level 7 must be between 1 and 6"
+        );
+        assert_eq!(header.level(), 1);
+
+        header.set_level(3).unwrap();
+        header.set_text("other");
+        header.set_centered(true);
+        assert_eq!(header.level(), 3);
+        assert!(header.centered());
+        assert_eq!(header.to_str(), "other");
+    }
+
+    #[test]
+    fn list_should_number_items_by_position() {
+        let mut list = List::with_items(
+            vec![
+                ListItem::new("a", true, None),
+                ListItem::new("c", true, None),
+            ],
+            None,
+        );
+        assert_eq!(list.item_cnt(), 2);
+        assert_eq!(list.item_at(1).unwrap().pos(), 1);
+
+        list.insert_item(1, ListItem::new("b", true, None)).unwrap();
+        list.push_item(ListItem::new("d", true, None));
+        assert_eq!(list_text(&list), "1. a\n2. b\n3. c\n4. d\n");
+
+        let old = list
+            .replace_item(0, ListItem::new("x", true, None))
+            .unwrap();
+        assert_eq!(old.pos(), 0);
+        assert!(list.remove_item(1).is_some());
+        assert_eq!(list_text(&list), "1. x\n2. c\n3. d\n");
+        assert_eq!(list.item_at(2).unwrap().pos(), 2);
+    }
+
+    #[test]
+    fn list_should_reject_indexes_out_of_bounds() {
+        let mut list =
+            List::with_items(vec![ListItem::new("a", false, None)], None);
+
+        assert!(list
+            .try_insert_item(2, ListItem::new("b", false, None))
+            .is_err());
+        assert!(list
+            .try_replace_item(1, ListItem::new("b", false, None))
+            .is_err());
+        assert!(list.remove_item(1).is_none());
+        assert!(list.remove_item(usize::MAX).is_none());
+        assert!(list.item_at(1).is_none());
+        assert_eq!(list_text(&list), "- a\n");
+    }
+
+    #[test]
+    fn list_item_should_support_setting_and_toggling_todo_status() {
+        let mut item = ListItem::new("task", false, None);
+        assert!(item.is_unordered());
+        assert_eq!(item.0.attributes.todo_status, None);
+
+        item.toggle_todo();
+        assert_eq!(
+            item.0.attributes.todo_status,
+            Some(v::ListItemTodoStatus::Incomplete)
+        );
+        item.toggle_todo();
+        assert_eq!(
+            item.0.attributes.todo_status,
+            Some(v::ListItemTodoStatus::Complete)
+        );
+        item.toggle_todo();
+        assert_eq!(
+            item.0.attributes.todo_status,
+            Some(v::ListItemTodoStatus::Incomplete)
+        );
+
+        item.set_todo_status(Some(ListItemTodoStatus::PartiallyComplete2));
+        item.toggle_todo();
+        assert_eq!(
+            item.0.attributes.todo_status,
+            Some(v::ListItemTodoStatus::Complete)
+        );
+
+        item.set_todo_status(Some(ListItemTodoStatus::Rejected));
+        assert!(item.attributes().is_todo_rejected());
+        item.set_todo_status(None);
+        assert_eq!(item.0.attributes.todo_status, None);
+    }
+
+    #[test]
+    fn list_item_should_be_numbered_if_ordered() {
+        let item = ListItem::new("a", true, None);
+        assert!(item.is_ordered());
+        assert_eq!(item.prefix(), "1.");
+
+        let item = ListItem::new("a", false, None);
+        assert_eq!(item.prefix(), "-");
+    }
+
+    #[test]
+    fn paragraph_should_contain_a_line_for_each_line_of_text() {
+        let paragraph = Paragraph::new("one\ntwo", None);
+        assert_eq!(paragraph.line_cnt(), 2);
+        assert_eq!(paragraph.to_str(), "one\ntwo");
+    }
+
+    #[test]
+    fn table_should_insert_and_remove_rows() {
+        let mut table = Table::from(v::Located::from(v::Table::new(
+            Vec::<(v::CellPos, v::Located<v::Cell>)>::new(),
+            false,
+        )));
+        table.insert_cells_at_row(0, vec![cell("c1"), cell("c2")]);
+        table
+            .try_insert_row(0, vec![cell("a1"), cell("a2")])
+            .unwrap();
+        table
+            .try_insert_row(1, vec![cell("b1"), cell("b2")])
+            .unwrap();
+        assert_eq!(table.row_cnt(), 3);
+        assert_eq!(table.col_cnt(), 2);
+        assert_eq!(cell_text(&table, 1, 0).as_deref(), Some("b1"));
+        assert_eq!(cell_text(&table, 2, 1).as_deref(), Some("c2"));
+
+        assert!(table.remove_row(1));
+        assert_eq!(table.row_cnt(), 2);
+        assert_eq!(cell_text(&table, 1, 0).as_deref(), Some("c1"));
+        assert_eq!(cell_text(&table, 2, 0), None);
+    }
+
+    #[test]
+    fn table_should_reject_rows_out_of_bounds() {
+        let mut table = Table::from(v::Located::from(v::Table::new(
+            vec![(v::CellPos { row: 0, col: 0 }, cell("a").0)],
+            false,
+        )));
+
+        assert_eq!(
+            table.try_insert_row(2, vec![cell("b")]).unwrap_err(),
+            "Index 2 is out of bounds for length 1"
+        );
+        assert!(!table.remove_row(1));
+        assert!(!table.remove_row(usize::MAX));
+        assert_eq!(table.row_cnt(), 1);
+        assert!(table.cell_at(usize::MAX, usize::MAX).is_none());
+    }
+
+    #[test]
+    fn page_should_render_html_using_config() {
+        let page = parse("some *bold* text\nnext line\n");
+        assert_eq!(
+            page.render_html(Default::default()).unwrap(),
+            "<p>some <strong id=\"bold\">bold</strong> text next line</p>\n"
+        );
+
+        let mut config = v::HtmlConfig::default();
+        config.paragraph.ignore_newline = false;
+        assert_eq!(
+            page.render_html(config).unwrap(),
+            "<p>some <strong id=\"bold\">bold</strong> text<br />next line</p>\n"
+        );
+    }
+
+    #[test]
+    fn page_should_render_html_page_using_template_of_config() {
+        let page = parse("%title Example\nsome text\n");
+        let html = page.render_html_page(Default::default()).unwrap();
+        assert!(html.contains("<title>Example</title>"), "{}", html);
+        assert!(html.contains("<p>some text</p>"), "{}", html);
+    }
+
+    #[test]
+    fn elements_should_render_vimwiki_using_config() {
+        let text = "= Header =\n\n- item\n";
+        let page = parse(text);
+        assert_eq!(page.render_vimwiki(Default::default()).unwrap(), text);
+
+        let header = page.element_at(0).unwrap().into_header().unwrap();
+        assert_eq!(
+            header.render_vimwiki(Default::default()).unwrap(),
+            "= Header =\n"
+        );
+        assert_eq!(
+            header.render_html(Default::default()).unwrap(),
+            "<h1 id=\"header\" class=\"header\"><a href=\"#header\">Header</a></h1>"
+        );
+    }
+
+    #[test]
+    fn element_at_offset_should_use_byte_offsets() {
+        // Each é is two bytes, so the bold text starts at byte 10 whereas it
+        // starts at UTF-16 code unit 6
+        let page = parse("éééé *b*\n");
+
+        let element = page.element_at_offset(10).unwrap();
+        assert!(matches!(
+            element.0.as_inner(),
+            v::Element::Inline(v::InlineElement::Text(x)) if x.as_str() == "b"
+        ));
+
+        let element = page.element_at_offset(6).unwrap();
+        assert!(matches!(
+            element.0.as_inner(),
+            v::Element::Inline(v::InlineElement::Text(x)) if x.as_str() == "éééé "
+        ));
+    }
+
+    #[test]
+    fn path_should_go_from_top_level_element_to_innermost_element() {
+        let page = parse("éééé *b*\n");
+
+        let path = page.path(10);
+        assert_eq!(path.len(), 3);
+        assert!(path[0].is_block());
+        assert!(path[2].is_inline());
+
+        assert!(page.path(100).is_empty());
+        assert!(page.element_at_offset(100).is_none());
+    }
+}
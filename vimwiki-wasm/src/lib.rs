@@ -1,11 +1,27 @@
 use vimwiki::{self as v, Language, ParseError};
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
 
 mod elements;
 pub use elements::*;
 
 mod utils;
 
+// Definitions for the plain object form of a page, generated from the
+// element model by the typescript test
+#[wasm_bindgen(typescript_custom_section)]
+const AST_TYPES: &'static str = include_str!("../ast.d.ts");
+
+#[wasm_bindgen]
+extern "C" {
+    /// Represents a page as a plain JavaScript object tree
+    #[wasm_bindgen(typescript_type = "ast.Page")]
+    pub type AstPage;
+
+    /// Represents the metadata of a page as a plain JavaScript object
+    #[wasm_bindgen(typescript_type = "ast.PageMetadata")]
+    pub type AstPageMetadata;
+}
+
 #[wasm_bindgen]
 pub fn parse_vimwiki_str(s: &str) -> Result<Page, JsValue> {
     let page_res: Result<v::Page, ParseError> =
@@ -16,3 +32,21 @@ pub fn parse_vimwiki_str(s: &str) -> Result<Page, JsValue> {
         Err(x) => Err(x.to_string().into()),
     }
 }
+
+/// Parses vimwiki text into a plain JavaScript object tree representing the
+/// page, where each element includes the region of text it occupies
+#[wasm_bindgen]
+pub fn parse_vimwiki_to_js(s: &str) -> Result<AstPage, JsValue> {
+    let page: v::Page = Language::from_vimwiki_str(s)
+        .parse()
+        .map_err(|x: ParseError| JsValue::from(x.to_string()))?;
+
+    utils::to_js(&page).map(JsCast::unchecked_into)
+}
+
+/// Creates a page from a plain JavaScript object tree, such as one produced
+/// by `parse_vimwiki_to_js`
+#[wasm_bindgen]
+pub fn page_from_js(obj: AstPage) -> Result<Page, JsValue> {
+    utils::from_js::<v::Page<'static>>(obj.into()).map(Page::from)
+}
@@ -1,3 +1,6 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::PathBuf;
+use vimwiki as v;
 use wasm_bindgen::{convert::FromWasmAbi, prelude::*};
 
 // From https://github.com/rustwasm/wasm-bindgen/issues/2231#issuecomment-656293288
@@ -16,3 +19,122 @@ pub fn cast_value<T: FromWasmAbi<Abi = u32>>(
         Err(JsValue::NULL)
     }
 }
+
+/// Converts a value into a plain JavaScript value, where maps become objects
+/// rather than `Map` instances so the result can be traversed like JSON
+pub fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
+
+/// Converts a plain JavaScript value into a value
+pub fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsValue> {
+    serde_wasm_bindgen::from_value(value).map_err(JsValue::from)
+}
+
+/// Represents the runtime settings of an html config that can be provided
+/// from JavaScript, which are otherwise skipped when deserializing
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct JsHtmlRuntimeConfig {
+    page: Option<PathBuf>,
+    wiki_index: Option<usize>,
+}
+
+/// Represents an html config provided from JavaScript
+#[derive(Deserialize)]
+struct JsHtmlConfig {
+    #[serde(flatten)]
+    config: v::HtmlConfig,
+
+    #[serde(default)]
+    runtime: JsHtmlRuntimeConfig,
+}
+
+impl JsHtmlConfig {
+    /// Converts into an html config, determining the index of the wiki from
+    /// the path of the page when not provided
+    fn into_html_config(self) -> v::HtmlConfig {
+        let Self {
+            mut config,
+            runtime,
+        } = self;
+
+        config.runtime.wiki_index = runtime.wiki_index;
+        if let Some(page) = runtime.page {
+            if config.runtime.wiki_index.is_none() {
+                config.runtime.wiki_index =
+                    config.find_wiki_index_by_path(&page);
+            }
+            config.runtime.page = page;
+        }
+
+        config
+    }
+}
+
+/// Reads an html config from a JS object, defaulting to the standard config
+/// if not provided. Supports a `runtime` object with the `page` being
+/// processed and the `wiki_index` of its wiki, which is determined from the
+/// page's path when not provided.
+pub fn html_config_from_js(config: &JsValue) -> Result<v::HtmlConfig, JsValue> {
+    if config.is_undefined() || config.is_null() {
+        return Ok(Default::default());
+    }
+
+    from_js::<JsHtmlConfig>(config.clone()).map(JsHtmlConfig::into_html_config)
+}
+
+/// Reads a vimwiki config from a JS object, defaulting to the standard
+/// config if not provided
+pub fn vimwiki_config_from_js(
+    config: &JsValue,
+) -> Result<v::VimwikiConfig, JsValue> {
+    if config.is_undefined() || config.is_null() {
+        return Ok(Default::default());
+    }
+
+    from_js(config.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn html_config(value: serde_json::Value) -> v::HtmlConfig {
+        serde_json::from_value::<JsHtmlConfig>(value)
+            .unwrap()
+            .into_html_config()
+    }
+
+    #[test]
+    fn html_config_should_support_partial_settings() {
+        let config = html_config(json!({
+            "paragraph": { "ignore_newline": false },
+        }));
+        assert!(!config.paragraph.ignore_newline);
+        assert!(config.wikis.is_empty());
+        assert_eq!(config.runtime.wiki_index, None);
+    }
+
+    #[test]
+    fn html_config_should_find_wiki_of_runtime_page_if_index_not_provided() {
+        let config = html_config(json!({
+            "wikis": [{ "path": "/a" }, { "path": "/b" }],
+            "runtime": { "page": "/b/notes/page.wiki" },
+        }));
+        assert_eq!(config.runtime.wiki_index, Some(1));
+        assert_eq!(config.runtime.page, PathBuf::from("/b/notes/page.wiki"));
+    }
+
+    #[test]
+    fn html_config_should_keep_wiki_index_of_runtime_if_provided() {
+        let config = html_config(json!({
+            "wikis": [{ "path": "/a" }, { "path": "/b" }],
+            "runtime": { "page": "/b/page.wiki", "wiki_index": 0 },
+        }));
+        assert_eq!(config.runtime.wiki_index, Some(0));
+    }
+}
@@ -0,0 +1,71 @@
+use std::{fs, path::PathBuf};
+use vimwiki::{Description, ListItemSuffix, Placeholder};
+
+/// Set to regenerate the definitions instead of comparing against them
+const UPDATE_ENV_VAR: &str = "VIMWIKI_UPDATE_TYPESCRIPT";
+
+fn definitions_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("ast.d.ts")
+}
+
+/// Produces the contents of ast.d.ts, placing the definitions within a
+/// namespace so they do not collide with the classes of the same name
+fn make_definitions() -> String {
+    let definitions = vimwiki::typescript::definitions()
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {}", line)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    format!(
+        "// Generated from the vimwiki element model, do not edit by hand!\n\
+         // Run `{}=1 cargo test -p vimwiki-wasm` to regenerate.\n\
+         export namespace ast {{\n{}\n}}\n",
+        UPDATE_ENV_VAR, definitions,
+    )
+}
+
+#[test]
+fn definitions_should_match_element_model() {
+    let expected = make_definitions();
+    let path = definitions_path();
+
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        fs::write(&path, &expected).unwrap();
+    }
+
+    let actual = fs::read_to_string(&path).unwrap();
+    assert!(
+        actual == expected,
+        "{:?} is out of date, run `{}=1 cargo test -p vimwiki-wasm`",
+        path,
+        UPDATE_ENV_VAR,
+    );
+}
+
+#[test]
+fn definitions_should_declare_serialized_type_tags() {
+    let definitions = vimwiki::typescript::definitions();
+    for (name, value) in [
+        ("Description", serde_json::to_value(Description::from("d"))),
+        (
+            "ListItemSuffix",
+            serde_json::to_value(ListItemSuffix::Paren),
+        ),
+        ("Placeholder", serde_json::to_value(Placeholder::NoHtml)),
+    ] {
+        let prefix = format!("export type {} = ", name);
+        let decl = definitions
+            .lines()
+            .find(|line| line.starts_with(&prefix))
+            .unwrap();
+        let tag = format!("\"type\": {}", value.unwrap()["type"]);
+        assert!(decl.contains(&tag), "{} is missing {}", name, tag);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::error::{Error, Result};
+use proc_macro2::{Span, TokenTree};
+use quote::ToTokens;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Expr, ExprLit, ExprUnary, Ident, Lit, Token, UnOp,
+};
+use vimwiki_core::{BlockElement, HtmlConfig, Page, Placeholder};
+
+/// Represents a single inline html config setting in the form of
+/// `path.to.setting = value`
+pub struct HtmlSetting {
+    pub path: Punctuated<Ident, Token![.]>,
+    pub value: Expr,
+}
+
+impl Parse for HtmlSetting {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = Punctuated::parse_separated_nonempty(input)?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(Self { path, value })
+    }
+}
+
+/// The arguments expected by the html macros, being a string literal of
+/// vimwiki followed by optional html config settings such as
+/// `paragraph.ignore_newline = false`
+pub struct HtmlArgs {
+    pub input: TokenTree,
+    pub settings: Vec<HtmlSetting>,
+}
+
+impl Parse for HtmlArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lit: Lit = input.parse()?;
+        let mut settings = Vec::new();
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            settings.push(input.parse()?);
+        }
+
+        Ok(Self {
+            input: lit.into_token_stream().into_iter().next().ok_or_else(
+                || syn::Error::new(Span::call_site(), "missing string literal"),
+            )?,
+            settings,
+        })
+    }
+}
+
+impl HtmlArgs {
+    /// Builds an html config from the default config with each of the
+    /// settings applied, using the same field names as the config file
+    pub fn to_config(&self) -> Result<HtmlConfig> {
+        let mut root = toml::value::Table::new();
+
+        for setting in self.settings.iter() {
+            let mut table = &mut root;
+            let mut path = setting.path.iter().peekable();
+            while let Some(ident) = path.next() {
+                let key = ident.to_string();
+                if path.peek().is_none() {
+                    table.insert(key, expr_to_value(&setting.value)?);
+                    break;
+                }
+
+                let value = table.entry(key).or_insert_with(|| {
+                    toml::Value::Table(toml::value::Table::new())
+                });
+                table = match value {
+                    toml::Value::Table(x) => x,
+                    _ => {
+                        return Err(Error::new(
+                            ident.span(),
+                            "setting was already assigned a value",
+                        ))
+                    }
+                };
+            }
+        }
+
+        toml::Value::Table(root).try_into().map_err(|x| {
+            let span = self
+                .settings
+                .first()
+                .map(|x| x.path.span())
+                .unwrap_or_else(Span::call_site);
+            Error::new(span, &format!("invalid html config: {}", x))
+        })
+    }
+}
+
+/// Returns true if the page has a `%date` placeholder
+pub fn has_date(page: &Page<'_>) -> bool {
+    page.elements().iter().any(|x| {
+        matches!(
+            x.as_inner(),
+            BlockElement::Placeholder(Placeholder::Date(_))
+        )
+    })
+}
+
+/// Converts a literal expression, or array of literal expressions, into a
+/// toml value that can be deserialized into a config
+fn expr_to_value(expr: &Expr) -> Result<toml::Value> {
+    let invalid = || {
+        Error::new(
+            expr.span(),
+            "setting must be a literal or an array of literals",
+        )
+    };
+
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(x) => Ok(toml::Value::String(x.value())),
+            Lit::Bool(x) => Ok(toml::Value::Boolean(x.value)),
+            Lit::Int(x) => x
+                .base10_parse()
+                .map(toml::Value::Integer)
+                .map_err(|x| Error::new(x.span(), &x.to_string())),
+            Lit::Float(x) => x
+                .base10_parse()
+                .map(toml::Value::Float)
+                .map_err(|x| Error::new(x.span(), &x.to_string())),
+            _ => Err(invalid()),
+        },
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => match expr_to_value(expr)? {
+            toml::Value::Integer(x) => Ok(toml::Value::Integer(-x)),
+            toml::Value::Float(x) => Ok(toml::Value::Float(-x)),
+            _ => Err(invalid()),
+        },
+        Expr::Array(x) => x
+            .elems
+            .iter()
+            .map(expr_to_value)
+            .collect::<Result<Vec<_>>>()
+            .map(toml::Value::Array),
+        _ => Err(invalid()),
+    }
+}
@@ -15,10 +15,16 @@ use args::FormatArgs;
 mod formatter;
 use formatter::Formatter;
 
+#[cfg(feature = "html")]
+mod html;
+
 mod utils;
 
 macro_rules! impl_macro {
     ($name:ident, $from_str:ident, $type:ty, $raw_mode:expr, $verbatim:expr) => {
+        impl_macro!($name, $from_str, $type, $raw_mode, $verbatim, false);
+    };
+    ($name:ident, $from_str:ident, $type:ty, $raw_mode:expr, $verbatim:expr, $include:expr) => {
         #[proc_macro]
         pub fn $name(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             let input_2 = input.clone();
@@ -38,14 +44,19 @@ macro_rules! impl_macro {
                 // Validate we did indeed only get a single argument
                 // utils::require_empty_or_trailing_comma(&mut input)?;
 
-                // Load our input into a string
-                let input = utils::input_to_string(first, $raw_mode)?;
+                // Load our input into a string, either from the literal
+                // itself or from the file whose path is the literal
+                let input = if $include {
+                    utils::file_to_string(first)?
+                } else {
+                    utils::input_to_string(first, $raw_mode)?
+                };
 
                 // Perform the action of parsing our language into a
                 // structured format
-                let element: $type = Language::$from_str(&input)
+                let element: $type = Language::$from_str(&input.text)
                     .parse()
-                    .map_err(|x| Error::new(Span::call_site(), &format!("{}", x)))?;
+                    .map_err(|x| input.parse_error(&x))?;
 
                 // Stuff our structure language into a proper token stream
                 let ctx = TokenizeContext {
@@ -54,6 +65,17 @@ macro_rules! impl_macro {
                 };
                 let mut stream = TokenStream::new();
                 element.tokenize(&ctx, &mut stream);
+
+                // Include the file's bytes (unused) so that the compiler
+                // tracks it and rebuilds whenever the file changes
+                if let Some(path) = input.path() {
+                    let path = path.to_string_lossy();
+                    stream = quote::quote! {{
+                        const _: &[u8] = ::core::include_bytes!(#path);
+                        #stream
+                    }};
+                }
+
                 Ok(stream)
             }
 
@@ -69,12 +91,14 @@ macro_rules! impl_macro {
     };
 }
 
-/// Macro that generates four macros in the form of
+/// Macro that generates six macros in the form of
 ///
 /// 1. vimwiki_${suffix}
 /// 2. vimwiki_${suffix}_raw
 /// 3. vimwiki_${suffix}_format
 /// 4. vimwiki_${suffix}_raw_format
+/// 5. include_vimwiki_${suffix}
+/// 6. include_vimwiki_${suffix}_format
 ///
 /// All convert the given text to the specified vimwiki type at compile time.
 ///
@@ -87,6 +111,12 @@ macro_rules! impl_macro {
 /// format!, println!, and write! can inject content. Note that formatting
 /// with {} currently does not support lists and definition lists as the order
 /// cannot be guaranteed. Instead, use named or numbered injection instead.
+///
+/// The include versions read the text from the file at the given path,
+/// relative to the directory containing the manifest of the crate being
+/// compiled. Like the raw versions, only leading and trailing blank lines
+/// are removed. The file is tracked by the compiler so that changes to it
+/// trigger a rebuild.
 macro_rules! impl_macro_vimwiki {
     ($suffix:ident, $type:ty) => {
         paste! {
@@ -94,6 +124,8 @@ macro_rules! impl_macro_vimwiki {
             impl_macro!([<vimwiki_ $suffix _raw>], from_vimwiki_str, $type, true, true);
             impl_macro!([<vimwiki_ $suffix _format>], from_vimwiki_str, $type, false, false);
             impl_macro!([<vimwiki_ $suffix _raw_format>], from_vimwiki_str, $type, true, false);
+            impl_macro!([<include_vimwiki_ $suffix>], from_vimwiki_str, $type, true, true, true);
+            impl_macro!([<include_vimwiki_ $suffix _format>], from_vimwiki_str, $type, true, false, true);
         }
     };
 }
@@ -102,6 +134,15 @@ macro_rules! impl_macro_vimwiki {
 // Implement macros for vimwiki
 ///////////////////////////////////////////////////////////////////////////////
 impl_macro_vimwiki!(page, Page);
+impl_macro!(include_vimwiki, from_vimwiki_str, Page, true, true, true);
+impl_macro!(
+    include_vimwiki_format,
+    from_vimwiki_str,
+    Page,
+    true,
+    false,
+    true
+);
 impl_macro_vimwiki!(block_element, Located<BlockElement>);
 impl_macro_vimwiki!(inline_element_container, Located<InlineElementContainer>);
 impl_macro_vimwiki!(inline_element, Located<InlineElement>);
@@ -126,3 +167,114 @@ impl_macro_vimwiki!(tags, Located<Tags>);
 impl_macro_vimwiki!(decorated_text, Located<DecoratedText>);
 impl_macro_vimwiki!(keyword, Located<Keyword>);
 impl_macro_vimwiki!(text, Located<Text>);
+
+/// Macro that generates a macro that converts the given text into a page
+/// at compile time and renders it into a `&'static str` of html using the
+/// provided render function (`to_html_string_with_report` or
+/// `to_html_page_with_report`).
+///
+/// After the string literal, settings of the `HtmlConfig` can be provided
+/// using the same names as the config file, which are applied on top of the
+/// default config:
+///
+/// ```ignore
+/// vimwiki_html!("some text", paragraph.ignore_newline = false);
+/// ```
+///
+/// When rendering a page, the text must include a `%date` placeholder as
+/// the page would otherwise be dated when it was compiled, and any template
+/// files that are loaded are tracked so that changing them causes a rebuild
+#[cfg(feature = "html")]
+macro_rules! impl_html_macro {
+    ($name:ident, $raw_mode:expr, $render:path, $is_page:expr) => {
+        #[proc_macro]
+        pub fn $name(
+            input: proc_macro::TokenStream,
+        ) -> proc_macro::TokenStream {
+            let args = parse_macro_input!(input as html::HtmlArgs);
+
+            fn try_expand(args: html::HtmlArgs) -> Result<TokenStream> {
+                let config = args.to_config()?;
+
+                // Load our input into a string
+                let span = args.input.span();
+                let input = utils::input_to_string(args.input, $raw_mode)?;
+
+                let page: Page = Language::from_vimwiki_str(&input.text)
+                    .parse()
+                    .map_err(|x| input.parse_error(&x))?;
+
+                if $is_page && !html::has_date(&page) {
+                    return Err(Error::new(
+                        span,
+                        "page must have a %date placeholder, otherwise it \
+                        would be dated when compiled",
+                    ));
+                }
+
+                // Render our page into html and produce a string literal
+                let (html, report) = $render(&page, config)
+                    .map_err(|x| Error::new(span, &format!("{}", x)))?;
+                let html = proc_macro2::Literal::string(&html);
+
+                // Include the bytes (unused) of each template that was
+                // loaded so that the compiler tracks them and rebuilds
+                // whenever they change
+                if report.templates.is_empty() {
+                    return Ok(TokenStream::from(
+                        proc_macro2::TokenTree::Literal(html),
+                    ));
+                }
+                let paths = report.templates.iter().map(|path| {
+                    std::fs::canonicalize(path)
+                        .unwrap_or_else(|_| path.to_path_buf())
+                        .to_string_lossy()
+                        .to_string()
+                });
+                Ok(quote::quote! {{
+                    #(const _: &[u8] = ::core::include_bytes!(#paths);)*
+                    #html
+                }})
+            }
+
+            let output = match try_expand(args) {
+                Ok(tokens) => tokens,
+                Err(err) => err.to_compile_error(),
+            };
+
+            proc_macro::TokenStream::from(output)
+        }
+    };
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Implement macros for html
+///////////////////////////////////////////////////////////////////////////////
+#[cfg(feature = "html")]
+impl_html_macro!(
+    vimwiki_html,
+    false,
+    ToHtmlString::to_html_string_with_report,
+    false
+);
+#[cfg(feature = "html")]
+impl_html_macro!(
+    vimwiki_html_raw,
+    true,
+    ToHtmlString::to_html_string_with_report,
+    false
+);
+#[cfg(feature = "html")]
+impl_html_macro!(
+    vimwiki_html_page,
+    false,
+    ToHtmlPage::to_html_page_with_report,
+    true
+);
+#[cfg(feature = "html")]
+impl_html_macro!(
+    vimwiki_html_page_raw,
+    true,
+    ToHtmlPage::to_html_page_with_report,
+    true
+);
@@ -1,13 +1,174 @@
 use crate::error::{Error, Result};
-use proc_macro2::{Span, TokenTree};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use std::path::{Path, PathBuf};
+use syn::LitStr;
+use vimwiki_core::ParseError;
 
-/// Converts a token tree that is a string or byte string into a Rust string
-/// instance. Removes any blank lines (whitespace only) before and after
-/// lines with content.
+/// Represents where the text of some input was loaded from
+enum Source {
+    /// Text is the content of a string literal, starting at the given byte
+    /// offset within the literal's representation (after `r#"` and such)
+    Literal {
+        token: TokenTree,
+        content_offset: usize,
+    },
+
+    /// Text is the content of a file whose path was the given literal
+    File { path: PathBuf, span: Span },
+}
+
+/// Represents the text provided to a macro along with what is needed to map
+/// an offset within the text back to its original location
+pub struct Input {
+    pub text: String,
+    source: Source,
+
+    /// Original content before blank lines and indentation were removed
+    content: String,
+
+    /// Offset of the start of each line within the text paired with the
+    /// offset of that same position within the original content
+    lines: Vec<(usize, usize)>,
+}
+
+impl Input {
+    /// Returns the path of the file the text was loaded from, if any
+    pub fn path(&self) -> Option<&Path> {
+        match &self.source {
+            Source::File { path, .. } => Some(path),
+            Source::Literal { .. } => None,
+        }
+    }
+
+    /// Converts an offset within the text to the offset within the original
+    /// content
+    fn content_offset(&self, offset: usize) -> usize {
+        let i = match self.lines.binary_search_by_key(&offset, |x| x.0) {
+            Ok(i) => i,
+            Err(i) => i.saturating_sub(1),
+        };
+
+        self.lines
+            .get(i)
+            .map_or(0, |(start, original)| original + (offset - start))
+            .min(self.content.len())
+    }
+
+    /// Converts an offset within the text to a 1-based line and column
+    /// within the source file, or within the content if the source
+    /// location is not available
+    fn line_column(&self, offset: usize) -> (usize, usize) {
+        let offset = self.content_offset(offset);
+        let before = self.content.get(..offset).unwrap_or_default();
+        let line = before.matches('\n').count();
+        let column = before
+            .rfind('\n')
+            .map_or(before, |i| &before[i + 1..])
+            .chars()
+            .count();
+
+        match &self.source {
+            Source::Literal {
+                token,
+                content_offset,
+            } => {
+                let start = token.span().start();
+                if start.line == 0 {
+                    (line + 1, column + 1)
+                } else if line == 0 {
+                    (start.line, start.column + content_offset + column + 1)
+                } else {
+                    (start.line + line, column + 1)
+                }
+            }
+            Source::File { .. } => (line + 1, column + 1),
+        }
+    }
+
+    /// Returns the span covering the character at the offset within the
+    /// text, falling back to the span of the entire literal when the
+    /// compiler does not support pointing within a literal
+    fn span(&self, offset: usize) -> Span {
+        match &self.source {
+            Source::Literal {
+                token,
+                content_offset,
+            } => {
+                let start = content_offset + self.content_offset(offset);
+                let len = self.content[start - content_offset..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+                match token {
+                    TokenTree::Literal(x) => x.subspan(start..start + len),
+                    _ => None,
+                }
+                .unwrap_or_else(|| token.span())
+            }
+            Source::File { span, .. } => *span,
+        }
+    }
+
+    /// Produces an error pointing at the location within the input where
+    /// parsing failed, listing the chain of contexts being parsed as notes
+    pub fn parse_error(&self, err: &ParseError) -> Error {
+        let mut chain = vec![err];
+        while let Some(next) = chain.last().and_then(|x| x.next()) {
+            chain.push(next);
+        }
+
+        // The innermost error is the most specific as to what failed
+        let inner = chain.last().copied().unwrap_or(err);
+        let (line, column) = self.line_column(inner.offset());
+        let location = match &self.source {
+            Source::Literal { .. } => {
+                format!("line {}, column {}", line, column)
+            }
+            Source::File { path, .. } => {
+                format!("{}:{}:{}", path.display(), line, column)
+            }
+        };
+
+        let mut msg = format!(
+            "failed to parse vimwiki: {} at {}",
+            inner.context(),
+            location
+        );
+
+        // Show the offending line of the input with a marker beneath the
+        // position where parsing failed
+        let start = self.text[..inner.offset().min(self.text.len())]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let text_line = self.text[start..].lines().next().unwrap_or_default();
+        let marker = self.text[start..inner.offset().min(self.text.len())]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        msg.push_str(&format!("\n  | {}\n  | {}^", text_line, marker));
+
+        for outer in chain.iter().rev().skip(1) {
+            let (line, column) = self.line_column(outer.offset());
+            msg.push_str(&format!(
+                "\n  = note: while parsing {} starting at line {}, column {}",
+                outer.context(),
+                line,
+                column
+            ));
+        }
+
+        let span = self.span(inner.offset());
+        Error::new(span, &msg)
+    }
+}
+
+/// Converts a token tree that is a string or byte string into input text.
+/// Removes any blank lines (whitespace only) before and after lines with
+/// content.
 ///
 /// If `raw_mode` is specified, will leave lines unaltered, otherwise will
 /// find the minimum indentation level and remove that from all lines.
-pub fn input_to_string(token: TokenTree, raw_mode: bool) -> Result<String> {
+pub fn input_to_string(token: TokenTree, raw_mode: bool) -> Result<Input> {
     let repr = token.to_string();
     let repr = repr.trim();
     let is_string = repr.starts_with('"') || repr.starts_with('r');
@@ -23,8 +184,57 @@ pub fn input_to_string(token: TokenTree, raw_mode: bool) -> Result<String> {
     // Get the raw string as it appears
     let begin = repr.find('"').unwrap() + 1;
     let end = repr.rfind('"').unwrap();
-    let s = repr[begin..end].to_string();
+    let content = repr[begin..end].to_string();
+    let (text, lines) = normalize_lines(&content, raw_mode)?;
+
+    Ok(Input {
+        text,
+        source: Source::Literal {
+            token,
+            content_offset: begin,
+        },
+        content,
+        lines,
+    })
+}
+
+/// Converts a token tree that is a string literal into a path relative to
+/// the manifest directory of the crate invoking the macro, and reads the
+/// contents of the file at that path, removing any blank lines before and
+/// after lines with content and leaving all other lines unaltered
+pub fn file_to_string(token: TokenTree) -> Result<Input> {
+    let span = token.span();
+    let lit: LitStr = syn::parse2(TokenStream::from(token)).map_err(|_| {
+        Error::new(span, "argument must be a single string literal path")
+    })?;
+
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").ok_or_else(|| {
+        Error::new(span, "CARGO_MANIFEST_DIR is not set, unable to find file")
+    })?;
+    let path = Path::new(&root).join(lit.value());
+
+    let content = std::fs::read_to_string(&path).map_err(|x| {
+        Error::new(span, &format!("failed to read {:?}: {}", path, x))
+    })?;
+    let (text, lines) = normalize_lines(&content, true)?;
 
+    Ok(Input {
+        text,
+        source: Source::File { path, span },
+        content,
+        lines,
+    })
+}
+
+/// Removes any blank lines (whitespace only) before and after lines with
+/// content, and if not in raw mode, removes the minimum indentation level
+/// from all lines. Alongside the resulting text, returns the offset of each
+/// line within the text paired with the offset of the same position within
+/// the original string.
+fn normalize_lines(
+    s: &str,
+    raw_mode: bool,
+) -> Result<(String, Vec<(usize, usize)>)> {
     // Determine which lines in the macro are blank and what each line's
     // indentation level is, which will be used if not in raw mode
     let mut line_data = s
@@ -70,23 +280,76 @@ pub fn input_to_string(token: TokenTree, raw_mode: bool) -> Result<String> {
 
     // Process the lines back into a single string, either by doing nothing
     // to them or removing a set minimum indentation from all
-    let lines =
-        if raw_mode {
-            line_data
-                .iter()
-                .map(|x| x.2)
-                .collect::<Vec<&str>>()
-                .join("\n")
-        } else {
-            let min_indentation = line_data
-                .iter()
-                .fold(usize::MAX, |acc, x| if x.1 < acc { x.1 } else { acc });
-            line_data
-                .iter()
-                .map(|x| &x.2[min_indentation..])
-                .collect::<Vec<&str>>()
-                .join("\n")
-        };
+    let min_indentation = if raw_mode {
+        0
+    } else {
+        line_data
+            .iter()
+            .fold(usize::MAX, |acc, x| if x.1 < acc { x.1 } else { acc })
+    };
+
+    let mut text = String::new();
+    let mut lines = Vec::new();
+    for (i, (_, _, line)) in line_data.iter().enumerate() {
+        if i > 0 {
+            text.push('\n');
+        }
+
+        // Track where this line starts in both the text and original string
+        let original = line.as_ptr() as usize - s.as_ptr() as usize;
+        lines.push((text.len(), original + min_indentation));
+        text.push_str(&line[min_indentation..]);
+    }
+
+    Ok((text, lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use vimwiki_core::{Header, Language, Located};
+
+    fn literal(s: &str) -> TokenTree {
+        TokenStream::from_str(s)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+    }
+
+    fn load(s: &str, raw_mode: bool) -> Input {
+        input_to_string(literal(s), raw_mode)
+            .unwrap_or_else(|_| panic!("failed to load {}", s))
+    }
 
-    Ok(lines)
+    #[test]
+    fn input_to_string_should_map_offsets_back_to_the_literal() {
+        let input = load("r#\"\n\n    = a =\n      b\n  \"#", false);
+        assert_eq!(input.text, "= a =\n  b");
+
+        // Line 1 is the one containing the opening quote of the literal
+        assert_eq!(input.line_column(0), (3, 5));
+        assert_eq!(input.line_column(8), (4, 7));
+    }
+
+    #[test]
+    fn input_to_string_should_map_offsets_on_the_first_line() {
+        let input = load("\"= a\"", true);
+        assert_eq!(input.line_column(0), (1, 2));
+        assert_eq!(input.line_column(3), (1, 5));
+    }
+
+    #[test]
+    fn parse_error_should_point_at_location_of_failure() {
+        let input = load("\"= a\"", true);
+        let err = Language::from_vimwiki_str(&input.text)
+            .parse::<Located<Header>>()
+            .unwrap_err();
+
+        let msg = input.parse_error(&err).to_compile_error().to_string();
+        assert!(msg.contains("at line 1, column 3"), "{}", msg);
+        assert!(msg.contains("| = a"), "{}", msg);
+        assert!(msg.contains("note: while parsing Header"), "{}", msg);
+    }
 }
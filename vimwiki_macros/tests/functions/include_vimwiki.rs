@@ -0,0 +1,38 @@
+use vimwiki_macros::*;
+
+#[test]
+fn include_vimwiki() {
+    let x = include_vimwiki!("tests/fixtures/page.wiki");
+    let y = vimwiki_page_raw!(
+        r#"= Header =
+
+Some {} text
+  with *bold* content"#
+    );
+    assert_eq!(x, y);
+}
+
+#[test]
+fn include_vimwiki_format() {
+    let x = include_vimwiki_format!("tests/fixtures/page.wiki", "cool");
+    let y = vimwiki_page_raw!(
+        r#"= Header =
+
+Some cool text
+  with *bold* content"#
+    );
+    assert_eq!(x, y);
+}
+
+#[test]
+fn include_vimwiki_header() {
+    let x = include_vimwiki_header_format!(
+        "tests/fixtures/header.wiki",
+        name = "Title"
+    );
+    assert_eq!(x.into_inner(), vimwiki_header!("= Title =").into_inner());
+
+    let x = include_vimwiki_header!("tests/fixtures/header.wiki");
+    assert_eq!(x.level, 1);
+    assert_eq!(x.content.to_string(), "{name}");
+}
@@ -1,2 +1,5 @@
+mod include_vimwiki;
 mod vimwiki;
 mod vimwiki_format;
+#[cfg(feature = "html")]
+mod vimwiki_html;
@@ -0,0 +1,60 @@
+use vimwiki_macros::*;
+
+#[test]
+fn vimwiki_html() {
+    let x: &'static str = vimwiki_html!("some *bold* text");
+    assert_eq!(x, "<p>some <strong id=\"bold\">bold</strong> text</p>\n");
+}
+
+#[test]
+fn vimwiki_html_should_remove_indentation() {
+    let x = vimwiki_html! {r#"
+        = Header =
+        some text
+    "#};
+    assert_eq!(
+        x,
+        vimwiki_html_raw!(
+            r#"= Header =
+some text"#
+        )
+    );
+}
+
+#[test]
+fn vimwiki_html_should_support_inline_config() {
+    let x = vimwiki_html! {r#"
+        some
+        text
+    "#};
+    assert_eq!(x, "<p>some text</p>\n");
+
+    let x = vimwiki_html!(
+        r#"
+        some
+        text
+        "#,
+        paragraph.ignore_newline = false,
+    );
+    assert_eq!(x, "<p>some<br />text</p>\n");
+
+    let x = vimwiki_html!(
+        "%% comment",
+        comment.include = true,
+        header.table_of_contents = "Contents",
+    );
+    assert_eq!(x, "<!--  comment -->\n");
+}
+
+#[test]
+fn vimwiki_html_page() {
+    let x = vimwiki_html_page!(
+        r#"
+        %title My Title
+        %date 2021-06-17
+        some text
+        "#,
+        template.text = "<title>%title%</title>%date%%content%",
+    );
+    assert_eq!(x, "<title>My Title</title>2021-06-17\n\n<p>some text</p>\n");
+}
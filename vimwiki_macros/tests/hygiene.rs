@@ -6,6 +6,8 @@ use ::vimwiki_macros::*;
 #[test]
 fn hygiene_passes_for_vimwiki() {
     let _ = vimwiki_page!("some text");
+    let _ = include_vimwiki!("tests/fixtures/page.wiki");
+    let _ = include_vimwiki_header!("tests/fixtures/header.wiki");
     let _ = vimwiki_block_element!("some text");
     let _ = vimwiki_inline_element_container!("some text");
     let _ = vimwiki_inline_element!("some text");